@@ -21,19 +21,26 @@ pub use interactive::Interactive;
 pub use tests::Tests;
 /// Script runner mode
 pub use runner::Runner;
+/// Filesystem module loader
+pub use loader::FileLoader;
 mod interactive;
 mod tests;
 mod runner;
+mod loader;
 /// The main function
 pub fn main() {
     let opts = [
         getopts::optflag("h", "help", "Show this message"),
         getopts::optflag("t", "tests", "Run tests"),
         getopts::optflag("i", "interactive", "Run in interactive mode"),
+        getopts::optflag("m", "module", "Run the script as a module, which can import other modules"),
         getopts::optopt("s", "source-code", "Run some Javascript code", "The path to the source code")
     ];
     let m = getopts::getopts(std::os::args().as_slice(), opts).ok().expect("Could not parse arguments");
     match m.opt_str("s") {
+        Some(path) if m.opt_present("m") => {
+            Runner::new(path).run_module()
+        },
         Some(path) => {
             Runner::new(path).run()
         },
@@ -46,6 +53,9 @@ pub fn main() {
         None if m.opt_present("i") || (m.free.len() >= 2 && m.free[1].as_slice() == "interactive") => {
             Interactive::new().run();
         },
+        None if m.free.len() >= 2 && m.opt_present("m") => {
+            Runner::new(m.free[1].clone()).run_module();
+        },
         None if m.free.len() >= 2 => {
             Runner::new(m.free[1].clone()).run();
         },
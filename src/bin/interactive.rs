@@ -1,13 +1,11 @@
 use js::back::compiler::JitCompiler;
 use js::back::executor::JitExecutor;
-use js::front::run::compiler::Compiler;
 use js::front::run::executor::Executor;
-use js::syntax::lexer::Lexer;
-use js::syntax::parser::Parser;
+use js::syntax::error::parse_str;
 use jit::Context;
 use std::default::Default;
 use std::io::stdio::{stdin, StdReader};
-use std::io::{BufReader, BufferedReader};
+use std::io::BufferedReader;
 /// An interactive command-line mode
 pub struct Interactive<'a> {
     context: Context<'a>,
@@ -32,18 +30,20 @@ impl<'a> Interactive<'a> {
         loop {
             let line = self.input.read_line().unwrap();
             debug!("Now parsing line {}", line);
-            let line_bytes = line.as_bytes();
-            debug!("Now lexing...");
-            let mut lexer = Lexer::new(BufferedReader::new(BufReader::new(line_bytes)));
-            lexer.lex().unwrap();
-            let tokens = lexer.tokens;
-            debug!("Lexed into tokens: {}", tokens);
-            debug!("Now parsing...");
-            let expr = Parser::new(tokens).parse_all().unwrap();
+            debug!("Now lexing and parsing...");
+            let expr = match parse_str(line.as_slice()) {
+                Ok(expr) => expr,
+                Err(err) => {
+                    println!("{}", err);
+                    print!("> ");
+                    continue;
+                }
+            };
             debug!("Parsed into expression: {}", expr);
             debug!("Now compiling");
             let compiler = JitCompiler::new(&self.context);
-            let compiled = compiler.compile(&expr);
+            let compiled = compiler.compile_script(&expr);
+            compiler.finish();
             debug!("Now executing");
             match self.executor.execute(&compiled) {
                 Ok(v) =>
@@ -0,0 +1,28 @@
+use js::front::run::module::ModuleLoader;
+use std::io::File;
+use std::os;
+/// A module loader that loads modules from files, resolving specifiers relative to the file of the
+/// module that imports them
+pub struct FileLoader;
+impl ModuleLoader for FileLoader {
+    fn resolve(&self, specifier:&str, referrer:Option<&str>) -> Result<String, String> {
+        let mut path = match referrer {
+            Some(referrer) => Path::new(referrer).dir_path().join(specifier),
+            None => os::getcwd().join(specifier)
+        };
+        // the extension can be left out of a specifier
+        if !path.is_file() && path.extension().is_none() {
+            path.set_extension("js");
+        }
+        if !path.is_file() {
+            return Err(format!("{} does not exist", path.display()));
+        }
+        match path.as_str() {
+            Some(key) => Ok(key.into_string()),
+            None => Err(format!("{} is not a valid UTF-8 path", path.display()))
+        }
+    }
+    fn load(&self, key:&str) -> Result<String, String> {
+        File::open(&Path::new(key)).read_to_string().map_err(|err| err.to_string())
+    }
+}
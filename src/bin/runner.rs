@@ -1,13 +1,17 @@
 use js::back::compiler::JitCompiler;
 use js::back::executor::JitExecutor;
-use js::front::run::compiler::Compiler;
-use js::front::run::executor::Executor;
+use js::front::run::executor::{Executor, ExecutorConfig};
+use js::front::run::module::ModuleMap;
+use js::front::stdlib::value::Value;
 use js::syntax::lexer::Lexer;
 use js::syntax::parser::Parser;
+use js::syntax::error::{SyntaxError, LexFailure};
+use js::syntax::ast::pos::Position;
 use jit::Context;
 use std::default::Default;
 use std::io::{BufferedReader, File};
 use std::path::Path;
+use loader::FileLoader;
 /// An command-line script executor
 pub struct Runner {
     /// The path to the script
@@ -26,17 +30,31 @@ impl Runner {
             let file = File::open(&self.path).unwrap();
             debug!("Now lexing...");
             let mut lexer = Lexer::new(BufferedReader::new(file));
-            lexer.lex().unwrap();
+            match lexer.lex() {
+                Ok(()) => (),
+                Err(err) => {
+                    println!("{}", LexFailure(err));
+                    return;
+                }
+            }
             let tokens = lexer.tokens;
             debug!("Now lexed into: {}", tokens);
             debug!("Now parsing...");
-            let expr = Parser::new(tokens).parse_all().unwrap();
+            let end = tokens.last().map(|token| token.end).unwrap_or(Position::new(1, 1, 0));
+            let expr = match Parser::new(tokens).parse_all() {
+                Ok(expr) => expr,
+                Err(err) => {
+                    println!("{}", SyntaxError::from_parse(err, end));
+                    return;
+                }
+            };
             debug!("Parsed as {}", expr);
             debug!("Creating JIT Context");
             let context = Context::new();
             debug!("Compiling");
             let compiler = JitCompiler::new(&context);
-            let result = compiler.compile(&expr);
+            let result = compiler.compile_script(&expr);
+            compiler.finish();
             debug!("Now running on JIT backend...");
             let executor: JitExecutor = Executor::new(&Default::default());
             match executor.execute(&result) {
@@ -49,4 +67,24 @@ impl Runner {
             fail!("{} does not exist", self.path.display());
         }
     }
+    /// Run the script as a module, loading the modules it imports from the filesystem
+    pub fn run_module(&self) {
+        let context = Context::new();
+        let mut modules = ModuleMap::new(FileLoader, Value::new_global());
+        let result = modules.import(self.path.as_str().unwrap(), |module| {
+            debug!("Compiling module {}", module.key);
+            let compiler = JitCompiler::new(&context);
+            let result = compiler.compile_module(module);
+            compiler.finish();
+            debug!("Now running module {} on JIT backend...", module.key);
+            let executor: JitExecutor = Executor::new(&ExecutorConfig {
+                global: module.environment
+            });
+            executor.execute(&result)
+        });
+        match result {
+            Ok(_) => (),
+            Err(err) => println!("{}", err)
+        }
+    }
 }
\ No newline at end of file
@@ -1,29 +1,24 @@
 use collections::treemap::TreeMap;
 use js::back::compiler::JitCompiler;
 use js::back::executor::JitExecutor;
-use js::front::run::compiler::Compiler;
-use js::front::run::executor::Executor;
+use js::front::run::executor::{Executor, ExecutorConfig};
+use js::front::run::module::ModuleMap;
 use js::front::stdlib::function::Function;
 use js::front::stdlib::value::{ResultValue, Value, to_value, from_value};
-use js::syntax::lexer::Lexer;
-use js::syntax::parser::Parser;
-use js::syntax::ast::token::{Token, TComment};
+use js::syntax::cst::SyntaxTree;
 use jit::Context;
-use std::default::Default;
-use std::io::{BufferedReader, File};
+use std::io::File;
 use std::io::fs::walk_dir;
-fn find_attrs(tokens: Vec<Token>) -> TreeMap<String, String> {
+use loader::FileLoader;
+fn find_attrs(tree: &SyntaxTree) -> TreeMap<String, String> {
     let mut map = TreeMap::new();
-    for tk in tokens.iter() {
-        match tk.data {
-            TComment(ref comm) => {
-                let current = comm.as_slice();
-                if current.starts_with(" @") {
-                    let space_ind = current.slice_from(1).find(' ').unwrap() + 1;
-                    let key = current.slice_chars(2, space_ind);
-                    let value = current.slice_from(space_ind + 1);
-                    map.insert(key.into_string(), value.into_string());
-                }
+    for trivia in tree.comments(&tree.script).iter() {
+        match trivia.comment() {
+            Some(current) if current.starts_with(" @") => {
+                let space_ind = current.slice_from(1).find(' ').unwrap() + 1;
+                let key = current.slice_chars(2, space_ind);
+                let value = current.slice_from(space_ind + 1);
+                map.insert(key.into_string(), value.into_string());
             },
             _ => ()
         }
@@ -57,22 +52,55 @@ impl<'a> Tests<'a> {
         }
         let file = path.display();
         debug!("Opened {} for testing", file);
-        let mut lexer = Lexer::new(BufferedReader::new(File::open(&path).unwrap()));
-        debug!("Lexing");
-        lexer.lex().unwrap();
-        let tokens = lexer.tokens;
-        let attrs = find_attrs(tokens.clone());
-        let desc = attrs.find(&"description".into_string()).unwrap();
-        debug!("Lexed into: {}", tokens);
+        let source = File::open(&path).read_to_string().unwrap();
         debug!("Parsing");
-        let expr = Parser::new(tokens).parse_all().unwrap();
+        // the attributes saying whether a test is a module are in the test, so a test that can't be
+        // parsed as a script is parsed as a module, which is strict but can import and export
+        let tree = match SyntaxTree::parse(source.as_slice()) {
+            Ok(tree) => tree,
+            Err(err) => match SyntaxTree::parse_module(source.as_slice()) {
+                Ok(tree) => tree,
+                Err(_) => {
+                    println!("{}: {}", file, err);
+                    return;
+                }
+            }
+        };
+        let attrs = find_attrs(&tree);
+        let desc = attrs.find(&"description".into_string()).unwrap();
+        // a test of type module is run as a module, which can import the modules next to it
+        if attrs.find(&"type".into_string()).map(|kind| kind.as_slice()) == Some("module") {
+            let global = Value::new_global();
+            global.set_field("assert", Function::make(assert, ["condition"]));
+            let mut modules = ModuleMap::new(FileLoader, global);
+            let result = modules.import(path.as_str().unwrap(), |module| {
+                let compiler = JitCompiler::new(&self.context);
+                let compiled = compiler.compile_module(module);
+                compiler.finish();
+                let executor: JitExecutor = Executor::new(&ExecutorConfig {
+                    global: module.environment
+                });
+                executor.execute(&compiled)
+            });
+            match result {
+                Ok(_) =>
+                    println!("{}: {}: All tests passed successfully", file, desc),
+                Err(err) =>
+                    println!("{}: {}: Failed with {}", file, desc, err)
+            }
+            return;
+        }
+        let expr = tree.script;
         debug!("Parsed as {}", expr);
         debug!("Now running");
-        let env = Value::new_obj(None);
-        env.set_field("assert", Function::make(assert, ["condition"]));
+        let global = Value::new_global();
+        global.set_field("assert", Function::make(assert, ["condition"]));
         let compiler = JitCompiler::new(&self.context);
-        let compiled = compiler.compile(&expr);
-        let executor: JitExecutor = Executor::new(&Default::default());
+        let compiled = compiler.compile_script(&expr);
+        compiler.finish();
+        let executor: JitExecutor = Executor::new(&ExecutorConfig {
+            global: global
+        });
         match executor.execute(&compiled) {
             Ok(_) =>
                 println!("{}: {}: All tests passed successfully", file, desc),
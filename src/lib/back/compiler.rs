@@ -1,11 +1,30 @@
 use front::run::compiler::Compiler;
+use front::run::module::{ModuleRecord, DEFAULT_BINDING, declared_names};
 use syntax::ast::constant::*;
 use syntax::ast::op::*;
-use syntax::ast::expr::Expr;
+use syntax::ast::expr::*;
+use syntax::ast::pattern::*;
+use syntax::scope::{var_names, captured_names, declaration};
+use front::stdlib::object::{ObjectData, Property, PROTOTYPE, INSTANCE_PROTOTYPE};
+use front::stdlib::value::{VString, VObject, VFunction, VNull, ResultValue, from_value, to_value};
+use front::stdlib::array::new_array;
+use front::stdlib::error::new_error;
+use front::stdlib::regexp::new_regexp;
+use front::stdlib::generator::{ResumeNext, ResumeReturn, ResumeThrow, iter_result, new_generator};
+use front::stdlib::promise::{new_promise, reject_promise, run_async};
+use back::executor::{convert_to_value, set_uncaught, take_uncaught};
+use JSVal = front::stdlib::value::Value;
+use JSFunction = front::stdlib::function::Function;
+use arena::TypedArena;
+use collections::treemap::TreeMap;
+use std::cell::{Cell, RefCell};
+use std::mem::replace;
+use NativeString = std::c_str::CString;
 use jit::{
     Context,
     Compile,
     Function,
+    Label,
     Value,
     get_type,
     SysBool,
@@ -17,17 +36,453 @@ use jit::{
     Pointer
 };
 type CompiledValue<'a> = (Value<'a>, &'a Function<'a>);
+/// Compiled code, which runs with the global object, the scope object it runs in and `this`
+type Code = fn(JSVal, JSVal, JSVal) -> JSVal;
+/// The field of an activation object holding the arguments its function was called with
+static ARGUMENTS: &'static str = "__arguments__";
+/// The field of an activation object holding the object that a method's `super` refers to the
+/// prototype of
+static HOME: &'static str = "__home__";
+/// The field of a generator's activation object holding where its body was last suspended
+static YIELD_POINT: &'static str = "__yield_point__";
+/// The field of a generator's activation object holding the value it was last resumed with
+static SENT: &'static str = "__sent__";
+/// The field of a generator's activation object holding how it was last resumed
+static SENT_KIND: &'static str = "__sent_kind__";
+#[deriving(Clone, PartialEq)]
+/// The kinds of function that are called differently
+enum FunctionKind {
+    /// A function or method, which has its own `this` and `arguments`
+    PlainFunction,
+    /// An arrow function, which uses the `this` and `arguments` of the code it's in
+    ArrowFunction,
+    /// A generator function, which binds its parameters then gives a generator that runs its body
+    GeneratorFunction,
+    /// An async function, which runs its body until each `await` and gives a promise of what it returns
+    AsyncFunction,
+    /// An async arrow function, which is run like an async function but uses the `this` and
+    /// `arguments` of the code it's in
+    AsyncArrowFunction
+}
+impl FunctionKind {
+    /// Returns true if the function uses the `this` and `arguments` of the code it's in
+    fn is_arrow(&self) -> bool {
+        *self == ArrowFunction || *self == AsyncArrowFunction
+    }
+    /// Returns true if the function's body can be suspended and resumed later, which it's
+    /// compiled to carry on from where it was suspended for
+    fn is_resumable(&self) -> bool {
+        *self != PlainFunction && *self != ArrowFunction
+    }
+}
+/// Code compiled for a function, with what the functions made from it need to know to run it
+struct CompiledCode {
+    code: Code,
+    /// Whether it's strict mode code
+    strict: bool,
+    kind: FunctionKind
+}
+/// A range of instructions whose thrown values are caught by jumping to `target`
+struct Handler<'a> {
+    start: Label<'a>,
+    end: Label<'a>,
+    target: Label<'a>,
+    thrown: Value<'a>
+}
+/// Where `break` and `continue` jump to inside a loop, switch or labelled statement
+struct JumpTarget<'a> {
+    labels: Vec<String>,
+    breakable: bool,
+    break_to: Label<'a>,
+    continue_to: Option<Label<'a>>,
+    /// How many finally blocks were running when the target was entered, so a jump to it knows
+    /// which ones it leaves
+    finalisers: uint
+}
+/// The bindings that the `let` and `const` bindings of a block shadow, which are brought back
+/// when it ends
+struct Shadowed<'a> {
+    bindings: Vec<(String, Option<Value<'a>>, Option<Value<'a>>)>,
+    /// Whether the block has a scope object of its own for the bindings that functions refer to
+    scoped: bool
+}
 /// A compiler using the LibJIT backend
 pub struct JitCompiler<'a> {
-    curr: Function<'a>
+    context: &'a Context<'a>,
+    curr: Function<'a>,
+    /// The compilers of the functions declared in the code being compiled
+    children: TypedArena<JitCompiler<'a>>,
+    /// The scope objects of the blocks being compiled, from outermost to innermost, inside the
+    /// one the code runs in
+    scopes: RefCell<Vec<Value<'a>>>,
+    /// The names that functions in the code being compiled refer to, which are kept in scope
+    /// objects instead of registers
+    captured: RefCell<Vec<String>>,
+    locals: RefCell<TreeMap<String, Value<'a>>>,
+    /// Whether each `let` or `const` binding in scope has been initialised yet
+    lexicals: RefCell<TreeMap<String, Value<'a>>>,
+    handlers: RefCell<Vec<Handler<'a>>>,
+    jumps: RefCell<Vec<JumpTarget<'a>>>,
+    /// The labels of the labelled statements around the loop or switch about to be compiled
+    pending_labels: RefCell<Vec<String>>,
+    /// The finally blocks of the try statements being compiled, which run before a jump out of them
+    finalisers: RefCell<Vec<Expr>>,
+    /// The registers besides the scope objects and `let` and `const` flags in scope that hold
+    /// values still needed by the code being compiled, which are kept over a `yield`
+    live: RefCell<Vec<Value<'a>>>,
+    /// Where the body of a generator carries on from after each `yield`, in order
+    resume_points: RefCell<Vec<Label<'a>>>,
+    /// Whether the next block compiled is the top level of a module, which binds its `let`,
+    /// `const` and class declarations in the module's environment instead of a scope object of its own
+    module_top_level: Cell<bool>,
+    /// Whether the code being compiled is strict mode code
+    strict: Cell<bool>,
+    /// The kind of function whose code is being compiled
+    kind: Cell<FunctionKind>
 }
 impl<'a> JitCompiler<'a> {
     /// Construct a new JIT Compiler on the given context
-    pub fn new(context: &'a Context) -> JitCompiler<'a> {
+    pub fn new(context: &'a Context<'a>) -> JitCompiler<'a> {
         let main_t = get_type::<fn(*mut int, *mut int, *mut int) -> *mut int>();
+        let curr = Function::new(context, main_t);
+        curr.insn_uses_catcher();
         JitCompiler {
-            curr: Function::new(context, main_t)
+            context: context,
+            curr: curr,
+            children: TypedArena::new(),
+            scopes: RefCell::new(Vec::new()),
+            captured: RefCell::new(Vec::new()),
+            locals: RefCell::new(TreeMap::new()),
+            lexicals: RefCell::new(TreeMap::new()),
+            handlers: RefCell::new(Vec::new()),
+            jumps: RefCell::new(Vec::new()),
+            pending_labels: RefCell::new(Vec::new()),
+            finalisers: RefCell::new(Vec::new()),
+            live: RefCell::new(Vec::new()),
+            resume_points: RefCell::new(Vec::new()),
+            module_top_level: Cell::new(false),
+            strict: Cell::new(false),
+            kind: Cell::new(PlainFunction)
+        }
+    }
+    /// Compile the code after this as strict mode code, which module code always is
+    pub fn use_strict(&self) {
+        self.strict.set(true);
+    }
+    /// Compile the body of a module to run with its environment as the global object, keeping
+    /// the bindings at its top level there so the modules that import them see them change
+    pub fn compile_module(&'a self, module:&ModuleRecord) -> CompiledValue<'a> {
+        self.use_strict();
+        let statements = match module.body.def {
+            BlockExpr(ref statements) => statements.clone(),
+            _ => vec!(module.body.clone())
+        };
+        let mut names = var_names(&module.body);
+        names.push_all(lexical_names(statements.iter()).as_slice());
+        names.extend(module.local_exports.iter().map(|&(_, ref local)| local.clone()));
+        let mut captured = captured_names(&Params::from_names(Vec::new()), &module.body);
+        // the imported bindings are already in the environment, so declaring them leaves them as they are
+        for name in names.move_iter() {
+            self.declare_scoped(&self.scope(), &name);
+            captured.push(name);
+        }
+        *self.captured.borrow_mut() = captured;
+        self.module_top_level.set(true);
+        self.compile(&module.body)
+    }
+    /// Compile a script, whose `var` and function declarations are bound on the global object
+    /// before any of it runs
+    pub fn compile_script(&'a self, script:&Expr) -> CompiledValue<'a> {
+        *self.captured.borrow_mut() = captured_names(&Params::from_names(Vec::new()), script);
+        for name in var_names(script).move_iter() {
+            self.declare_scoped(&self.scope(), &name);
+        }
+        self.compile(script)
+    }
+    /// Compile the parameters and body of a function into code of its own, giving the index it's
+    /// registered under
+    fn compile_code(&'a self, kind:FunctionKind, params:&Params, body:&Expr) -> uint {
+        let child = self.children.alloc(JitCompiler::new(self.context));
+        // a function inside strict mode code is strict too
+        child.strict.set(self.strict.get());
+        child.kind.set(kind);
+        *child.captured.borrow_mut() = captured_names(params, body);
+        // the code of a generator or async function carries on from where its body was suspended
+        // each time it runs
+        let mut start = Label::new(&child.curr);
+        let mut dispatch = Label::new(&child.curr);
+        if kind.is_resumable() {
+            child.curr.insn_branch(&mut dispatch);
+            child.curr.insn_label(&mut start);
+        }
+        child.compile_prologue(params, body);
+        if kind.is_resumable() {
+            // and it stops once its parameters are bound, until it's first resumed
+            let global = child.curr.get_param(0);
+            child.compile_suspend(&global);
         }
+        match body.def {
+            BlockExpr(_) => { child.compile(body); },
+            // an arrow function whose body is an expression returns its value
+            _ => { child.compile_return(Some(box body.clone())); }
+        }
+        child.finish();
+        let undefined = convert_to_value(&child.curr, &child.undefined());
+        child.compile_exit(&undefined);
+        if kind.is_resumable() {
+            child.compile_dispatch(&mut dispatch, &mut start);
+        }
+        child.curr.compile();
+        // a directive in the body can have made it strict
+        let strict = child.strict.get();
+        child.curr.with_closure3(|code:Code| register_code(CompiledCode {
+            code: code,
+            strict: strict,
+            kind: kind
+        }))
+    }
+    /// Return a value from the code being compiled, which a generator or async function gives as
+    /// the value it's done with
+    fn compile_exit(&'a self, val:&Value<'a>) {
+        if self.kind.get().is_resumable() {
+            let done = true.compile(&self.curr);
+            let sig = get_type::<fn(&'static int, &'static int, bool) -> &'static int>();
+            let result = self.curr.insn_call_native3(Some("generator_result"), generator_result, sig, [&self.curr.get_param(0), val, &done]);
+            self.curr.insn_return(&result);
+        } else {
+            self.curr.insn_return(val);
+        }
+    }
+    /// Get the registers holding what's still needed at a `yield`, which don't keep their values
+    /// between runs of a generator's body
+    fn live_registers(&'a self) -> Vec<Value<'a>> {
+        let mut registers = self.scopes.borrow().clone();
+        registers.extend(self.lexicals.borrow().iter().map(|(_, flag)| flag.clone()));
+        registers.push_all(self.live.borrow().as_slice());
+        registers
+    }
+    /// Suspend the body of a generator or async function, giving `result` to whatever resumed it,
+    /// and compile where
+    /// it carries on from when it's next resumed, with the registers it needs brought back
+    fn compile_suspend(&'a self, result:&Value<'a>) {
+        let activation = self.curr.get_param(1);
+        let registers = self.live_registers();
+        let save_sig = get_type::<fn(&'static int, i32, &'static int)>();
+        for (i, register) in registers.iter().enumerate() {
+            let c_index = (i as i32).compile(&self.curr);
+            let c_register = convert_to_value(&self.curr, register);
+            self.curr.insn_call_native3(Some("frame_save"), frame_save, save_sig.clone(), [&activation, &c_index, &c_register]);
+        }
+        // the points are counted from 1, since 0 is the start of the code
+        let c_point = ((self.resume_points.borrow().len() + 1) as i32).compile(&self.curr);
+        let suspend_sig = get_type::<fn(&'static int, i32)>();
+        self.curr.insn_call_native2(Some("frame_suspend"), frame_suspend, suspend_sig, [&activation, &c_point]);
+        self.curr.insn_return(result);
+        let mut resume = Label::new(&self.curr);
+        self.curr.insn_label(&mut resume);
+        self.resume_points.borrow_mut().push(resume);
+        let restore_sig = get_type::<fn(&'static int, i32) -> &'static int>();
+        let bool_sig = get_type::<fn(&'static int) -> bool>();
+        let int_sig = get_type::<fn(&'static int) -> i32>();
+        let num_sig = get_type::<fn(&'static int) -> f64>();
+        for (i, register) in registers.iter().enumerate() {
+            let c_index = (i as i32).compile(&self.curr);
+            let saved = self.curr.insn_call_native2(Some("frame_restore"), frame_restore, restore_sig.clone(), [&activation, &c_index]);
+            let restored = match register.get_type().get_kind() {
+                SysBool | UByte => self.curr.insn_call_native1(Some("value_to_bool"), value_to_bool, bool_sig.clone(), [&saved]),
+                Int | UInt => self.curr.insn_call_native1(Some("value_to_int"), value_to_int, int_sig.clone(), [&saved]),
+                Float64 => self.curr.insn_call_native1(Some("value_to_num"), value_to_num, num_sig.clone(), [&saved]),
+                _ => saved
+            };
+            self.curr.insn_store(register, &restored);
+        }
+    }
+    /// Suspend the body of a generator or async function with a value that isn't done, then
+    /// compile what it does when it's resumed, which is to throw the value it's resumed with or
+    /// return it, or give it as the value of the `yield` or `await`
+    fn compile_suspension(&'a self, val:&Value<'a>) -> Value<'a> {
+        let done = false.compile(&self.curr);
+        let sig = get_type::<fn(&'static int, &'static int, bool) -> &'static int>();
+        let result = self.curr.insn_call_native3(Some("generator_result"), generator_result, sig, [&self.curr.get_param(0), val, &done]);
+        self.compile_suspend(&result);
+        let activation = self.curr.get_param(1);
+        let sent_sig = get_type::<fn(&'static int) -> &'static int>();
+        let sent = self.curr.insn_call_native1(Some("frame_sent"), frame_sent, sent_sig, [&activation]);
+        let kind_sig = get_type::<fn(&'static int) -> i32>();
+        let kind = self.curr.insn_call_native1(Some("frame_sent_kind"), frame_sent_kind, kind_sig, [&activation]);
+        let mut not_thrown = Label::new(&self.curr);
+        let mut not_returned = Label::new(&self.curr);
+        let thrown = self.curr.insn_eq(&kind, &(ResumeThrow as i32).compile(&self.curr));
+        self.curr.insn_branch_if_not(&thrown, &mut not_thrown);
+        self.curr.insn_throw(&sent);
+        self.curr.insn_label(&mut not_thrown);
+        let returned = self.curr.insn_eq(&kind, &(ResumeReturn as i32).compile(&self.curr));
+        self.curr.insn_branch_if_not(&returned, &mut not_returned);
+        // returning from a `yield` runs the finally blocks around it
+        self.run_finalisers(0);
+        self.compile_exit(&sent);
+        self.curr.insn_label(&mut not_returned);
+        sent
+    }
+    /// Keep a value in a register of its own over an expression that can suspend the body it's
+    /// in, since the registers it's in otherwise don't keep their values while that's suspended
+    fn keep(&'a self, val:Value<'a>) -> Value<'a> {
+        let c_val = convert_to_value(&self.curr, &val);
+        let kept = Value::new(&self.curr, get_type::<&'static int>());
+        self.curr.insn_store(&kept, &c_val);
+        self.live.borrow_mut().push(kept.clone());
+        kept
+    }
+    /// Stop keeping the last `count` registers kept over a `yield` or `await`
+    fn release(&'a self, count:uint) {
+        let live = self.live.borrow().len();
+        self.live.borrow_mut().truncate(live - count);
+    }
+    /// Compile the second of two operands, keeping the value of the first over it if it can
+    /// suspend the body it's in
+    fn compile_after(&'a self, first:Value<'a>, second:&Expr) -> (Value<'a>, Value<'a>) {
+        if !suspends(second) {
+            let (c_second, _) = self.compile(second);
+            return (first, c_second);
+        }
+        let kept = self.keep(first);
+        let (c_second, _) = self.compile(second);
+        self.release(1);
+        (kept, c_second)
+    }
+    /// Compile the jumps to where the body of a generator or async function carries on from, by
+    /// where it was last suspended
+    fn compile_dispatch(&'a self, dispatch:&mut Label<'a>, start:&mut Label<'a>) {
+        self.curr.insn_label(dispatch);
+        let sig = get_type::<fn(&'static int) -> i32>();
+        let point = self.curr.insn_call_native1(Some("frame_point"), frame_point, sig, [&self.curr.get_param(1)]);
+        for (i, resume) in self.resume_points.borrow_mut().mut_iter().enumerate() {
+            let is_point = self.curr.insn_eq(&point, &((i + 1) as i32).compile(&self.curr));
+            self.curr.insn_branch_if(&is_point, resume);
+        }
+        self.curr.insn_branch(start);
+    }
+    /// Bind the `var` and function declarations of a function to undefined, then bind its
+    /// parameters to the arguments it was called with, using their defaults in place of
+    /// undefined and collecting the rest of the arguments into an array
+    fn compile_prologue(&'a self, params:&Params, body:&Expr) {
+        let activation = self.curr.get_param(1);
+        for name in params.names().move_iter().chain(var_names(body).move_iter()) {
+            if self.is_captured(&name) {
+                self.declare_scoped(&activation, &name);
+            } else if !self.locals.borrow().contains_key(&name) {
+                self.store_local(name, &self.undefined());
+            }
+        }
+        let args_sig = get_type::<fn(&'static int) -> &'static int>();
+        let c_args = self.curr.insn_call_native1(Some("scope_arguments"), scope_arguments, args_sig, [&activation]);
+        let index_sig = get_type::<fn(&'static int, i32) -> &'static int>();
+        // each default is worked out after the parameters before it are bound, so it can refer to them
+        for (i, &(ref pattern, ref default)) in params.args.iter().enumerate() {
+            let c_index = (i as i32).compile(&self.curr);
+            let c_arg = self.curr.insn_call_native2(Some("pattern_get_index"), pattern_get_index, index_sig.clone(), [&c_args, &c_index]);
+            let c_arg = self.pattern_default(c_arg, default);
+            self.assign_pattern(pattern, c_arg, false);
+        }
+        match params.rest {
+            Some(box ref rest) => {
+                let c_start = (params.args.len() as i32).compile(&self.curr);
+                let rest_sig = get_type::<fn(&'static int, &'static int, i32) -> &'static int>();
+                let c_rest = self.curr.insn_call_native3(Some("pattern_array_rest"), pattern_array_rest, rest_sig, [&self.curr.get_param(0), &c_args, &c_start]);
+                self.assign_pattern(rest, c_rest, false);
+            },
+            None => ()
+        }
+    }
+    /// Make a function that runs the code compiled from a function's parameters and body in the
+    /// current scope, which finds `super` through `home` if it's a method
+    fn compile_closure(&'a self, kind:FunctionKind, params:&Params, body:&Expr, home:Option<&Value<'a>>) -> Value<'a> {
+        let code = self.compile_code(kind, params, body);
+        let c_code = (code as i32).compile(&self.curr);
+        let new_sig = get_type::<fn(&'static int, &'static int, i32) -> &'static int>();
+        let func = self.curr.insn_call_native3(Some("function_new"), function_new, new_sig, [&self.curr.get_param(0), &self.scope(), &c_code]);
+        let set_sig = get_type::<fn(&'static int, &'static int)>();
+        if kind.is_arrow() {
+            let this = self.this_value();
+            self.curr.insn_call_native2(Some("function_set_this"), function_set_this, set_sig.clone(), [&func, &this]);
+        }
+        match home {
+            Some(home) => { self.curr.insn_call_native2(Some("function_set_home"), function_set_home, set_sig, [&func, home]); },
+            None => ()
+        }
+        func
+    }
+    /// Compile a function declaration or expression into a function without binding its name,
+    /// giving it the object that `super` refers to the prototype of if it's a method
+    fn compile_function(&'a self, func:&Expr, home:Option<&Value<'a>>) -> Value<'a> {
+        match func.def {
+            FunctionDeclExpr(_, ref params, box ref body) => self.compile_closure(PlainFunction, params, body, home),
+            GeneratorDeclExpr(_, ref params, box ref body) => self.compile_closure(GeneratorFunction, params, body, home),
+            AsyncFunctionDeclExpr(_, ref params, box ref body) => self.compile_closure(AsyncFunction, params, body, home),
+            _ => self.compile_value(func)
+        }
+    }
+    /// Compile a function declaration or expression, which can refer to itself by its name if it's
+    /// an expression, unlike the code around it
+    fn compile_named_closure(&'a self, kind:FunctionKind, name:Option<String>, params:&Params, body:&Expr) -> Value<'a> {
+        match name {
+            Some(name) => {
+                let sig = get_type::<fn(&'static int) -> &'static int>();
+                let scope = self.curr.insn_call_native1(Some("scope_new"), scope_new, sig, [&self.scope()]);
+                self.declare_scoped(&scope, &name);
+                self.scopes.borrow_mut().push(scope.clone());
+                let func = self.compile_closure(kind, params, body, None);
+                self.scopes.borrow_mut().pop();
+                let c_name = name.compile(&self.curr);
+                let set_sig = get_type::<fn(&'static int, String, &'static int)>();
+                self.curr.insn_call_native3(Some("scope_set"), scope_set, set_sig, [&scope, &c_name, &func]);
+                func
+            },
+            None => self.compile_closure(kind, params, body, None)
+        }
+    }
+    /// Get `this` as a value
+    fn this_value(&'a self) -> Value<'a> {
+        convert_to_value(&self.curr, &self.curr.get_param(2))
+    }
+    /// Get the scope object that names which aren't in registers are looked up in
+    fn scope(&'a self) -> Value<'a> {
+        match self.scopes.borrow().last() {
+            Some(scope) => scope.clone(),
+            None => self.curr.get_param(1)
+        }
+    }
+    /// Returns true if a name is referred to by a function in the code being compiled, so it's
+    /// kept in a scope object
+    fn is_captured(&'a self, name:&String) -> bool {
+        // the bindings of a generator or async function have to last between runs of its body,
+        // which registers don't
+        self.kind.get().is_resumable() || self.captured.borrow().contains(name)
+    }
+    /// Define a name in a scope object as undefined, unless it's already defined there
+    fn declare_scoped(&'a self, scope:&Value<'a>, name:&String) {
+        let c_name = name.compile(&self.curr);
+        let sig = get_type::<fn(&'static int, String)>();
+        self.curr.insn_call_native2(Some("scope_declare"), scope_declare, sig, [scope, &c_name]);
+    }
+    /// Finish compiling by emitting the catcher, which dispatches thrown values to the
+    /// innermost enclosing handler and hands uncaught values to the executor
+    pub fn finish(&'a self) {
+        let mut done = Label::new(&self.curr);
+        self.curr.insn_branch(&mut done);
+        self.curr.insn_start_catcher();
+        let thrown = self.curr.insn_thrown_exception();
+        for handler in self.handlers.borrow_mut().mut_iter() {
+            let mut next = Label::new(&self.curr);
+            self.curr.insn_branch_if_pc_not_in_range(&handler.start, &handler.end, &mut next);
+            self.curr.insn_store(&handler.thrown, &thrown);
+            self.curr.insn_branch(&mut handler.target);
+            self.curr.insn_label(&mut next);
+        }
+        let sig = get_type::<fn(&'static int)>();
+        self.curr.insn_call_native1(Some("set_uncaught"), set_uncaught, sig, [&thrown]);
+        self.curr.insn_return(&self.curr.get_param(0));
+        self.curr.insn_label(&mut done);
     }
     fn convert_bool(&'a self, val:Value<'a>) -> Value<'a> {
         let bool_t = get_type::<bool>();
@@ -46,17 +501,494 @@ impl<'a> JitCompiler<'a> {
                 convert(self.curr.insn_neq(&val, &zero))
             },
             Pointer => {
-                let one = 1i.compile(&self.curr);
-                convert(self.curr.insn_gt(&val, &one))
+                let c_val = convert_to_value(&self.curr, &val);
+                let sig = get_type::<fn(&'static int) -> bool>();
+                self.curr.insn_call_native1(Some("value_to_bool"), value_to_bool, sig, [&c_val])
             },
             _ => convert(val)
         }
     }
+    /// Convert a compiled value to a float, since all Javascript numbers are floats and integers
+    /// are only kept while they're known to fit
+    fn to_number(&'a self, val:Value<'a>) -> Value<'a> {
+        match val.get_type().get_kind() {
+            Float64 => val,
+            Pointer => {
+                let c_val = convert_to_value(&self.curr, &val);
+                let sig = get_type::<fn(&'static int) -> f64>();
+                self.curr.insn_call_native1(Some("value_to_num"), value_to_num, sig, [&c_val])
+            },
+            _ => self.curr.insn_convert(&val, get_type::<f64>(), false)
+        }
+    }
+    /// Convert a compiled value to a 32-bit integer for a bitwise operator
+    fn to_int32(&'a self, val:Value<'a>) -> Value<'a> {
+        match val.get_type().get_kind() {
+            Pointer => {
+                let c_val = convert_to_value(&self.curr, &val);
+                let sig = get_type::<fn(&'static int) -> i32>();
+                self.curr.insn_call_native1(Some("value_to_int"), value_to_int, sig, [&c_val])
+            },
+            _ => self.curr.insn_convert(&val, get_type::<i32>(), false)
+        }
+    }
+    /// Negate a compiled value as a boolean
+    fn not(&'a self, val:Value<'a>) -> Value<'a> {
+        let c_val = self.convert_bool(val);
+        self.curr.insn_eq(&c_val, &false.compile(&self.curr))
+    }
+    /// Compare two compiled values with `==`, or with `===` if `strict` is set
+    fn equals(&'a self, left:Value<'a>, right:Value<'a>, strict:bool) -> Value<'a> {
+        let is_bool = |val:&Value<'a>| val.get_type().get_kind() == SysBool;
+        // a boolean is never strictly equal to a number, even though they're compared the same way
+        if is_pointer(&left) || is_pointer(&right) || (strict && is_bool(&left) != is_bool(&right)) {
+            let c_left = convert_to_value(&self.curr, &left);
+            let c_right = convert_to_value(&self.curr, &right);
+            let sig = get_type::<fn(&'static int, &'static int) -> bool>();
+            if strict {
+                self.curr.insn_call_native2(Some("value_strict_equals"), value_strict_equals, sig, [&c_left, &c_right])
+            } else {
+                self.curr.insn_call_native2(Some("value_equals"), value_equals, sig, [&c_left, &c_right])
+            }
+        } else {
+            self.curr.insn_eq(&left, &right)
+        }
+    }
+    /// Compare the order of two compiled values, which compares strings by their characters
+    fn compare(&'a self, op:CompOp, left:Value<'a>, right:Value<'a>) -> Value<'a> {
+        if is_pointer(&left) || is_pointer(&right) {
+            let c_left = convert_to_value(&self.curr, &left);
+            let c_right = convert_to_value(&self.curr, &right);
+            let sig = get_type::<fn(&'static int, &'static int) -> bool>();
+            let args = [&c_left, &c_right];
+            match op {
+                CompGreaterThan => self.curr.insn_call_native2(Some("value_gt"), value_gt, sig, args),
+                CompGreaterThanOrEqual => self.curr.insn_call_native2(Some("value_geq"), value_geq, sig, args),
+                CompLessThan => self.curr.insn_call_native2(Some("value_lt"), value_lt, sig, args),
+                _ => self.curr.insn_call_native2(Some("value_leq"), value_leq, sig, args)
+            }
+        } else {
+            match op {
+                CompGreaterThan => self.curr.insn_gt(&left, &right),
+                CompGreaterThanOrEqual => self.curr.insn_geq(&left, &right),
+                CompLessThan => self.curr.insn_lt(&left, &right),
+                _ => self.curr.insn_leq(&left, &right)
+            }
+        }
+    }
+    /// Store a value in a local, which holds it as a Javascript value so each local has one
+    /// register whatever is stored in it
+    fn store_local(&'a self, name:String, val:&Value<'a>) -> Value<'a> {
+        let val = convert_to_value(&self.curr, val);
+        let local = self.locals.borrow().find(&name).map(|local| local.clone());
+        let local = match local {
+            Some(local) => local,
+            None => {
+                let local = Value::new(&self.curr, get_type::<&'static int>());
+                self.locals.borrow_mut().insert(name, local.clone());
+                local
+            }
+        };
+        self.curr.insn_store(&local, &val);
+        local
+    }
+    /// Returns true if a name is bound to a register in the code being compiled rather than
+    /// looked up in the scope chain at runtime
+    fn is_bound(&'a self, name:&String) -> bool {
+        !self.is_captured(name) && (self.locals.borrow().contains_key(name) || self.lexicals.borrow().contains_key(name))
+    }
+    /// Look up a name that isn't in a register in the scope chain, which ends with the global
+    /// object and throws a `ReferenceError` if the name isn't there
+    fn load_unbound(&'a self, name:&String) -> Value<'a> {
+        let scope = self.scope();
+        let c_name = name.compile(&self.curr);
+        self.check_defined(&scope, name);
+        let sig = get_type::<fn(&'static int, String) -> &'static int>();
+        self.curr.insn_call_native2(Some("scope_get"), scope_get, sig, [&scope, &c_name])
+    }
+    /// Assign to a name that isn't in a register, which makes a field of the global object if
+    /// nothing in the scope chain defines it, unless it's strict mode code, where that throws a
+    /// `ReferenceError` instead
+    fn store_unbound(&'a self, name:&String, val:&Value<'a>) {
+        let scope = self.scope();
+        if self.strict.get() {
+            self.check_defined(&scope, name);
+        }
+        let c_name = name.compile(&self.curr);
+        let c_val = convert_to_value(&self.curr, val);
+        let sig = get_type::<fn(&'static int, String, &'static int)>();
+        self.curr.insn_call_native3(Some("scope_set"), scope_set, sig, [&scope, &c_name, &c_val]);
+    }
+    /// Throw a `ReferenceError` unless a name is a field of the scope given or the scopes it's in
+    fn check_defined(&'a self, scope:&Value<'a>, name:&String) {
+        let c_name = name.compile(&self.curr);
+        let has_sig = get_type::<fn(&'static int, String) -> bool>();
+        let defined = self.curr.insn_call_native2(Some("scope_has"), scope_has, has_sig, [scope, &c_name]);
+        let mut done = Label::new(&self.curr);
+        self.curr.insn_branch_if(&defined, &mut done);
+        let error_sig = get_type::<fn(&'static int, String) -> &'static int>();
+        let error = self.curr.insn_call_native2(Some("undefined_error"), undefined_error, error_sig, [&self.curr.get_param(0), &c_name]);
+        self.curr.insn_throw(&error);
+        self.curr.insn_label(&mut done);
+    }
+    /// Assign a value to a name, whether it's bound in the code being compiled or not
+    fn store_name(&'a self, name:&String, val:&Value<'a>) {
+        if self.is_bound(name) {
+            self.store_local(name.clone(), val);
+        } else {
+            self.store_unbound(name, val);
+        }
+    }
+    /// Bring `let` and `const` bindings into scope, uninitialised, giving the bindings they shadow
+    fn enter_lexicals(&'a self, names:Vec<String>) -> Shadowed<'a> {
+        // the bindings that functions refer to get a scope object each time the block runs, so
+        // functions made in different runs of it don't share them
+        let scoped = names.iter().any(|name| self.is_captured(name));
+        // but the top level of a module only runs once, and keeps them in its environment
+        let top_level = self.module_top_level.get();
+        self.module_top_level.set(false);
+        let scoped = scoped && !top_level;
+        if scoped {
+            let sig = get_type::<fn(&'static int) -> &'static int>();
+            let scope = self.curr.insn_call_native1(Some("scope_new"), scope_new, sig, [&self.scope()]);
+            for name in names.iter().filter(|name| self.is_captured(*name)) {
+                self.declare_scoped(&scope, name);
+            }
+            self.scopes.borrow_mut().push(scope);
+        }
+        let not_initialised = false.compile(&self.curr);
+        let bindings : Vec<(String, Option<Value<'a>>, Option<Value<'a>>)> = names.move_iter().map(|name| {
+            let local = self.locals.borrow_mut().pop(&name);
+            let flag = Value::new(&self.curr, get_type::<bool>());
+            self.curr.insn_store(&flag, &not_initialised);
+            let lexical = self.lexicals.borrow_mut().swap(name.clone(), flag);
+            (name, local, lexical)
+        }).collect();
+        // the flags of the shadowed bindings are needed again once the block ends
+        for &(_, _, ref lexical) in bindings.iter() {
+            match *lexical {
+                Some(ref flag) => self.live.borrow_mut().push(flag.clone()),
+                None => ()
+            }
+        }
+        Shadowed {
+            bindings: bindings,
+            scoped: scoped
+        }
+    }
+    /// Take `let` and `const` bindings out of scope, restoring the bindings they shadowed
+    fn leave_lexicals(&'a self, shadowed:Shadowed<'a>) {
+        if shadowed.scoped {
+            self.scopes.borrow_mut().pop();
+        }
+        self.release(shadowed.bindings.iter().filter(|&&(_, _, ref lexical)| lexical.is_some()).count());
+        for (name, local, lexical) in shadowed.bindings.move_iter() {
+            match local {
+                Some(local) => self.locals.borrow_mut().insert(name.clone(), local),
+                None => self.locals.borrow_mut().remove(&name)
+            };
+            match lexical {
+                Some(lexical) => self.lexicals.borrow_mut().insert(name, lexical),
+                None => self.lexicals.borrow_mut().remove(&name)
+            };
+        }
+    }
+    /// Mark a `let` or `const` binding as initialised
+    fn initialise_lexical(&'a self, name:&String) {
+        match self.lexicals.borrow().find(name) {
+            Some(flag) => self.curr.insn_store(flag, &true.compile(&self.curr)),
+            None => ()
+        }
+    }
+    /// Throw a `ReferenceError` if `name` is a `let` or `const` binding that hasn't been initialised yet
+    fn check_initialised(&'a self, name:&String) {
+        let flag = match self.lexicals.borrow().find(name) {
+            Some(flag) => flag.clone(),
+            None => return
+        };
+        let mut initialised = Label::new(&self.curr);
+        self.curr.insn_branch_if(&flag, &mut initialised);
+        let c_name = name.compile(&self.curr);
+        let sig = get_type::<fn(&'static int, String) -> &'static int>();
+        let error = self.curr.insn_call_native2(Some("uninitialised_error"), uninitialised_error, sig, [&self.curr.get_param(0), &c_name]);
+        self.curr.insn_throw(&error);
+        self.curr.insn_label(&mut initialised);
+    }
+    /// Destructure a value into a pattern, initialising the `let` and `const` bindings it
+    /// declares if `declare` is set
+    fn assign_pattern(&'a self, pattern:&Pattern, val:Value<'a>, declare:bool) {
+        let global = self.curr.get_param(0);
+        let value_sig = get_type::<fn(&'static int, &'static int) -> &'static int>();
+        match *pattern {
+            NamePattern(ref name) => {
+                if declare {
+                    self.initialise_lexical(name);
+                } else {
+                    self.check_initialised(name);
+                }
+                self.store_name(name, &val);
+            },
+            ExprPattern(box ref expr) => {
+                let (c_obj, c_key) = self.compile_field(expr);
+                let c_val = convert_to_value(&self.curr, &val);
+                self.store_field(&c_obj, &c_key, &c_val);
+            },
+            ObjectPattern(ref fields, ref rest) => {
+                let c_obj = convert_to_value(&self.curr, &val);
+                for &(ref key, ref field, ref default) in fields.iter() {
+                    let c_key = key.compile(&self.curr);
+                    let c_key = convert_to_value(&self.curr, &c_key);
+                    let c_field = self.curr.insn_call_native2(Some("field_get"), field_get, value_sig.clone(), [&c_obj, &c_key]);
+                    let c_field = self.pattern_default(c_field, default);
+                    self.assign_pattern(field, c_field, declare);
+                }
+                match *rest {
+                    Some(box ref rest) => {
+                        let copy = self.curr.insn_call_native2(Some("pattern_copy_object"), pattern_copy_object, value_sig.clone(), [&global, &c_obj]);
+                        let remove_sig = get_type::<fn(&'static int, String)>();
+                        for &(ref key, _, _) in fields.iter() {
+                            let c_key = key.compile(&self.curr);
+                            self.curr.insn_call_native2(Some("pattern_remove"), pattern_remove, remove_sig.clone(), [&copy, &c_key]);
+                        }
+                        self.assign_pattern(rest, copy, declare);
+                    },
+                    None => ()
+                }
+            },
+            ArrayPattern(ref items, ref rest) => {
+                let c_arr = convert_to_value(&self.curr, &val);
+                let index_sig = get_type::<fn(&'static int, i32) -> &'static int>();
+                for (i, item) in items.iter().enumerate() {
+                    match *item {
+                        Some((ref item, ref default)) => {
+                            let c_index = (i as i32).compile(&self.curr);
+                            let c_item = self.curr.insn_call_native2(Some("pattern_get_index"), pattern_get_index, index_sig.clone(), [&c_arr, &c_index]);
+                            let c_item = self.pattern_default(c_item, default);
+                            self.assign_pattern(item, c_item, declare);
+                        },
+                        None => ()
+                    }
+                }
+                match *rest {
+                    Some(box ref rest) => {
+                        let c_start = (items.len() as i32).compile(&self.curr);
+                        let sig = get_type::<fn(&'static int, &'static int, i32) -> &'static int>();
+                        let c_rest = self.curr.insn_call_native3(Some("pattern_array_rest"), pattern_array_rest, sig, [&global, &c_arr, &c_start]);
+                        self.assign_pattern(rest, c_rest, declare);
+                    },
+                    None => ()
+                }
+            }
+        }
+    }
+    /// Compile an expression and convert it to a value
+    fn compile_value(&'a self, expr:&Expr) -> Value<'a> {
+        let (c_expr, _) = self.compile(expr);
+        convert_to_value(&self.curr, &c_expr)
+    }
+    /// Compile the object and key of a field access to values
+    fn compile_field(&'a self, expr:&Expr) -> (Value<'a>, Value<'a>) {
+        match expr.def {
+            GetConstFieldExpr(box ref obj, ref field) => {
+                let c_field = field.compile(&self.curr);
+                (self.compile_value(obj), convert_to_value(&self.curr, &c_field))
+            },
+            GetFieldExpr(box ref obj, box ref field) => (self.compile_value(obj), self.compile_value(field)),
+            _ => unreachable!()
+        }
+    }
+    /// Compile the arguments of a call or the items of an array literal into an array, spreading
+    /// the items of spread elements into it
+    fn compile_elements(&'a self, elements:&Vec<Element>) -> Value<'a> {
+        let new_sig = get_type::<fn(&'static int) -> &'static int>();
+        let array = self.curr.insn_call_native1(Some("array_new"), array_new, new_sig, [&self.curr.get_param(0)]);
+        let push_sig = get_type::<fn(&'static int, &'static int)>();
+        let kept = elements.iter().any(|element| suspends(element.expr()));
+        let array = if kept {self.keep(array)} else {array};
+        for element in elements.iter() {
+            let c_expr = self.compile_value(element.expr());
+            match *element {
+                ExprElement(_) => self.curr.insn_call_native2(Some("array_push"), array_push, push_sig.clone(), [&array, &c_expr]),
+                SpreadElement(_) => self.curr.insn_call_native2(Some("array_spread"), array_spread, push_sig.clone(), [&array, &c_expr])
+            };
+        }
+        if kept {
+            self.release(1);
+        }
+        array
+    }
+    /// Copy a value as a number, converting it first if it's a Javascript value
+    fn copy_number(&'a self, val:&Value<'a>) -> Value<'a> {
+        if val.get_type().get_kind() == Pointer {
+            let c_val = convert_to_value(&self.curr, val);
+            let sig = get_type::<fn(&'static int) -> f64>();
+            self.curr.insn_call_native1(Some("value_to_num"), value_to_num, sig, [&c_val])
+        } else {
+            let copy = Value::new(&self.curr, val.get_type());
+            self.curr.insn_store(&copy, val);
+            copy
+        }
+    }
+    /// Add one to a local or field, or take one away from it, giving the old number for `a++` and
+    /// `a--` or the new one for `++a` and `--a`
+    fn compile_update(&'a self, op:UnaryOp, target:&Expr) -> Value<'a> {
+        let increment = op == UnaryIncrementPre || op == UnaryIncrementPost;
+        let postfix = op == UnaryIncrementPost || op == UnaryDecrementPost;
+        let step = |old:&Value<'a>| {
+            let one = self.curr.insn_convert(&1f64.compile(&self.curr), old.get_type(), false);
+            if increment {
+                *old + one
+            } else {
+                *old - one
+            }
+        };
+        let (old, new) = match target.def {
+            LocalExpr(ref name) => {
+                let (local, _) = self.compile_local(name.clone());
+                let old = self.copy_number(&local);
+                let new = step(&old);
+                self.store_name(name, &new);
+                (old, new)
+            },
+            GetConstFieldExpr(_, _) | GetFieldExpr(_, _) => {
+                let (obj, key) = self.compile_field(target);
+                let get_sig = get_type::<fn(&'static int, &'static int) -> &'static int>();
+                let field = self.curr.insn_call_native2(Some("field_get"), field_get, get_sig, [&obj, &key]);
+                let old = self.copy_number(&field);
+                let new = step(&old);
+                let c_new = convert_to_value(&self.curr, &new);
+                self.store_field(&obj, &key, &c_new);
+                (old, new)
+            },
+            // the parser only allows locals and fields to be incremented and decremented
+            _ => unreachable!()
+        };
+        if postfix {
+            old
+        } else {
+            new
+        }
+    }
+    /// Set a field of a value, which throws a `TypeError` in strict mode code if the field is
+    /// read-only instead of leaving it as it is
+    fn store_field(&'a self, obj:&Value<'a>, key:&Value<'a>, val:&Value<'a>) {
+        if self.strict.get() {
+            let check_sig = get_type::<fn(&'static int, &'static int) -> bool>();
+            let can_set = self.curr.insn_call_native2(Some("field_can_set"), field_can_set, check_sig, [obj, key]);
+            self.check_type(&can_set, "Cannot assign to a read-only field");
+        }
+        let sig = get_type::<fn(&'static int, &'static int, &'static int)>();
+        self.curr.insn_call_native3(Some("field_set"), field_set, sig, [obj, key, val]);
+    }
+    /// Throw a `TypeError` with the message given unless `valid` is true
+    fn check_type(&'a self, valid:&Value<'a>, message:&str) {
+        let mut done = Label::new(&self.curr);
+        self.curr.insn_branch_if(valid, &mut done);
+        let c_message = message.into_string().compile(&self.curr);
+        let sig = get_type::<fn(&'static int, String) -> &'static int>();
+        let error = self.curr.insn_call_native2(Some("type_error"), type_error, sig, [&self.curr.get_param(0), &c_message]);
+        self.curr.insn_throw(&error);
+        self.curr.insn_label(&mut done);
+    }
+    /// Throw the result of calling a function with an array of arguments if the call threw
+    fn throw_if_threw(&'a self, args:&Value<'a>, result:&Value<'a>) {
+        let threw_sig = get_type::<fn(&'static int) -> bool>();
+        let threw = self.curr.insn_call_native1(Some("call_threw"), call_threw, threw_sig, [args]);
+        let mut done = Label::new(&self.curr);
+        self.curr.insn_branch_if_not(&threw, &mut done);
+        self.curr.insn_throw(result);
+        self.curr.insn_label(&mut done);
+    }
+    /// Use the default value of part of a pattern in place of the value it was given if that's undefined
+    fn pattern_default(&'a self, val:Value<'a>, default:&Option<Expr>) -> Value<'a> {
+        let default = match *default {
+            Some(ref default) => default,
+            None => return val
+        };
+        let result = Value::new(&self.curr, val.get_type());
+        self.curr.insn_store(&result, &val);
+        let mut done = Label::new(&self.curr);
+        let sig = get_type::<fn(&'static int) -> bool>();
+        let is_undefined = self.curr.insn_call_native1(Some("pattern_is_undefined"), pattern_is_undefined, sig, [&val]);
+        self.curr.insn_branch_if_not(&is_undefined, &mut done);
+        let (c_default, _) = self.compile(default);
+        let c_default = convert_to_value(&self.curr, &c_default);
+        self.curr.insn_store(&result, &c_default);
+        self.curr.insn_label(&mut done);
+        result
+    }
+    fn push_jump_target(&'a self, break_to:&Label<'a>, continue_to:Option<&Label<'a>>) {
+        // a labelled loop or switch takes the labels of the statements around it
+        let labels = replace(&mut *self.pending_labels.borrow_mut(), Vec::new());
+        self.jumps.borrow_mut().push(JumpTarget {
+            labels: labels,
+            breakable: true,
+            break_to: break_to.clone(),
+            continue_to: continue_to.map(|label| label.clone()),
+            finalisers: self.finalisers.borrow().len()
+        });
+    }
+    /// Compile the finally blocks being left by a jump, innermost first, until there are only
+    /// `depth` of them left
+    fn run_finalisers(&'a self, depth:uint) {
+        let mut outer = self.finalisers.borrow().clone();
+        while outer.len() > depth {
+            let finally = outer.pop().unwrap();
+            // a jump out of the finally block itself only runs the ones around it
+            let active = replace(&mut *self.finalisers.borrow_mut(), outer.clone());
+            self.compile(&finally);
+            *self.finalisers.borrow_mut() = active;
+        }
+    }
+    /// Apply a numeric operator to two compiled values
+    fn num_op(&'a self, op:NumOp, left:Value<'a>, right:Value<'a>) -> Value<'a> {
+        match op {
+            // either side could be a string, which makes this a concatenation
+            OpAdd if is_pointer(&left) || is_pointer(&right) => {
+                let c_left = convert_to_value(&self.curr, &left);
+                let c_right = convert_to_value(&self.curr, &right);
+                let sig = get_type::<fn(&'static int, &'static int) -> &'static int>();
+                return self.curr.insn_call_native2(Some("value_add"), value_add, sig, [&c_left, &c_right]);
+            },
+            _ => ()
+        }
+        let c_left = self.to_number(left);
+        let c_right = self.to_number(right);
+        match op {
+            OpAdd => c_left + c_right,
+            OpSub => c_left - c_right,
+            OpDiv => c_left / c_right,
+            OpMul => c_left * c_right,
+            OpMod => c_left % c_right,
+            OpExp => {
+                let sig = get_type::<fn(f64, f64) -> f64>();
+                self.curr.insn_call_native2(Some("num_pow"), num_pow, sig, [&c_left, &c_right])
+            }
+        }
+    }
+    /// Apply a bitwise operator to two compiled values
+    fn bit_op(&'a self, op:BitOp, left:Value<'a>, right:Value<'a>) -> Value<'a> {
+        let c_left = self.to_int32(left);
+        let c_right = self.to_int32(right);
+        match op {
+            BitAnd => c_left & c_right,
+            BitOr => c_left | c_right,
+            BitXor => c_left ^ c_right,
+            BitShl => c_left << c_right,
+            BitShr => c_left >> c_right,
+            BitUShr => {
+                // the result is unsigned so it's given as a number in case it doesn't fit in an integer
+                let uint_t = get_type::<u32>();
+                let c_left = self.curr.insn_convert(&c_left, uint_t.clone(), false);
+                let c_right = self.curr.insn_convert(&c_right, uint_t, false);
+                let shifted = c_left >> c_right;
+                self.curr.insn_convert(&shifted, get_type::<f64>(), false)
+            }
+        }
+    }
     fn undefined(&'a self) -> Value<'a> {
-        let ptr = Value::new(&self.curr, get_type::<&int>());
-        let val = 0u8.compile(&self.curr);
-        self.curr.insn_store(&ptr, &val);
-        ptr
+        let sig = get_type::<fn(&'static int) -> &'static int>();
+        self.curr.insn_call_native1(Some("undefined_value"), undefined_value, sig, [&self.curr.get_param(0)])
     }
 }
 impl<'a> Compiler<'a, (Value<'a>, &'a Function<'a>)> for JitCompiler<'a> {
@@ -71,56 +1003,66 @@ impl<'a> Compiler<'a, (Value<'a>, &'a Function<'a>)> for JitCompiler<'a> {
             CBool(v) =>
                 v.compile(&self.curr),
             CNull => {
-                let ptr = Value::new(&self.curr, get_type::<&int>());
-                let val = 1u8.compile(&self.curr);
-                self.curr.insn_store(&ptr, &val);
-                ptr
+                let sig = get_type::<fn(&'static int) -> &'static int>();
+                self.curr.insn_call_native1(Some("null_value"), null_value, sig, [&self.curr.get_param(0)])
             },
             CUndefined => {
                 self.undefined()
             },
-            _ => unimplemented!()
+            // the lexer has already checked that the pattern compiles
+            CRegExp(source, flags) => {
+                let c_source = source.compile(&self.curr);
+                let c_flags = flags.compile(&self.curr);
+                let sig = get_type::<fn(&'static int, String, String) -> &'static int>();
+                self.curr.insn_call_native3(Some("regexp_value"), regexp_value, sig, [&self.curr.get_param(0), &c_source, &c_flags])
+            },
+            // there is no BigInt value yet, so using one throws when it's reached
+            CBigInt(_) => {
+                let supported = false.compile(&self.curr);
+                self.check_type(&supported, "BigInt values are not supported");
+                self.undefined()
+            }
         }, &self.curr)
     }
     fn compile_block(&'a self, block:Vec<Expr>) -> CompiledValue<'a> {
-        let last = block.last();
+        let shadowed = self.enter_lexicals(lexical_names(block.iter()));
+        // function declarations are bound before anything in the block runs
         for expr in block.iter() {
-            let comp = self.compile(expr);
-            if expr == last.unwrap() {
-                return comp
+            match declared_function(expr) {
+                Some(name) => {
+                    let func = self.compile_function(declaration(expr), None);
+                    self.store_name(&name, &func);
+                },
+                None => ()
             }
         }
-        unreachable!()
+        let mut last = None;
+        for expr in block.iter() {
+            last = Some(match declared_function(expr) {
+                Some(_) => (self.undefined(), &self.curr),
+                None => self.compile(expr)
+            });
+        }
+        self.leave_lexicals(shadowed);
+        match last {
+            Some(comp) => comp,
+            None => (self.undefined(), &self.curr)
+        }
     }
     fn compile_num_op(&'a self, op:NumOp, left:&Expr, right:&Expr) -> CompiledValue<'a> {
         let (c_left, _) = self.compile(left);
-        let (c_right, _) = self.compile(right);
-        (match op {
-            OpAdd => c_left + c_right,
-            OpSub => c_left - c_right,
-            OpDiv => c_left / c_right,
-            OpMul => c_left * c_right,
-            OpMod => c_left % c_right
-        }, &self.curr)
+        let (c_left, c_right) = self.compile_after(c_left, right);
+        (self.num_op(op, c_left, c_right), &self.curr)
     }
     fn compile_bit_op(&'a self, op:BitOp, left:&Expr, right:&Expr) -> CompiledValue<'a> {
-        let int_t = get_type::<i32>();
         let (c_left, _) = self.compile(left);
-        let c_left = self.curr.insn_convert(&c_left, int_t.clone(), false);
-        let (c_right, _) = self.compile(right);
-        let c_right = self.curr.insn_convert(&c_right, int_t, false);
-        (match op {
-            BitAnd => c_left & c_right,
-            BitOr => c_left | c_right,
-            BitXor => c_left ^ c_right,
-            BitShl => c_left << c_right,
-            BitShr => c_left >> c_right
-        }, &self.curr)
+        let (c_left, c_right) = self.compile_after(c_left, right);
+        (self.bit_op(op, c_left, c_right), &self.curr)
     }
     fn compile_log_op(&'a self, op:LogOp, left:&Expr, right:&Expr) -> CompiledValue<'a> {
         let (c_left, _) = self.compile(left);
+        let (c_left, c_right) = self.compile_after(c_left, right);
         let c_left = self.convert_bool(c_left);
-        let (c_right, _) = self.compile(right);
         let c_right = self.convert_bool(c_right);
         (match op {
             LogAnd => c_left & c_right,
@@ -129,46 +1071,1427 @@ impl<'a> Compiler<'a, (Value<'a>, &'a Function<'a>)> for JitCompiler<'a> {
     }
     fn compile_comp_op(&'a self, op:CompOp, left:&Expr, right:&Expr) -> CompiledValue<'a> {
         let (c_left, _) = self.compile(left);
-        let (c_right, _) = self.compile(right);
+        let (c_left, c_right) = self.compile_after(c_left, right);
         let val = match op {
-            CompEqual | CompStrictEqual =>
-                self.curr.insn_eq(&c_left, &c_right),
-            CompNotEqual | CompStrictNotEqual =>
-                self.curr.insn_neq(&c_left, &c_right),
-            CompGreaterThan =>
-                self.curr.insn_gt(&c_left, &c_right),
-            CompGreaterThanOrEqual =>
-                self.curr.insn_geq(&c_left, &c_right),
-            CompLessThan =>
-                self.curr.insn_lt(&c_left, &c_right),
-            CompLessThanOrEqual =>
-                self.curr.insn_leq(&c_left, &c_right),
+            CompEqual =>
+                self.equals(c_left, c_right, false),
+            CompStrictEqual =>
+                self.equals(c_left, c_right, true),
+            CompNotEqual => {
+                let equal = self.equals(c_left, c_right, false);
+                self.not(equal)
+            },
+            CompStrictNotEqual => {
+                let equal = self.equals(c_left, c_right, true);
+                self.not(equal)
+            },
+            CompGreaterThan | CompGreaterThanOrEqual | CompLessThan | CompLessThanOrEqual =>
+                self.compare(op, c_left, c_right),
+            CompIn => {
+                let key = convert_to_value(&self.curr, &c_left);
+                let obj = convert_to_value(&self.curr, &c_right);
+                let is_object = self.curr.insn_call_native1(Some("op_is_object"), op_is_object, get_type::<fn(&'static int) -> bool>(), [&obj]);
+                self.check_type(&is_object, "the right side of 'in' must be an object");
+                let sig = get_type::<fn(&'static int, &'static int) -> bool>();
+                self.curr.insn_call_native2(Some("op_in"), op_in, sig, [&obj, &key])
+            },
+            CompInstanceOf => {
+                let val = convert_to_value(&self.curr, &c_left);
+                let constructor = convert_to_value(&self.curr, &c_right);
+                let is_function = self.curr.insn_call_native1(Some("op_is_function"), op_is_function, get_type::<fn(&'static int) -> bool>(), [&constructor]);
+                self.check_type(&is_function, "the right side of 'instanceof' must be a function");
+                let sig = get_type::<fn(&'static int, &'static int) -> bool>();
+                self.curr.insn_call_native2(Some("op_instance_of"), op_instance_of, sig, [&val, &constructor])
+            }
         };
         let bool_val = self.curr.insn_convert(&val, get_type::<bool>(), false);
         (bool_val, &self.curr)
     }
     fn compile_unary_op(&'a self, op:UnaryOp, val:&Expr) -> CompiledValue<'a> {
+        match op {
+            UnaryDelete => return (match val.def {
+                GetConstFieldExpr(_, _) | GetFieldExpr(_, _) => {
+                    let (obj, key) = self.compile_field(val);
+                    let sig = get_type::<fn(&'static int, &'static int) -> bool>();
+                    let deleted = self.curr.insn_call_native2(Some("field_delete"), field_delete, sig, [&obj, &key]);
+                    // strict mode code throws instead of giving false when a field can't be deleted
+                    if self.strict.get() {
+                        self.check_type(&deleted, "Cannot delete a field that isn't configurable");
+                    }
+                    deleted
+                },
+                // bindings can't be deleted
+                LocalExpr(_) => false.compile(&self.curr),
+                _ => {
+                    self.compile(val);
+                    true.compile(&self.curr)
+                }
+            }, &self.curr),
+            UnaryVoid => {
+                self.compile(val);
+                return (self.undefined(), &self.curr);
+            },
+            UnaryIncrementPre | UnaryIncrementPost | UnaryDecrementPre | UnaryDecrementPost =>
+                return (self.compile_update(op, val), &self.curr),
+            _ => ()
+        }
         let (c_val, _) = self.compile(val);
         (match op {
-            UnaryMinus => -c_val,
-            UnaryPlus => c_val,
+            UnaryMinus => -self.to_number(c_val),
+            UnaryPlus => self.to_number(c_val),
             UnaryNot => {
-                let c_not = !c_val;
+                let c_not = self.not(c_val);
                 self.curr.insn_convert(&c_not, get_type::<bool>(), false)
             },
-            _ => unimplemented!()
+            // the other operators have been compiled already
+            _ => unreachable!()
         }, &self.curr)
     }
     fn compile_return(&'a self, val:Option<Box<Expr>>) -> CompiledValue<'a> {
-        match val {
-            Some(box ref val) => {
-                let (c_val, _) = self.compile(val);
-                self.curr.insn_return(&c_val)
+        // the value is worked out before the finally blocks around the return run
+        let c_val = match val {
+            Some(box ref val) => self.compile_value(val),
+            None => convert_to_value(&self.curr, &self.undefined())
+        };
+        self.run_finalisers(0);
+        self.compile_exit(&c_val);
+        (self.undefined(), &self.curr)
+    }
+    fn compile_throw(&'a self, val:&Expr) -> CompiledValue<'a> {
+        let (c_val, _) = self.compile(val);
+        let thrown = convert_to_value(&self.curr, &c_val);
+        self.curr.insn_throw(&thrown);
+        (self.undefined(), &self.curr)
+    }
+    fn compile_try(&'a self, expr:&Expr, catch:Option<(String, Box<Expr>)>, finally:Option<Box<Expr>>) -> CompiledValue<'a> {
+        let thrown = Value::new(&self.curr, get_type::<&'static int>());
+        let mut start = Label::new(&self.curr);
+        let mut end = Label::new(&self.curr);
+        let mut done = Label::new(&self.curr);
+        match finally {
+            Some(box ref finally) => self.finalisers.borrow_mut().push(finally.clone()),
+            None => ()
+        }
+        self.curr.insn_label(&mut start);
+        self.compile(expr);
+        self.curr.insn_label(&mut end);
+        self.curr.insn_branch(&mut done);
+        // the range that still needs the finally block to run if it throws
+        let unwound = match catch {
+            Some((name, box ref catch)) => {
+                let mut catch_start = Label::new(&self.curr);
+                let mut catch_end = Label::new(&self.curr);
+                self.curr.insn_label(&mut catch_start);
+                // the catch parameter is only in scope in the catch block
+                let shadowed = self.enter_lexicals(vec!(name.clone()));
+                self.initialise_lexical(&name);
+                self.store_name(&name, &thrown);
+                self.compile(catch);
+                self.leave_lexicals(shadowed);
+                self.curr.insn_label(&mut catch_end);
+                self.curr.insn_branch(&mut done);
+                self.handlers.borrow_mut().push(Handler {
+                    start: start,
+                    end: end,
+                    target: catch_start.clone(),
+                    thrown: thrown.clone()
+                });
+                (catch_start, catch_end)
+            },
+            None => (start, end)
+        };
+        match finally {
+            Some(box ref finally) => {
+                self.finalisers.borrow_mut().pop();
+                let (unwound_start, unwound_end) = unwound;
+                let mut unwind = Label::new(&self.curr);
+                self.curr.insn_label(&mut unwind);
+                self.live.borrow_mut().push(thrown.clone());
+                self.compile(finally);
+                self.live.borrow_mut().pop();
+                self.curr.insn_throw(&thrown);
+                self.handlers.borrow_mut().push(Handler {
+                    start: unwound_start,
+                    end: unwound_end,
+                    target: unwind,
+                    thrown: thrown.clone()
+                });
+                self.curr.insn_label(&mut done);
+                self.compile(finally);
             },
             None => {
-                self.curr.insn_default_return()
+                self.curr.insn_label(&mut done);
             }
+        }
+        (self.undefined(), &self.curr)
+    }
+    fn compile_get_const_field(&'a self, obj:&Expr, field:String) -> CompiledValue<'a> {
+        let c_obj = self.compile_value(obj);
+        let c_field = field.compile(&self.curr);
+        let c_field = convert_to_value(&self.curr, &c_field);
+        let sig = get_type::<fn(&'static int, &'static int) -> &'static int>();
+        (self.curr.insn_call_native2(Some("field_get"), field_get, sig, [&c_obj, &c_field]), &self.curr)
+    }
+    fn compile_get_field(&'a self, obj:&Expr, field:&Expr) -> CompiledValue<'a> {
+        let c_obj = self.compile_value(obj);
+        let c_field = self.compile_value(field);
+        let sig = get_type::<fn(&'static int, &'static int) -> &'static int>();
+        (self.curr.insn_call_native2(Some("field_get"), field_get, sig, [&c_obj, &c_field]), &self.curr)
+    }
+    fn compile_call(&'a self, func:&Expr, args:Vec<Element>) -> CompiledValue<'a> {
+        let global = self.curr.get_param(0);
+        let value_sig = get_type::<fn(&'static int, &'static int) -> &'static int>();
+        // a method is called with the object it was got from as `this`
+        let (c_func, c_this) = match func.def {
+            // `super(...)` runs the parent class's constructor on the object being constructed
+            SuperExpr => {
+                let sig = get_type::<fn(&'static int) -> &'static int>();
+                let c_func = self.curr.insn_call_native1(Some("super_constructor"), super_constructor, sig, [&self.scope()]);
+                (c_func, Some(self.this_value()))
+            },
+            // and a method of the parent class is called on the same object as the method calling it
+            GetConstFieldExpr(box Expr {def: SuperExpr, ..}, _) | GetFieldExpr(box Expr {def: SuperExpr, ..}, _) => {
+                let (c_obj, c_key) = self.compile_field(func);
+                let c_func = self.curr.insn_call_native2(Some("field_get"), field_get, value_sig.clone(), [&c_obj, &c_key]);
+                (c_func, Some(self.this_value()))
+            },
+            GetConstFieldExpr(_, _) | GetFieldExpr(_, _) => {
+                let (c_obj, c_key) = self.compile_field(func);
+                let c_func = self.curr.insn_call_native2(Some("field_get"), field_get, value_sig.clone(), [&c_obj, &c_key]);
+                (c_func, Some(c_obj))
+            },
+            _ => (self.compile_value(func), None)
+        };
+        // the function and `this` are kept over arguments that can suspend the body they're in
+        let kept = args.iter().any(|arg| suspends(arg.expr()));
+        let (c_func, c_this) = if kept {
+            (self.keep(c_func), c_this.map(|c_this| self.keep(c_this)))
+        } else {
+            (c_func, c_this)
         };
+        let c_args = self.compile_elements(&args);
+        if kept {
+            self.release(if c_this.is_some() {2} else {1});
+        }
+        match c_this {
+            Some(c_this) => {
+                let this_sig = get_type::<fn(&'static int, &'static int)>();
+                self.curr.insn_call_native2(Some("call_set_this"), call_set_this, this_sig, [&c_args, &c_this]);
+            },
+            None => ()
+        }
+        let call_sig = get_type::<fn(&'static int, &'static int, &'static int) -> &'static int>();
+        let result = self.curr.insn_call_native3(Some("call_function"), call_function, call_sig, [&global, &c_func, &c_args]);
+        self.throw_if_threw(&c_args, &result);
+        (result, &self.curr)
+    }
+    fn compile_construct(&'a self, func:&Expr, args:Vec<Element>) -> CompiledValue<'a> {
+        let global = self.curr.get_param(0);
+        let c_func = self.compile_value(func);
+        let c_args = self.compile_elements(&args);
+        let sig = get_type::<fn(&'static int, &'static int, &'static int) -> &'static int>();
+        let result = self.curr.insn_call_native3(Some("call_construct"), call_construct, sig, [&global, &c_func, &c_args]);
+        self.throw_if_threw(&c_args, &result);
+        (result, &self.curr)
+    }
+    fn compile_object_decl(&'a self, props:Vec<PropertyDef>) -> CompiledValue<'a> {
+        let new_sig = get_type::<fn(&'static int) -> &'static int>();
+        let obj = self.curr.insn_call_native1(Some("object_new"), object_new, new_sig, [&self.curr.get_param(0)]);
+        let spread_sig = get_type::<fn(&'static int, &'static int)>();
+        let define_sig = get_type::<fn(&'static int, &'static int, &'static int)>();
+        for prop in props.iter() {
+            let (key, kind) = match *prop {
+                ValueProperty(ref key, _) => (key, PlainMethod),
+                MethodProperty(ref key, ref kind, _) => (key, kind.clone()),
+                SpreadProperty(ref value) => {
+                    let c_value = self.compile_value(value);
+                    self.curr.insn_call_native2(Some("object_spread"), object_spread, spread_sig.clone(), [&obj, &c_value]);
+                    continue;
+                }
+            };
+            // the key is worked out before the value
+            let c_key = match *key {
+                NamedKey(ref name) => {
+                    let c_name = name.compile(&self.curr);
+                    convert_to_value(&self.curr, &c_name)
+                },
+                ComputedKey(ref key) => self.compile_value(key)
+            };
+            // methods, getters and setters find `super` through the object they're defined on
+            let c_value = match *prop {
+                MethodProperty(_, _, ref func) => self.compile_function(func, Some(&obj)),
+                _ => self.compile_value(prop.expr())
+            };
+            let args = [&obj, &c_key, &c_value];
+            match kind {
+                PlainMethod => self.curr.insn_call_native3(Some("object_define_value"), object_define_value, define_sig.clone(), args),
+                GetMethod => self.curr.insn_call_native3(Some("object_define_getter"), object_define_getter, define_sig.clone(), args),
+                SetMethod => self.curr.insn_call_native3(Some("object_define_setter"), object_define_setter, define_sig.clone(), args)
+            };
+        }
+        (obj, &self.curr)
+    }
+    fn compile_array_decl(&'a self, items:Vec<Element>) -> CompiledValue<'a> {
+        (self.compile_elements(&items), &self.curr)
+    }
+    fn compile_class_decl(&'a self, name:Option<String>, parent:Option<Box<Expr>>, constructor:&Expr, members:Vec<ClassMember>) -> CompiledValue<'a> {
+        let global = self.curr.get_param(0);
+        let c_parent = match parent {
+            Some(box ref parent) => self.compile_value(parent),
+            None => convert_to_value(&self.curr, &self.undefined())
+        };
+        let proto_sig = get_type::<fn(&'static int, &'static int) -> &'static int>();
+        let prototype = self.curr.insn_call_native2(Some("class_prototype"), class_prototype, proto_sig, [&global, &c_parent]);
+        // the whole of a class is strict mode code
+        let strict = self.strict.get();
+        self.strict.set(true);
+        // the constructor and the methods on the prototype find the parent's methods through the prototype
+        let c_constructor = self.compile_function(constructor, Some(&prototype));
+        let new_sig = get_type::<fn(&'static int, &'static int, &'static int) -> &'static int>();
+        let class = self.curr.insn_call_native3(Some("class_new"), class_new, new_sig, [&c_constructor, &c_parent, &prototype]);
+        let define_sig = get_type::<fn(&'static int, String, &'static int)>();
+        for member in members.iter() {
+            let target = if member.is_static {&class} else {&prototype};
+            let c_name = member.name.compile(&self.curr);
+            let c_func = self.compile_function(&member.func, Some(target));
+            let args = [target, &c_name, &c_func];
+            match member.kind {
+                PlainMethod => self.curr.insn_call_native3(Some("class_define_method"), class_define_method, define_sig.clone(), args),
+                GetMethod => self.curr.insn_call_native3(Some("class_define_getter"), class_define_getter, define_sig.clone(), args),
+                SetMethod => self.curr.insn_call_native3(Some("class_define_setter"), class_define_setter, define_sig.clone(), args)
+            };
+        }
+        self.strict.set(strict);
+        match name {
+            Some(name) => {
+                self.initialise_lexical(&name);
+                self.store_name(&name, &class);
+            },
+            None => ()
+        }
+        (class, &self.curr)
+    }
+    fn compile_directive(&'a self, directive:String) -> CompiledValue<'a> {
+        // directives are only at the start of a script or function, which has a compiler of its
+        // own, so everything after `"use strict"` in it is strict
+        if directive.as_slice() == "use strict" {
+            self.use_strict();
+        }
+        self.compile_const(&CString(directive))
+    }
+    fn compile_this(&'a self) -> CompiledValue<'a> {
+        (self.curr.get_param(2), &self.curr)
+    }
+    fn compile_super(&'a self) -> CompiledValue<'a> {
+        let sig = get_type::<fn(&'static int) -> &'static int>();
+        (self.curr.insn_call_native1(Some("super_base"), super_base, sig, [&self.scope()]), &self.curr)
+    }
+    fn compile_function_decl(&'a self, name:Option<String>, params:Params, body:&Expr) -> CompiledValue<'a> {
+        (self.compile_named_closure(PlainFunction, name, &params, body), &self.curr)
+    }
+    fn compile_generator_decl(&'a self, name:Option<String>, params:Params, body:&Expr) -> CompiledValue<'a> {
+        (self.compile_named_closure(GeneratorFunction, name, &params, body), &self.curr)
+    }
+    fn compile_arrow_function_decl(&'a self, params:Params, body:&Expr) -> CompiledValue<'a> {
+        (self.compile_closure(ArrowFunction, &params, body, None), &self.curr)
+    }
+    fn compile_async_function_decl(&'a self, name:Option<String>, params:Params, body:&Expr) -> CompiledValue<'a> {
+        (self.compile_named_closure(AsyncFunction, name, &params, body), &self.curr)
+    }
+    fn compile_async_arrow_function_decl(&'a self, params:Params, body:&Expr) -> CompiledValue<'a> {
+        (self.compile_closure(AsyncArrowFunction, &params, body, None), &self.curr)
+    }
+    fn compile_yield(&'a self, val:Option<Box<Expr>>) -> CompiledValue<'a> {
+        let c_val = match val {
+            Some(box ref val) => self.compile_value(val),
+            None => convert_to_value(&self.curr, &self.undefined())
+        };
+        (self.compile_suspension(&c_val), &self.curr)
+    }
+    fn compile_delegate_yield(&'a self, val:&Expr) -> CompiledValue<'a> {
+        let global = self.curr.get_param(0);
+        let activation = self.curr.get_param(1);
+        let iterator = self.compile_value(val);
+        // the iterator is resumed the same way as the generator delegating to it, starting with `next()`
+        let undefined = convert_to_value(&self.curr, &self.undefined());
+        let sent = Value::new(&self.curr, get_type::<&'static int>());
+        self.curr.insn_store(&sent, &undefined);
+        let kind = Value::new(&self.curr, get_type::<i32>());
+        self.curr.insn_store(&kind, &(ResumeNext as i32).compile(&self.curr));
+        let mut step = Label::new(&self.curr);
+        let mut finished = Label::new(&self.curr);
+        self.curr.insn_label(&mut step);
+        let method_sig = get_type::<fn(&'static int, i32) -> &'static int>();
+        let method = self.curr.insn_call_native2(Some("delegate_method"), delegate_method, method_sig, [&iterator, &kind]);
+        let new_sig = get_type::<fn(&'static int) -> &'static int>();
+        let c_args = self.curr.insn_call_native1(Some("array_new"), array_new, new_sig, [&global]);
+        let set_sig = get_type::<fn(&'static int, &'static int)>();
+        self.curr.insn_call_native2(Some("array_push"), array_push, set_sig.clone(), [&c_args, &sent]);
+        self.curr.insn_call_native2(Some("call_set_this"), call_set_this, set_sig, [&c_args, &iterator]);
+        let call_sig = get_type::<fn(&'static int, &'static int, &'static int) -> &'static int>();
+        let result = self.curr.insn_call_native3(Some("call_function"), call_function, call_sig, [&global, &method, &c_args]);
+        self.throw_if_threw(&c_args, &result);
+        let done_sig = get_type::<fn(&'static int) -> bool>();
+        let done = self.curr.insn_call_native1(Some("result_done"), result_done, done_sig, [&result]);
+        self.curr.insn_branch_if(&done, &mut finished);
+        // each result the iterator gives that isn't done is given as it is by this generator
+        self.live.borrow_mut().push(iterator.clone());
+        self.compile_suspend(&result);
+        self.live.borrow_mut().pop();
+        let sent_sig = get_type::<fn(&'static int) -> &'static int>();
+        let c_sent = self.curr.insn_call_native1(Some("frame_sent"), frame_sent, sent_sig, [&activation]);
+        self.curr.insn_store(&sent, &c_sent);
+        let kind_sig = get_type::<fn(&'static int) -> i32>();
+        let c_kind = self.curr.insn_call_native1(Some("frame_sent_kind"), frame_sent_kind, kind_sig, [&activation]);
+        self.curr.insn_store(&kind, &c_kind);
+        self.curr.insn_branch(&mut step);
+        self.curr.insn_label(&mut finished);
+        let value_sig = get_type::<fn(&'static int, &'static int) -> &'static int>();
+        let c_key = "value".into_string().compile(&self.curr);
+        let c_key = convert_to_value(&self.curr, &c_key);
+        let value = self.curr.insn_call_native2(Some("field_get"), field_get, value_sig, [&result, &c_key]);
+        // and when it's done after being returned from, so is the generator delegating to it
+        let mut not_returned = Label::new(&self.curr);
+        let returned = self.curr.insn_eq(&kind, &(ResumeReturn as i32).compile(&self.curr));
+        self.curr.insn_branch_if_not(&returned, &mut not_returned);
+        self.run_finalisers(0);
+        self.compile_exit(&value);
+        self.curr.insn_label(&mut not_returned);
+        (value, &self.curr)
+    }
+    fn compile_await(&'a self, val:&Expr) -> CompiledValue<'a> {
+        // the body is suspended with the value awaited, and resumed with what it settles to
+        let c_val = self.compile_value(val);
+        (self.compile_suspension(&c_val), &self.curr)
+    }
+    fn compile_local(&'a self, name:String) -> CompiledValue<'a> {
+        self.check_initialised(&name);
+        let local = self.locals.borrow().find(&name).map(|local| local.clone());
+        (match local {
+            // the value is copied so assigning to the local later doesn't change it
+            Some(local) => {
+                let copy = Value::new(&self.curr, local.get_type());
+                self.curr.insn_store(&copy, &local);
+                copy
+            },
+            // a name that isn't in a register is in the scope chain, unless it's a `let` binding
+            // that's never been set
+            None if self.is_captured(&name) || !self.lexicals.borrow().contains_key(&name) => self.load_unbound(&name),
+            None => self.undefined()
+        }, &self.curr)
+    }
+    fn compile_var_decl(&'a self, kind:DeclKind, vars:Vec<(Pattern, Option<Expr>)>) -> CompiledValue<'a> {
+        for (pattern, val) in vars.move_iter() {
+            // `var x;` leaves x as it was, but `let x;` initialises it to undefined
+            let c_val = match val {
+                Some(ref val) => self.compile(val).val0(),
+                None if kind == VarDecl => continue,
+                None => self.undefined()
+            };
+            self.assign_pattern(&pattern, c_val, kind != VarDecl);
+        }
+        (self.undefined(), &self.curr)
+    }
+    fn compile_assign(&'a self, left:&Pattern, right:&Expr) -> CompiledValue<'a> {
+        let (c_right, _) = self.compile(right);
+        self.assign_pattern(left, c_right.clone(), false);
+        (c_right, &self.curr)
+    }
+    fn compile_assign_op(&'a self, op:BinOp, left:&Pattern, right:&Expr) -> CompiledValue<'a> {
+        let apply = |old:Value<'a>| {
+            let (old, c_right) = self.compile_after(old, right);
+            match op {
+                BinNum(num) => self.num_op(num, old, c_right),
+                BinBit(bit) => self.bit_op(bit, old, c_right),
+                // the parser only makes compound assignments for numeric and bitwise operators
+                _ => unreachable!()
+            }
+        };
+        (match *left {
+            NamePattern(ref name) => {
+                let (old, _) = self.compile_local(name.clone());
+                let new = apply(old);
+                self.store_name(name, &new);
+                new
+            },
+            ExprPattern(box ref field) => {
+                // the object and key are evaluated once for both getting and setting the field
+                let (c_obj, c_key) = self.compile_field(field);
+                let kept = suspends(right);
+                let (c_obj, c_key) = if kept {(self.keep(c_obj), self.keep(c_key))} else {(c_obj, c_key)};
+                let get_sig = get_type::<fn(&'static int, &'static int) -> &'static int>();
+                let old = self.curr.insn_call_native2(Some("field_get"), field_get, get_sig, [&c_obj, &c_key]);
+                let new = apply(old);
+                if kept {
+                    self.release(2);
+                }
+                let c_new = convert_to_value(&self.curr, &new);
+                self.store_field(&c_obj, &c_key, &c_new);
+                new
+            },
+            _ => unreachable!()
+        }, &self.curr)
+    }
+    fn compile_while_loop(&'a self, cond:&Expr, expr:&Expr) -> CompiledValue<'a> {
+        let mut start = Label::new(&self.curr);
+        let mut end = Label::new(&self.curr);
+        self.push_jump_target(&end, Some(&start));
+        self.curr.insn_label(&mut start);
+        let (c_cond, _) = self.compile(cond);
+        let c_cond = self.convert_bool(c_cond);
+        self.curr.insn_branch_if_not(&c_cond, &mut end);
+        self.compile(expr);
+        self.curr.insn_branch(&mut start);
+        self.curr.insn_label(&mut end);
+        self.jumps.borrow_mut().pop();
+        (self.undefined(), &self.curr)
+    }
+    fn compile_do_while_loop(&'a self, expr:&Expr, cond:&Expr) -> CompiledValue<'a> {
+        let mut start = Label::new(&self.curr);
+        let mut next = Label::new(&self.curr);
+        let mut end = Label::new(&self.curr);
+        self.push_jump_target(&end, Some(&next));
+        self.curr.insn_label(&mut start);
+        self.compile(expr);
+        self.curr.insn_label(&mut next);
+        let (c_cond, _) = self.compile(cond);
+        let c_cond = self.convert_bool(c_cond);
+        self.curr.insn_branch_if(&c_cond, &mut start);
+        self.curr.insn_label(&mut end);
+        self.jumps.borrow_mut().pop();
         (self.undefined(), &self.curr)
     }
-}
\ No newline at end of file
+    fn compile_for_loop(&'a self, init:Option<Box<Expr>>, cond:Option<Box<Expr>>, step:Option<Box<Expr>>, expr:&Expr) -> CompiledValue<'a> {
+        let mut start = Label::new(&self.curr);
+        let mut next = Label::new(&self.curr);
+        let mut end = Label::new(&self.curr);
+        self.push_jump_target(&end, Some(&next));
+        let shadowed = self.enter_lexicals(lexical_names(init.iter().map(|init| &**init)));
+        match init {
+            Some(box ref init) => { self.compile(init); },
+            None => ()
+        }
+        self.curr.insn_label(&mut start);
+        match cond {
+            Some(box ref cond) => {
+                let (c_cond, _) = self.compile(cond);
+                let c_cond = self.convert_bool(c_cond);
+                self.curr.insn_branch_if_not(&c_cond, &mut end);
+            },
+            None => ()
+        }
+        self.compile(expr);
+        self.curr.insn_label(&mut next);
+        match step {
+            Some(box ref step) => { self.compile(step); },
+            None => ()
+        }
+        self.curr.insn_branch(&mut start);
+        self.curr.insn_label(&mut end);
+        self.leave_lexicals(shadowed);
+        self.jumps.borrow_mut().pop();
+        (self.undefined(), &self.curr)
+    }
+    fn compile_for_in_loop(&'a self, var:&Expr, obj:&Expr, expr:&Expr) -> CompiledValue<'a> {
+        let (pattern, declare) = match var.def {
+            LocalExpr(ref name) => (NamePattern(name.clone()), false),
+            VarDeclExpr(kind, ref vars) => (vars[0].ref0().clone(), kind != VarDecl),
+            _ => unreachable!()
+        };
+        let shadowed = self.enter_lexicals(lexical_names(Some(var).move_iter()));
+        let mut start = Label::new(&self.curr);
+        let mut next = Label::new(&self.curr);
+        let mut end = Label::new(&self.curr);
+        self.push_jump_target(&end, Some(&next));
+        let c_obj = self.compile_value(obj);
+        // the keys are taken before the loop starts, so fields added by the body aren't visited
+        let keys_sig = get_type::<fn(&'static int, &'static int) -> &'static int>();
+        let keys = self.curr.insn_call_native2(Some("for_in_keys"), for_in_keys, keys_sig, [&self.curr.get_param(0), &c_obj]);
+        let count_sig = get_type::<fn(&'static int) -> i32>();
+        let count = self.curr.insn_call_native1(Some("for_in_count"), for_in_count, count_sig, [&keys]);
+        let index = Value::new(&self.curr, get_type::<i32>());
+        self.curr.insn_store(&index, &0i32.compile(&self.curr));
+        self.curr.insn_label(&mut start);
+        let in_range = self.curr.insn_lt(&index, &count);
+        self.curr.insn_branch_if_not(&in_range, &mut end);
+        let key_sig = get_type::<fn(&'static int, i32) -> &'static int>();
+        let key = self.curr.insn_call_native2(Some("pattern_get_index"), pattern_get_index, key_sig, [&keys, &index]);
+        // but fields deleted by the body before they're visited are skipped
+        let has_sig = get_type::<fn(&'static int, &'static int) -> bool>();
+        let present = self.curr.insn_call_native2(Some("for_in_present"), for_in_present, has_sig, [&c_obj, &key]);
+        self.curr.insn_branch_if_not(&present, &mut next);
+        self.assign_pattern(&pattern, key, declare);
+        self.live.borrow_mut().push_all([c_obj.clone(), keys.clone(), count.clone(), index.clone()]);
+        self.compile(expr);
+        self.release(4);
+        self.curr.insn_label(&mut next);
+        let incremented = index + 1i32.compile(&self.curr);
+        self.curr.insn_store(&index, &incremented);
+        self.curr.insn_branch(&mut start);
+        self.curr.insn_label(&mut end);
+        self.leave_lexicals(shadowed);
+        self.jumps.borrow_mut().pop();
+        (self.undefined(), &self.curr)
+    }
+    fn compile_conditional(&'a self, cond:&Expr, if_expr:&Expr, else_expr:&Expr) -> CompiledValue<'a> {
+        let (c_cond, _) = self.compile(cond);
+        let c_cond = self.convert_bool(c_cond);
+        // the branches can compile to different types, so both are converted to values
+        let result = Value::new(&self.curr, get_type::<&'static int>());
+        let mut else_label = Label::new(&self.curr);
+        let mut end = Label::new(&self.curr);
+        self.curr.insn_branch_if_not(&c_cond, &mut else_label);
+        let c_if = self.compile_value(if_expr);
+        self.curr.insn_store(&result, &c_if);
+        self.curr.insn_branch(&mut end);
+        self.curr.insn_label(&mut else_label);
+        let c_else = self.compile_value(else_expr);
+        self.curr.insn_store(&result, &c_else);
+        self.curr.insn_label(&mut end);
+        (result, &self.curr)
+    }
+    fn compile_switch(&'a self, value:&Expr, cases:Vec<(Expr, Vec<Expr>)>, default:Option<Box<Expr>>) -> CompiledValue<'a> {
+        let mut end = Label::new(&self.curr);
+        let mut default_label = Label::new(&self.curr);
+        let mut case_labels : Vec<Label<'a>> = cases.iter().map(|_| Label::new(&self.curr)).collect();
+        self.push_jump_target(&end, None);
+        let (c_val, _) = self.compile(value);
+        let shadowed = self.enter_lexicals(lexical_names(cases.iter().flat_map(|&(_, ref block)| block.iter())));
+        self.live.borrow_mut().push(c_val.clone());
+        for (&(ref cond, _), label) in cases.iter().zip(case_labels.mut_iter()) {
+            let (c_cond, _) = self.compile(cond);
+            let matches = self.equals(c_val.clone(), c_cond, true);
+            self.curr.insn_branch_if(&matches, label);
+        }
+        self.live.borrow_mut().pop();
+        self.curr.insn_branch(&mut default_label);
+        // cases fall through into each other until they break
+        for (&(_, ref block), label) in cases.iter().zip(case_labels.mut_iter()) {
+            self.curr.insn_label(label);
+            for expr in block.iter() {
+                self.compile(expr);
+            }
+        }
+        self.curr.insn_label(&mut default_label);
+        match default {
+            Some(box ref default) => { self.compile(default); },
+            None => ()
+        }
+        self.curr.insn_label(&mut end);
+        self.leave_lexicals(shadowed);
+        self.jumps.borrow_mut().pop();
+        (self.undefined(), &self.curr)
+    }
+    fn compile_break(&'a self, label:Option<String>) -> CompiledValue<'a> {
+        let (mut break_to, finalisers) = {
+            let jumps = self.jumps.borrow();
+            let target = jumps.iter().rev().find(|target| match label {
+                Some(ref label) => target.labels.contains(label),
+                None => target.breakable
+            }).unwrap();
+            (target.break_to.clone(), target.finalisers)
+        };
+        self.run_finalisers(finalisers);
+        self.curr.insn_branch(&mut break_to);
+        (self.undefined(), &self.curr)
+    }
+    fn compile_continue(&'a self, label:Option<String>) -> CompiledValue<'a> {
+        let (mut continue_to, finalisers) = {
+            let jumps = self.jumps.borrow();
+            let target = jumps.iter().rev().find(|target| target.continue_to.is_some() && match label {
+                Some(ref label) => target.labels.contains(label),
+                None => true
+            }).unwrap();
+            (target.continue_to.clone().unwrap(), target.finalisers)
+        };
+        self.run_finalisers(finalisers);
+        self.curr.insn_branch(&mut continue_to);
+        (self.undefined(), &self.curr)
+    }
+    fn compile_labelled(&'a self, label:String, expr:&Expr) -> CompiledValue<'a> {
+        match expr.def {
+            // a label on another label or a loop is given to the statement it labels
+            WhileLoopExpr(_, _) | DoWhileLoopExpr(_, _) | ForLoopExpr(_, _, _, _) | ForInLoopExpr(_, _, _) | SwitchExpr(_, _, _) | LabelledExpr(_, _) => {
+                self.pending_labels.borrow_mut().push(label);
+                self.compile(expr)
+            },
+            _ => {
+                let mut end = Label::new(&self.curr);
+                let mut labels = replace(&mut *self.pending_labels.borrow_mut(), Vec::new());
+                labels.push(label);
+                self.jumps.borrow_mut().push(JumpTarget {
+                    labels: labels,
+                    breakable: false,
+                    break_to: end.clone(),
+                    continue_to: None,
+                    finalisers: self.finalisers.borrow().len()
+                });
+                let compiled = self.compile(expr);
+                self.jumps.borrow_mut().pop();
+                self.curr.insn_label(&mut end);
+                compiled
+            }
+        }
+    }
+    fn compile_template(&'a self, tag:Option<Box<Expr>>, strings:Vec<(String, String)>, exprs:Vec<Expr>) -> CompiledValue<'a> {
+        let global = self.curr.get_param(0);
+        match tag {
+            None => {
+                let concat_sig = get_type::<fn(&'static int, &'static int) -> &'static int>();
+                let (ref head, _) = strings[0];
+                let c_head = head.compile(&self.curr);
+                let mut result = convert_to_value(&self.curr, &c_head);
+                for (expr, &(ref cooked, _)) in exprs.iter().zip(strings.iter().skip(1)) {
+                    let (c_expr, _) = self.compile(expr);
+                    let c_expr = convert_to_value(&self.curr, &c_expr);
+                    result = self.curr.insn_call_native2(Some("template_concat"), template_concat, concat_sig.clone(), [&result, &c_expr]);
+                    let c_cooked = cooked.compile(&self.curr);
+                    let c_cooked = convert_to_value(&self.curr, &c_cooked);
+                    result = self.curr.insn_call_native2(Some("template_concat"), template_concat, concat_sig.clone(), [&result, &c_cooked]);
+                }
+                (result, &self.curr)
+            },
+            Some(box tag) => {
+                let (c_tag, _) = self.compile(&tag);
+                let c_tag = convert_to_value(&self.curr, &c_tag);
+                let site = (new_template_site() as i32).compile(&self.curr);
+                // the strings array is only filled in the first time this site runs, then reused
+                let mut filled = Label::new(&self.curr);
+                let cached_sig = get_type::<fn(i32) -> bool>();
+                let cached = self.curr.insn_call_native1(Some("template_cached"), template_cached, cached_sig, [&site]);
+                let strings_sig = get_type::<fn(&'static int, i32) -> &'static int>();
+                let c_strings = self.curr.insn_call_native2(Some("template_strings"), template_strings, strings_sig, [&global, &site]);
+                self.curr.insn_branch_if(&cached, &mut filled);
+                let push_sig = get_type::<fn(&'static int, String, String)>();
+                for &(ref cooked, ref raw) in strings.iter() {
+                    let c_cooked = cooked.compile(&self.curr);
+                    let c_raw = raw.compile(&self.curr);
+                    self.curr.insn_call_native3(Some("template_push_string"), template_push_string, push_sig.clone(), [&c_strings, &c_cooked, &c_raw]);
+                }
+                self.curr.insn_label(&mut filled);
+                // the tag is called with the strings array then the value of each substitution
+                let new_sig = get_type::<fn(&'static int) -> &'static int>();
+                let c_args = self.curr.insn_call_native1(Some("array_new"), array_new, new_sig, [&global]);
+                let push_sig = get_type::<fn(&'static int, &'static int)>();
+                self.curr.insn_call_native2(Some("array_push"), array_push, push_sig.clone(), [&c_args, &c_strings]);
+                for expr in exprs.iter() {
+                    let c_expr = self.compile_value(expr);
+                    self.curr.insn_call_native2(Some("array_push"), array_push, push_sig.clone(), [&c_args, &c_expr]);
+                }
+                let call_sig = get_type::<fn(&'static int, &'static int, &'static int) -> &'static int>();
+                let result = self.curr.insn_call_native3(Some("call_function"), call_function, call_sig, [&global, &c_tag, &c_args]);
+                self.throw_if_threw(&c_args, &result);
+                (result, &self.curr)
+            }
+        }
+    }
+    fn compile_import(&'a self, _:Vec<ImportSpecifier>, _:String) -> CompiledValue<'a> {
+        (self.undefined(), &self.curr)
+    }
+    fn compile_export(&'a self, export:Export) -> CompiledValue<'a> {
+        match export {
+            ExportDeclaration(box decl) => self.compile(&decl),
+            // a default export that isn't a named declaration is bound to a name of its own
+            ExportDefault(box decl) => if declared_names(&decl).is_empty() {
+                let c_decl = self.compile_value(&decl);
+                self.store_name(&DEFAULT_BINDING.into_string(), &c_decl);
+                (self.undefined(), &self.curr)
+            } else {
+                self.compile(&decl)
+            },
+            // the bindings exported by name or from other modules are linked by the module record
+            ExportNames(_, _) | ExportAll(_) => (self.undefined(), &self.curr)
+        }
+    }
+    fn compile_error(&'a self, message:String) -> CompiledValue<'a> {
+        // the rest of the script still runs up to the statement that couldn't be parsed
+        let c_message = message.compile(&self.curr);
+        let sig = get_type::<fn(&'static int, String) -> &'static int>();
+        let error = self.curr.insn_call_native2(Some("syntax_error"), syntax_error, sig, [&self.curr.get_param(0), &c_message]);
+        self.curr.insn_throw(&error);
+        (self.undefined(), &self.curr)
+    }
+}
+/// Returns true if a compiled value is a pointer, which is a Javascript value or a string
+fn is_pointer(val:&Value) -> bool {
+    val.get_type().get_kind() == Pointer
+}
+/// Get the name of a statement if it's a function declaration, which is bound at the start of its block
+fn declared_function(statement:&Expr) -> Option<String> {
+    match declaration(statement).def {
+        FunctionDeclExpr(Some(ref name), _, _) | GeneratorDeclExpr(Some(ref name), _, _) |
+        AsyncFunctionDeclExpr(Some(ref name), _, _) => Some(name.clone()),
+        _ => None
+    }
+}
+/// Returns true if an expression can suspend the generator or async function it's in, which
+/// the functions inside it can't
+fn suspends(expr:&Expr) -> bool {
+    match expr.def {
+        YieldExpr(_) | DelegateYieldExpr(_) | AwaitExpr(_) => true,
+        FunctionDeclExpr(_, _, _) | GeneratorDeclExpr(_, _, _) | AsyncFunctionDeclExpr(_, _, _) |
+        ArrowFunctionDeclExpr(_, _) | AsyncArrowFunctionDeclExpr(_, _) => false,
+        _ => expr.children().iter().any(|child| suspends(*child))
+    }
+}
+/// Get the names declared by `let` and `const` in some statements
+fn lexical_names<'b, I:Iterator<&'b Expr>>(statements:I) -> Vec<String> {
+    let mut names = Vec::new();
+    for expr in statements {
+        match declaration(expr).def {
+            VarDeclExpr(kind, ref vars) if kind != VarDecl =>
+                names.extend(vars.iter().flat_map(|&(ref pattern, _)| pattern.names().move_iter())),
+            ClassDeclExpr(Some(ref name), _, _, _) =>
+                names.push(name.clone()),
+            _ => ()
+        }
+    }
+    names
+}
+/// Make the `ReferenceError` thrown when a binding is used before it's initialised
+fn uninitialised_error(global: JSVal, name: &i8) -> JSVal {
+    let name = unsafe { NativeString::new(name, false) };
+    new_error(global, "ReferenceError", format!("{} is not initialised", name.as_str().unwrap()).as_slice())
+}
+/// Make the `ReferenceError` thrown when a name that isn't defined is used
+fn undefined_error(global: JSVal, name: &i8) -> JSVal {
+    let name = unsafe { NativeString::new(name, false) };
+    new_error(global, "ReferenceError", format!("{} is not defined", name.as_str().unwrap()).as_slice())
+}
+/// Returns true if a name is defined in a scope or the scopes it's in
+fn scope_has(scope: JSVal, name: &i8) -> bool {
+    let name = unsafe { NativeString::new(name, false) };
+    scope.has_field(name.as_str().unwrap())
+}
+/// Get the value of a name from a scope or the scopes it's in
+fn scope_get(scope: JSVal, name: &i8) -> JSVal {
+    let name = unsafe { NativeString::new(name, false) };
+    scope.get_field(name.as_str().unwrap())
+}
+/// Set the value of a name in the scope it's defined in, or in the outermost scope if it isn't
+fn scope_set(scope: JSVal, name: &i8, val: JSVal) {
+    let name = unsafe { NativeString::new(name, false) };
+    let name = name.as_str().unwrap();
+    let mut scope = scope;
+    loop {
+        let outer = match *scope {
+            VObject(ref data) if !data.borrow().contains_key(&name.into_string()) =>
+                data.borrow().find(&INSTANCE_PROTOTYPE.into_string()).map(|prop| prop.value),
+            _ => None
+        };
+        match outer {
+            Some(outer) if outer.is_object() => scope = outer,
+            _ => break
+        }
+    }
+    scope.set_field(name, val);
+}
+/// Define a name declared by `var` in a scope as undefined, unless it's already defined there
+fn scope_declare(scope: JSVal, name: &i8) {
+    let name = unsafe { NativeString::new(name, false) };
+    let name = name.as_str().unwrap();
+    let defined = match *scope {
+        VObject(ref data) => data.borrow().contains_key(&name.into_string()),
+        _ => true
+    };
+    if !defined {
+        scope.set_field(name, JSVal::undefined());
+    }
+}
+/// Make a scope object for a block, which inherits the names it doesn't define from the scope it's in
+fn scope_new(scope: JSVal) -> JSVal {
+    let inner = JSVal::new_obj(None);
+    inner.set_field(INSTANCE_PROTOTYPE, scope);
+    inner
+}
+/// Get the arguments that the function an activation object was made for was called with
+fn scope_arguments(activation: JSVal) -> JSVal {
+    activation.get_field(ARGUMENTS)
+}
+/// Get the object that `super` refers to in the method a scope is in, which is the prototype of
+/// the object the method was defined on
+fn super_base(scope: JSVal) -> JSVal {
+    scope.get_field(HOME).get_field(INSTANCE_PROTOTYPE)
+}
+/// Get the parent class of the class whose constructor a scope is in, for a `super(...)` call
+fn super_constructor(scope: JSVal) -> JSVal {
+    scope.get_field(HOME).get_field("constructor").get_field(INSTANCE_PROTOTYPE)
+}
+local_data_key!(COMPILED_CODE: Vec<CompiledCode>)
+/// Keep the code compiled for a function so the functions made from it can run it, giving the
+/// index it can be found at
+fn register_code(code: CompiledCode) -> uint {
+    let mut codes = COMPILED_CODE.replace(None).unwrap_or(Vec::new());
+    codes.push(code);
+    let index = codes.len() - 1;
+    COMPILED_CODE.replace(Some(codes));
+    index
+}
+/// Get the record of a function made from compiled code, which holds what it runs and the scope it runs in
+fn function_record(func: JSVal) -> JSVal {
+    match *func {
+        VFunction(ref func) => func.borrow().scope.unwrap(),
+        _ => unreachable!()
+    }
+}
+/// Make a function that runs the code registered under `code` in the scope given
+fn function_new(global: JSVal, scope: JSVal, code: i32) -> JSVal {
+    let record = JSVal::new_obj(None);
+    record.set_field("global", global);
+    record.set_field("environment", scope);
+    record.set_field("code", to_value(code));
+    // a function that isn't strict is called with the global object as `this` in place of undefined or null
+    let strict = COMPILED_CODE.get().unwrap()[code as uint].strict;
+    let mut func = JSFunction::new(call_compiled, Vec::new(), strict);
+    func.scope = Some(record);
+    let func = JSVal::new(VFunction(RefCell::new(func)));
+    // the objects a function constructs inherit from its prototype, which leads back to it
+    let prototype = JSVal::new_obj(Some(global));
+    prototype.set_prop("constructor", Property {
+        configurable: true,
+        writable: true,
+        ..Property::new(func)
+    });
+    func.set_field(PROTOTYPE, prototype);
+    func
+}
+/// Give an arrow function the `this` of the code it was made in
+fn function_set_this(func: JSVal, this: JSVal) {
+    function_record(func).set_field("this", this);
+}
+/// Give a method the object it was defined on, whose prototype its `super` refers to
+fn function_set_home(func: JSVal, home: JSVal) {
+    function_record(func).set_field(HOME, home);
+}
+/// Call a function made from compiled code with an activation object as its scope, which holds
+/// its arguments and the bindings its functions refer to, and inherits from the scope it was made in
+fn call_compiled(args: Vec<JSVal>, _: JSVal, record: JSVal, this: JSVal) -> ResultValue {
+    // a function runs with the global object it was made with, even if a getter is called without one
+    let global = record.get_field("global");
+    let activation = scope_new(record.get_field("environment"));
+    let arguments = new_array(global, args);
+    activation.set_field(ARGUMENTS, arguments);
+    // an arrow function uses the `this` and `arguments` of the function it was made in
+    let this = match record.get_prop("this") {
+        Some(prop) => prop.value,
+        None => {
+            activation.set_field("arguments", arguments);
+            this
+        }
+    };
+    let home = record.get_field(HOME);
+    if !home.is_undefined() {
+        activation.set_field(HOME, home);
+    }
+    let code = record.get_field("code");
+    let compiled = COMPILED_CODE.get().unwrap()[code.to_int() as uint];
+    let result = (compiled.code)(global, activation, this);
+    let is_async = compiled.kind == AsyncFunction || compiled.kind == AsyncArrowFunction;
+    match take_uncaught() {
+        // an async function gives a rejected promise instead of throwing
+        Some(thrown) if is_async => {
+            let promise = new_promise(global);
+            reject_promise(global, promise, thrown);
+            Ok(promise)
+        },
+        Some(thrown) => Err(thrown),
+        // a generator or async function has only bound its parameters, and the rest of its body
+        // is run by the generator it gives or until each `await` by the promise it gives
+        None if compiled.kind.is_resumable() => {
+            let frame = JSVal::new_obj(None);
+            frame.set_field("global", global);
+            frame.set_field("activation", activation);
+            frame.set_field("this", this);
+            frame.set_field("code", code);
+            let resume = JSFunction::make_closure(generator_resume, ["value", "kind"], frame);
+            Ok(if is_async {
+                run_async(global, resume)
+            } else {
+                new_generator(global, resume)
+            })
+        },
+        None => Ok(result)
+    }
+}
+/// Run the body of a generator or async function from where it was suspended, with the value
+/// and the way it's resumed, until its next `yield` or `await` or until it returns, giving the
+/// iterator result for that
+fn generator_resume(args: Vec<JSVal>, _: JSVal, frame: JSVal, _: JSVal) -> ResultValue {
+    let activation = frame.get_field("activation");
+    activation.set_field(SENT, args.as_slice().get(0).map(|arg| *arg).unwrap_or(JSVal::undefined()));
+    activation.set_field(SENT_KIND, args.as_slice().get(1).map(|arg| *arg).unwrap_or(to_value(ResumeNext as i32)));
+    let compiled = COMPILED_CODE.get().unwrap()[frame.get_field("code").to_int() as uint];
+    let result = (compiled.code)(frame.get_field("global"), activation, frame.get_field("this"));
+    match take_uncaught() {
+        Some(thrown) => Err(thrown),
+        None => Ok(result)
+    }
+}
+/// Get where the body of a generator was last suspended, which is 0 if it hasn't run yet
+fn frame_point(activation: JSVal) -> i32 {
+    from_value::<i32>(activation.get_field(YIELD_POINT)).unwrap_or(0)
+}
+/// Record where the body of a generator is suspended
+fn frame_suspend(activation: JSVal, point: i32) {
+    activation.set_field(YIELD_POINT, to_value(point));
+}
+/// Keep the value of a register in a generator's activation object while its body is suspended
+fn frame_save(activation: JSVal, index: i32, value: JSVal) {
+    activation.set_field(format!("__register{}__", index).as_slice(), value);
+}
+/// Get the value of a register kept while the body of a generator was suspended
+fn frame_restore(activation: JSVal, index: i32) -> JSVal {
+    activation.get_field(format!("__register{}__", index).as_slice())
+}
+/// Get the value a generator was last resumed with
+fn frame_sent(activation: JSVal) -> JSVal {
+    activation.get_field(SENT)
+}
+/// Get how a generator was last resumed
+fn frame_sent_kind(activation: JSVal) -> i32 {
+    activation.get_field(SENT_KIND).to_int()
+}
+/// Make the iterator result that a generator gives
+fn generator_result(global: JSVal, value: JSVal, done: bool) -> JSVal {
+    iter_result(global, value, done)
+}
+/// Get the method of an iterator that resumes it the way given, for a `yield*` delegating to it
+fn delegate_method(iterator: JSVal, kind: i32) -> JSVal {
+    iterator.get_field(if kind == ResumeNext as i32 {
+        "next"
+    } else if kind == ResumeReturn as i32 {
+        "return"
+    } else {
+        "throw"
+    })
+}
+/// Check if an iterator result is done
+fn result_done(result: JSVal) -> bool {
+    result.get_field("done").is_true()
+}
+/// Raise a number to the power of another
+fn num_pow(base: f64, exponent: f64) -> f64 {
+    base.powf(exponent)
+}
+/// Make the `SyntaxError` thrown when a statement that couldn't be parsed is reached
+fn syntax_error(global: JSVal, message: &i8) -> JSVal {
+    let message = unsafe { NativeString::new(message, false) };
+    new_error(global, "SyntaxError", message.as_str().unwrap())
+}
+/// Make the `TypeError` thrown when an operator is used on a value of the wrong type
+fn type_error(global: JSVal, message: &i8) -> JSVal {
+    let message = unsafe { NativeString::new(message, false) };
+    new_error(global, "TypeError", message.as_str().unwrap())
+}
+local_data_key!(TEMPLATE_SITES: Vec<Option<JSVal>>)
+/// Reserve a slot for the strings array of a tagged template call site
+fn new_template_site() -> uint {
+    let mut sites = TEMPLATE_SITES.replace(None).unwrap_or(Vec::new());
+    sites.push(None);
+    let site = sites.len() - 1;
+    TEMPLATE_SITES.replace(Some(sites));
+    site
+}
+/// Check if the strings array of a tagged template call site has already been made
+fn template_cached(site: i32) -> bool {
+    match TEMPLATE_SITES.get() {
+        Some(sites) => sites[site as uint].is_some(),
+        None => false
+    }
+}
+/// Get the strings array of a tagged template call site, making an empty one the first time
+fn template_strings(global: JSVal, site: i32) -> JSVal {
+    let mut sites = TEMPLATE_SITES.replace(None).unwrap();
+    let strings = match sites[site as uint] {
+        Some(strings) => strings,
+        None => {
+            let strings = new_array(global, Vec::new());
+            strings.set_field("raw", new_array(global, Vec::new()));
+            *sites.get_mut(site as uint) = Some(strings);
+            strings
+        }
+    };
+    TEMPLATE_SITES.replace(Some(sites));
+    strings
+}
+/// Add a cooked string and its raw form to the strings array of a tagged template
+fn template_push_string(strings: JSVal, cooked: &i8, raw: &i8) {
+    fn push(array: JSVal, text: &i8) {
+        let text = unsafe { NativeString::new(text, false) };
+        array_push(array, to_value(text.as_str().unwrap()));
+    }
+    push(strings, cooked);
+    push(strings.get_field("raw"), raw);
+}
+/// Make the prototype of a class, which inherits from the parent class's prototype
+fn class_prototype(global: JSVal, parent: JSVal) -> JSVal {
+    if parent.is_undefined() {
+        return JSVal::new_obj(Some(global));
+    }
+    let prototype = JSVal::new_obj(None);
+    // `extends null` makes a class whose instances don't inherit from `Object.prototype`
+    if !parent.is_null() {
+        prototype.set_field(INSTANCE_PROTOTYPE, parent.get_field(PROTOTYPE));
+    }
+    prototype
+}
+/// Make a class out of its constructor and prototype, making the constructor inherit from the
+/// parent class
+fn class_new(constructor: JSVal, parent: JSVal, prototype: JSVal) -> JSVal {
+    if !parent.is_null_or_undefined() {
+        constructor.set_field(INSTANCE_PROTOTYPE, parent);
+    }
+    prototype.set_field("constructor", constructor);
+    constructor.set_field(PROTOTYPE, prototype);
+    constructor
+}
+/// Define a method, or the getter or setter of a property, on a class or its prototype
+fn class_define(target: JSVal, name: &i8, func: JSVal, kind: MethodKind) {
+    let name = unsafe { NativeString::new(name, false) };
+    let name = name.as_str().unwrap();
+    // a getter and setter with the same name share a property
+    let mut prop = match target.get_prop(name) {
+        Some(prop) if kind != PlainMethod => prop,
+        _ => Property::new(JSVal::undefined())
+    };
+    prop.configurable = true;
+    match kind {
+        PlainMethod => {
+            prop.writable = true;
+            prop.value = func;
+        },
+        GetMethod => prop.get = func,
+        SetMethod => prop.set = func
+    }
+    target.set_prop(name, prop);
+}
+/// Define a method on a class or its prototype
+fn class_define_method(target: JSVal, name: &i8, func: JSVal) {
+    class_define(target, name, func, PlainMethod)
+}
+/// Define the getter of a property on a class or its prototype
+fn class_define_getter(target: JSVal, name: &i8, func: JSVal) {
+    class_define(target, name, func, GetMethod)
+}
+/// Define the setter of a property on a class or its prototype
+fn class_define_setter(target: JSVal, name: &i8, func: JSVal) {
+    class_define(target, name, func, SetMethod)
+}
+/// Make an empty object for an object literal
+fn object_new(global: JSVal) -> JSVal {
+    JSVal::new_obj(Some(global))
+}
+/// Define a property, method, getter or setter of an object literal, which unlike a member of a
+/// class can be enumerated
+fn object_define(obj: JSVal, key: &str, val: JSVal, kind: MethodKind) {
+    let existing = match *obj {
+        VObject(ref data) => data.borrow().find(&key.into_string()).map(|prop| *prop),
+        _ => None
+    };
+    // a getter and setter with the same key share a property, but any other property replaces it
+    let mut prop = match existing {
+        Some(prop) if kind != PlainMethod && (!prop.get.is_undefined() || !prop.set.is_undefined()) => prop,
+        _ => Property::new(JSVal::undefined())
+    };
+    prop.configurable = true;
+    prop.enumerable = true;
+    match kind {
+        PlainMethod => {
+            prop.writable = true;
+            prop.value = val;
+        },
+        GetMethod => prop.get = val,
+        SetMethod => prop.set = val
+    }
+    obj.set_prop(key, prop);
+}
+/// Define a property or method of an object literal
+fn object_define_value(obj: JSVal, key: JSVal, val: JSVal) {
+    object_define(obj, key.to_string().as_slice(), val, PlainMethod)
+}
+/// Define the getter of a property of an object literal
+fn object_define_getter(obj: JSVal, key: JSVal, func: JSVal) {
+    object_define(obj, key.to_string().as_slice(), func, GetMethod)
+}
+/// Define the setter of a property of an object literal
+fn object_define_setter(obj: JSVal, key: JSVal, func: JSVal) {
+    object_define(obj, key.to_string().as_slice(), func, SetMethod)
+}
+/// Copy the enumerable fields of a value, or each character of a string, into an object literal,
+/// getting the value of fields that have getters
+fn object_spread(obj: JSVal, value: JSVal) {
+    match *value {
+        VString(ref text) => for (i, ch) in text.as_slice().chars().enumerate() {
+            object_define(obj, i.to_string().as_slice(), to_value(String::from_char(1, ch)), PlainMethod);
+        },
+        _ => match from_value::<ObjectData>(value) {
+            Ok(data) => for (key, prop) in data.iter().filter(|&(key, prop)| prop.enumerable && key.as_slice() != INSTANCE_PROTOTYPE) {
+                let val = if prop.get.is_undefined() {
+                    prop.value
+                } else {
+                    value.get_field(key.as_slice())
+                };
+                object_define(obj, key.as_slice(), val, PlainMethod);
+            },
+            Err(_) => ()
+        }
+    }
+}
+/// Make an empty array for an array literal or the arguments of a call
+fn array_new(global: JSVal) -> JSVal {
+    new_array(global, Vec::new())
+}
+/// Add a value to the end of an array
+fn array_push(array: JSVal, value: JSVal) {
+    let length = array.get_field("length").to_int();
+    array.set_field(length.to_string().as_slice(), value);
+    array.set_field("length", to_value(length + 1));
+}
+/// Add each item of an array-like value, or each character of a string, to the end of an array
+fn array_spread(array: JSVal, value: JSVal) {
+    match *value {
+        VString(ref text) => for ch in text.as_slice().chars() {
+            array_push(array, to_value(String::from_char(1, ch)));
+        },
+        _ => {
+            let length = value.get_field("length").to_int();
+            for i in range(0, length) {
+                array_push(array, value.get_field(i.to_string().as_slice()));
+            }
+        }
+    }
+}
+/// Set the value of `this` that a function is called with
+fn call_set_this(args: JSVal, this: JSVal) {
+    args.set_field("this", this);
+}
+/// Call a function with an array of arguments, giving the value it threw and marking the arguments if it throws
+fn call_function(global: JSVal, func: JSVal, args: JSVal) -> JSVal {
+    let length = args.get_field("length").to_int();
+    let values = range(0, length).map(|i| args.get_field(i.to_string().as_slice())).collect();
+    let result = match *func {
+        VFunction(ref func) => func.borrow().call(values, global, global, args.get_field("this")),
+        _ => Err(to_value(format!("{} is not a function", func)))
+    };
+    match result {
+        Ok(value) => value,
+        Err(thrown) => {
+            args.set_field("threw", to_value(true));
+            thrown
+        }
+    }
+}
+/// Construct an object by calling a function with an array of arguments and a new object
+/// that inherits from its prototype as `this`
+fn call_construct(global: JSVal, func: JSVal, args: JSVal) -> JSVal {
+    let this = JSVal::new_obj(Some(global));
+    this.set_field(INSTANCE_PROTOTYPE, func.get_field(PROTOTYPE));
+    call_set_this(args, this);
+    let result = call_function(global, func, args);
+    if call_threw(args) || result.is_object() {
+        result
+    } else {
+        this
+    }
+}
+/// Check if calling a function with these arguments threw
+fn call_threw(args: JSVal) -> bool {
+    args.get_field("threw").is_true()
+}
+/// Join the string forms of two values for an untagged template
+fn template_concat(left: JSVal, right: JSVal) -> JSVal {
+    to_value(format!("{}{}", left, right))
+}
+/// Get the keys that a for-in loop visits, which are the enumerable keys of a value and the
+/// objects it inherits from, without the keys it shadows
+fn for_in_keys(global: JSVal, obj: JSVal) -> JSVal {
+    let mut keys = Vec::new();
+    let mut seen : Vec<String> = Vec::new();
+    let mut current = obj;
+    loop {
+        let data = match *current {
+            VObject(ref data) => data.borrow().clone(),
+            VFunction(ref func) => func.borrow().object.clone(),
+            VString(ref text) => {
+                keys.extend(range(0, text.as_slice().char_len()).map(|i| to_value(i.to_string())));
+                break;
+            },
+            _ => break
+        };
+        for (key, prop) in data.iter() {
+            if key.as_slice() == INSTANCE_PROTOTYPE || seen.contains(key) {
+                continue;
+            }
+            seen.push(key.clone());
+            if prop.enumerable {
+                keys.push(to_value(key.clone()));
+            }
+        }
+        current = match data.find(&INSTANCE_PROTOTYPE.into_string()) {
+            Some(prop) => prop.value,
+            None => break
+        };
+    }
+    new_array(global, keys)
+}
+/// Count the keys that a for-in loop visits
+fn for_in_count(keys: JSVal) -> i32 {
+    keys.get_field("length").to_int()
+}
+/// Returns true if a key that a for-in loop is about to visit hasn't been deleted
+fn for_in_present(obj: JSVal, key: JSVal) -> bool {
+    match *obj {
+        VString(_) => true,
+        _ => obj.has_field(key.to_string().as_slice())
+    }
+}
+/// Get `undefined`, which compiled code passes the global object to
+fn undefined_value(_: JSVal) -> JSVal {
+    JSVal::undefined()
+}
+/// Get `null`, which compiled code passes the global object to
+fn null_value(_: JSVal) -> JSVal {
+    JSVal::new(VNull)
+}
+/// Create a `RegExp` object for a regular expression literal
+fn regexp_value(global: JSVal, source: &i8, flags: &i8) -> JSVal {
+    unsafe {
+        let source = NativeString::new(source, false);
+        let flags = NativeString::new(flags, false);
+        new_regexp(global, source.as_str().unwrap(), flags.as_str().unwrap()).unwrap()
+    }
+}
+/// Get a field of a value
+fn field_get(obj: JSVal, key: JSVal) -> JSVal {
+    obj.get_field(key.to_string().as_slice())
+}
+/// Get an item of a value being destructured by an array pattern
+fn pattern_get_index(arr: JSVal, index: i32) -> JSVal {
+    arr.get_field(index.to_string().as_slice())
+}
+/// Check if part of a destructured value is undefined, so its default should be used
+fn pattern_is_undefined(val: JSVal) -> bool {
+    val.is_undefined()
+}
+/// Convert a value to a number
+fn value_to_num(val: JSVal) -> f64 {
+    val.to_num()
+}
+/// Convert a value to a 32-bit integer
+fn value_to_int(val: JSVal) -> i32 {
+    val.to_int()
+}
+/// Convert a value to a boolean
+fn value_to_bool(val: JSVal) -> bool {
+    val.is_true()
+}
+/// Add two values, joining them if either is a string
+fn value_add(left: JSVal, right: JSVal) -> JSVal {
+    left + right
+}
+/// Returns true if two values are the same object or function
+fn same_object(left: JSVal, right: JSVal) -> bool {
+    match (&*left, &*right) {
+        (&VObject(_), &VObject(_)) | (&VFunction(_), &VFunction(_)) => left.ptr.ptr_eq(&right.ptr),
+        _ => false
+    }
+}
+/// Returns true if two values are equal once they're converted to the same type, as `==` does
+fn value_equals(left: JSVal, right: JSVal) -> bool {
+    same_object(left, right) || left == right
+}
+/// Returns true if two values have the same type and are equal, as `===` does
+fn value_strict_equals(left: JSVal, right: JSVal) -> bool {
+    match (&*left, &*right) {
+        (&VObject(_), _) | (&VFunction(_), _) | (_, &VObject(_)) | (_, &VFunction(_)) => same_object(left, right),
+        _ => left.get_type() == right.get_type() && left == right
+    }
+}
+/// Compare the order of two values, which compares two strings by their characters and anything
+/// else as numbers
+fn value_ordering(left: JSVal, right: JSVal) -> Option<Ordering> {
+    match (&*left, &*right) {
+        (&VString(ref left), &VString(ref right)) => Some(left.cmp(right)),
+        _ => left.partial_cmp(&right)
+    }
+}
+/// Returns true if a value is less than another
+fn value_lt(left: JSVal, right: JSVal) -> bool {
+    value_ordering(left, right) == Some(Less)
+}
+/// Returns true if a value is less than or equal to another
+fn value_leq(left: JSVal, right: JSVal) -> bool {
+    match value_ordering(left, right) {
+        Some(Less) | Some(Equal) => true,
+        _ => false
+    }
+}
+/// Returns true if a value is greater than another
+fn value_gt(left: JSVal, right: JSVal) -> bool {
+    value_ordering(left, right) == Some(Greater)
+}
+/// Returns true if a value is greater than or equal to another
+fn value_geq(left: JSVal, right: JSVal) -> bool {
+    match value_ordering(left, right) {
+        Some(Greater) | Some(Equal) => true,
+        _ => false
+    }
+}
+/// Remove a field from a value, returning false if it isn't configurable
+fn field_delete(obj: JSVal, key: JSVal) -> bool {
+    obj.remove_field(key.to_string().as_slice())
+}
+/// Returns true if a value is an object or function, which can have fields looked up with `in`
+fn op_is_object(val: JSVal) -> bool {
+    match *val {
+        VObject(_) | VFunction(_) => true,
+        _ => false
+    }
+}
+/// Returns true if a value is a function, which can be the constructor in `instanceof`
+fn op_is_function(val: JSVal) -> bool {
+    match *val {
+        VFunction(_) => true,
+        _ => false
+    }
+}
+/// Returns true if the object or its prototype chain has a field with the key given
+fn op_in(obj: JSVal, key: JSVal) -> bool {
+    obj.has_field(key.to_string().as_slice())
+}
+/// Returns true if the value was made by the constructor given or one inheriting from it
+fn op_instance_of(val: JSVal, constructor: JSVal) -> bool {
+    val.is_instance_of(constructor)
+}
+/// Returns true if a field of a value can be set, which it can't if it's read-only or only has a getter
+fn field_can_set(obj: JSVal, key: JSVal) -> bool {
+    obj.can_set_field(key.to_string().as_slice())
+}
+/// Set a field of a value
+fn field_set(obj: JSVal, key: JSVal, val: JSVal) {
+    obj.set_field(key.to_string().as_slice(), val);
+}
+/// Copy the fields of an object for the rest of an object pattern
+fn pattern_copy_object(global: JSVal, obj: JSVal) -> JSVal {
+    let copy = JSVal::new_obj(Some(global));
+    match from_value::<ObjectData>(obj) {
+        Ok(data) => for (key, prop) in data.iter().filter(|&(key, _)| key.as_slice() != INSTANCE_PROTOTYPE) {
+            copy.set_field(key.as_slice(), prop.value);
+        },
+        Err(_) => ()
+    }
+    copy
+}
+/// Remove a field that was destructured from the rest of an object pattern
+fn pattern_remove(obj: JSVal, key: &i8) {
+    let key = unsafe { NativeString::new(key, false) };
+    match *obj {
+        VObject(ref data) => { data.borrow_mut().remove(&key.as_str().unwrap().into_string()); },
+        _ => ()
+    }
+}
+/// Collect the items of an array from `start` onwards for the rest of an array pattern
+fn pattern_array_rest(global: JSVal, arr: JSVal, start: i32) -> JSVal {
+    let length = arr.get_field("length").to_int();
+    new_array(global, range(start, length).map(|i| arr.get_field(i.to_string().as_slice())).collect())
+}
@@ -14,11 +14,19 @@ use jit::{
     Float64
 };
 use JSVal = front::stdlib::value::Value;
-use front::stdlib::value::{VNull, to_value};
+use front::stdlib::value::to_value;
 use front::stdlib::value::ResultValue;
 use front::run::executor::{Executor, ExecutorConfig};
-use std::gc::GC;
 use std::c_str::CString;
+local_data_key!(UNCAUGHT: JSVal)
+/// Record a value that was thrown without being caught, so `execute` can return it as an error
+pub fn set_uncaught(thrown: JSVal) {
+    UNCAUGHT.replace(Some(thrown));
+}
+/// Take the value that was thrown without being caught by the code that just ran, if it threw
+pub fn take_uncaught() -> Option<JSVal> {
+    UNCAUGHT.replace(None)
+}
 /// A JIT executor
 pub struct JitExecutor {
     global: JSVal
@@ -40,13 +48,24 @@ impl<'a> Executor<(JITVal<'a>, &'a Function<'a>)> for JitExecutor {
         func.set_optimization_level(5);
         func.set_recompilable();
         func.compile();
-        Ok(func.with_closure3(|run:fn(JSVal, JSVal, JSVal) -> JSVal| {
+        UNCAUGHT.replace(None);
+        let result = func.with_closure3(|run:fn(JSVal, JSVal, JSVal) -> JSVal| {
             run(self.global, self.global, self.global)
-        }))
+        });
+        let result = match UNCAUGHT.replace(None) {
+            Some(thrown) => Err(thrown),
+            None => Ok(result)
+        };
+        // the jobs run even if the script threw, but what it threw is reported before anything they throw
+        let jobs = self.run_jobs();
+        match (result, jobs) {
+            (Ok(_), Err(thrown)) => Err(thrown),
+            (result, _) => result
+        }
     }
 }
-
-fn convert_to_value<'a>(func:&Function<'a>, val:&'a JITVal<'a>) -> JITVal<'a> {
+/// Convert a LibJIT value into a pointer to a Javascript value
+pub fn convert_to_value<'a>(func:&Function<'a>, val:&'a JITVal<'a>) -> JITVal<'a> {
     let val_type = val.get_type();
     let val_kind = val_type.get_kind();
     match val_kind {
@@ -67,17 +86,10 @@ fn convert_to_value<'a>(func:&Function<'a>, val:&'a JITVal<'a>) -> JITVal<'a> {
                 let sig = get_type::<fn(String) -> &'static int>();
                 func.insn_call_native1(Some("string_value"), string_value, sig, [val])
             } else {
-                fn ptr_value(ptr: &i8) -> JSVal {
-                    match ptr.to_uint() {
-                        Some(0u) => JSVal::undefined(),
-                        Some(1u) => JSVal {
-                            ptr: box(GC) VNull
-                        },
-                        ptr => fail!("Invalid pointer: {}", ptr)
-                    }
-                }
-                let sig = get_type::<fn(&'static i8) -> &'static int>();
-                func.insn_call_native1(Some("ptr_value"), ptr_value, sig, [val])
+                // anything else compiled code points to is already a Javascript value
+                let ptr = JITVal::new(func, get_type::<&'static int>());
+                func.insn_store(&ptr, val);
+                ptr
             }
         },
         Int | UInt => {
@@ -1,7 +1,7 @@
-use collections::TreeMap;
 use syntax::ast::expr::*;
 use syntax::ast::op::*;
 use syntax::ast::constant::Const;
+use syntax::ast::pattern::{Pattern, Params};
 /**
  * A compiler that transforms expressions into their compiled
  * form, typically through a library such as LibJIT or LLVM.
@@ -17,8 +17,14 @@ pub trait Compiler<'a, Compiled> {
                 self.compile_bin_op(op, &left, &right),
             ConstExpr(ref c) =>
                 self.compile_const(c),
+            DirectiveExpr(directive) =>
+                self.compile_directive(directive),
             LocalExpr(l) =>
                 self.compile_local(l),
+            ThisExpr =>
+                self.compile_this(),
+            SuperExpr =>
+                self.compile_super(),
             BlockExpr(vals) =>
                 self.compile_block(vals),
             GetConstFieldExpr(box obj, field) =>
@@ -29,30 +35,70 @@ pub trait Compiler<'a, Compiled> {
                 self.compile_call(&func, args),
             WhileLoopExpr(box cond, box expr) =>
                 self.compile_while_loop(&cond, &expr),
+            DoWhileLoopExpr(box expr, box cond) =>
+                self.compile_do_while_loop(&expr, &cond),
+            ForLoopExpr(init, cond, step, box expr) =>
+                self.compile_for_loop(init, cond, step, &expr),
+            ForInLoopExpr(box var, box obj, box expr) =>
+                self.compile_for_in_loop(&var, &obj, &expr),
+            BreakExpr(label) =>
+                self.compile_break(label),
+            ContinueExpr(label) =>
+                self.compile_continue(label),
+            LabelledExpr(label, box expr) =>
+                self.compile_labelled(label, &expr),
+            ConditionalExpr(box cond, box if_expr, box else_expr) =>
+                self.compile_conditional(&cond, &if_expr, &else_expr),
             IfExpr(box cond, box if_expr, else_expr) =>
                 self.compile_if(&cond, &if_expr, else_expr),
             SwitchExpr(box value, cases, default) =>
                 self.compile_switch(&value, cases, default),
-            ObjectDeclExpr(box fields) =>
-                self.compile_object_decl(&fields),
+            ObjectDeclExpr(props) =>
+                self.compile_object_decl(props),
             ArrayDeclExpr(values) =>
                 self.compile_array_decl(values),
             FunctionDeclExpr(name, args, box ret) =>
                 self.compile_function_decl(name, args, &ret),
+            GeneratorDeclExpr(name, args, box ret) =>
+                self.compile_generator_decl(name, args, &ret),
+            AsyncFunctionDeclExpr(name, args, box ret) =>
+                self.compile_async_function_decl(name, args, &ret),
             ArrowFunctionDeclExpr(args, box ret) =>
                 self.compile_arrow_function_decl(args, &ret),
+            AsyncArrowFunctionDeclExpr(args, box ret) =>
+                self.compile_async_arrow_function_decl(args, &ret),
             ConstructExpr(box func, args) =>
                 self.compile_construct(&func, args),
             ReturnExpr(val) =>
                 self.compile_return(val),
+            YieldExpr(val) =>
+                self.compile_yield(val),
+            DelegateYieldExpr(box val) =>
+                self.compile_delegate_yield(&val),
+            AwaitExpr(box val) =>
+                self.compile_await(&val),
             ThrowExpr(box val) =>
                 self.compile_throw(&val),
+            TryExpr(box expr, catch, finally) =>
+                self.compile_try(&expr, catch, finally),
             AssignExpr(box left, box right) =>
                 self.compile_assign(&left, &right),
-            VarDeclExpr(vars) =>
-                self.compile_var_decl(vars),
+            AssignOpExpr(op, box left, box right) =>
+                self.compile_assign_op(op, &left, &right),
+            VarDeclExpr(kind, vars) =>
+                self.compile_var_decl(kind, vars),
             TypeOfExpr(box expr) =>
-                self.compile_typeof(&expr)
+                self.compile_typeof(&expr),
+            TemplateExpr(tag, strings, exprs) =>
+                self.compile_template(tag, strings, exprs),
+            ClassDeclExpr(name, parent, box constructor, members) =>
+                self.compile_class_decl(name, parent, &constructor, members),
+            ImportExpr(specifiers, module) =>
+                self.compile_import(specifiers, module),
+            ExportExpr(export) =>
+                self.compile_export(export),
+            ErrorExpr(message) =>
+                self.compile_error(message)
         }
     }
     /// Compile a unary operation
@@ -88,10 +134,22 @@ pub trait Compiler<'a, Compiled> {
     fn compile_const(&'a self, _:&Const) -> Compiled {
         unimplemented!()
     }
+    /// Compile a directive, which gives its string and can change how the code after it is compiled
+    fn compile_directive(&'a self, _:String) -> Compiled {
+        unimplemented!()
+    }
     /// Compile a local variable
     fn compile_local(&'a self, _:String) -> Compiled {
         unimplemented!()
     }
+    /// Compile the `this` value
+    fn compile_this(&'a self) -> Compiled {
+        unimplemented!()
+    }
+    /// Compile a reference to the parent class
+    fn compile_super(&'a self) -> Compiled {
+        unimplemented!()
+    }
     /// Compile a block of expressions
     fn compile_block(&'a self, _:Vec<Expr>) -> Compiled {
         unimplemented!()
@@ -105,59 +163,139 @@ pub trait Compiler<'a, Compiled> {
         unimplemented!()
     }
     /// Compile a call to a function with some arguments
-    fn compile_call(&'a self, _:&Expr, _:Vec<Expr>) -> Compiled {
+    fn compile_call(&'a self, _:&Expr, _:Vec<Element>) -> Compiled {
         unimplemented!()
     }
     /// Compile a while loop
     fn compile_while_loop(&'a self, _:&Expr, _:&Expr) -> Compiled {
         unimplemented!()
     }
+    /// Compile a do-while loop
+    fn compile_do_while_loop(&'a self, _:&Expr, _:&Expr) -> Compiled {
+        unimplemented!()
+    }
+    /// Compile a C-style for loop
+    fn compile_for_loop(&'a self, _:Option<Box<Expr>>, _:Option<Box<Expr>>, _:Option<Box<Expr>>, _:&Expr) -> Compiled {
+        unimplemented!()
+    }
+    /// Compile a for-in loop over the keys of an object
+    fn compile_for_in_loop(&'a self, _:&Expr, _:&Expr, _:&Expr) -> Compiled {
+        unimplemented!()
+    }
+    /// Compile a break out of a loop, switch or labelled statement
+    fn compile_break(&'a self, _:Option<String>) -> Compiled {
+        unimplemented!()
+    }
+    /// Compile a continue to the next iteration of a loop
+    fn compile_continue(&'a self, _:Option<String>) -> Compiled {
+        unimplemented!()
+    }
+    /// Compile a labelled statement
+    fn compile_labelled(&'a self, _:String, _:&Expr) -> Compiled {
+        unimplemented!()
+    }
     /// Compile an if statement
     fn compile_if(&'a self, _:&Expr, _:&Expr, _:Option<Box<Expr>>) -> Compiled {
         unimplemented!()
     }
+    /// Compile a conditional expression, which gives the value of the branch that runs
+    fn compile_conditional(&'a self, _:&Expr, _:&Expr, _:&Expr) -> Compiled {
+        unimplemented!()
+    }
     /// Compile a switch statement
     fn compile_switch(&'a self, _:&Expr, Vec<(Expr, Vec<Expr>)>, Option<Box<Expr>>) -> Compiled {
         unimplemented!()
     }
     /// Compile an object declaration
-    fn compile_object_decl(&'a self, &TreeMap<String, Expr>) -> Compiled {
+    fn compile_object_decl(&'a self, Vec<PropertyDef>) -> Compiled {
         unimplemented!()
     }
     /// Compile an array declaration
-    fn compile_array_decl(&'a self, Vec<Expr>) -> Compiled {
+    fn compile_array_decl(&'a self, Vec<Element>) -> Compiled {
         unimplemented!()
     }
     /// Compile a function declaration
-    fn compile_function_decl(&'a self, _:Option<String>, _:Vec<String>, _:&Expr) -> Compiled {
+    fn compile_function_decl(&'a self, _:Option<String>, _:Params, _:&Expr) -> Compiled {
+        unimplemented!()
+    }
+    /// Compile a generator function declaration
+    fn compile_generator_decl(&'a self, _:Option<String>, _:Params, _:&Expr) -> Compiled {
+        unimplemented!()
+    }
+    /// Compile an async function declaration
+    fn compile_async_function_decl(&'a self, _:Option<String>, _:Params, _:&Expr) -> Compiled {
         unimplemented!()
     }
     /// Compile an arrow function declaration
-    fn compile_arrow_function_decl(&'a self, _:Vec<String>, _:&Expr) -> Compiled {
+    fn compile_arrow_function_decl(&'a self, _:Params, _:&Expr) -> Compiled {
+        unimplemented!()
+    }
+    /// Compile an async arrow function declaration
+    fn compile_async_arrow_function_decl(&'a self, _:Params, _:&Expr) -> Compiled {
         unimplemented!()
     }
     /// Compile a construction of an object
-    fn compile_construct(&'a self, _:&Expr, _:Vec<Expr>) -> Compiled {
+    fn compile_construct(&'a self, _:&Expr, _:Vec<Element>) -> Compiled {
         unimplemented!()
     }
     /// Compile a return expression
     fn compile_return(&'a self, _:Option<Box<Expr>>) -> Compiled {
         unimplemented!()
     }
+    /// Compile a yield expression
+    fn compile_yield(&'a self, _:Option<Box<Expr>>) -> Compiled {
+        unimplemented!()
+    }
+    /// Compile a yield expression that delegates to another iterator
+    fn compile_delegate_yield(&'a self, _:&Expr) -> Compiled {
+        unimplemented!()
+    }
+    /// Compile an await expression
+    fn compile_await(&'a self, _:&Expr) -> Compiled {
+        unimplemented!()
+    }
     /// Compile a throw expression
     fn compile_throw(&'a self, _:&Expr) -> Compiled {
         unimplemented!()
     }
+    /// Compile a try statement with an optional catch block and an optional finally block
+    fn compile_try(&'a self, _:&Expr, _:Option<(String, Box<Expr>)>, _:Option<Box<Expr>>) -> Compiled {
+        unimplemented!()
+    }
     /// Compile an assignment
-    fn compile_assign(&'a self, _:&Expr, _:&Expr) -> Compiled {
+    fn compile_assign(&'a self, _:&Pattern, _:&Expr) -> Compiled {
+        unimplemented!()
+    }
+    /// Compile a compound assignment
+    fn compile_assign_op(&'a self, _:BinOp, _:&Pattern, _:&Expr) -> Compiled {
         unimplemented!()
     }
     /// Compile a variable declaration
-    fn compile_var_decl(&'a self, _:Vec<(String, Option<Expr>)>) -> Compiled {
+    fn compile_var_decl(&'a self, _:DeclKind, _:Vec<(Pattern, Option<Expr>)>) -> Compiled {
         unimplemented!()
     }
     /// Compile a typeof expression
     fn compile_typeof(&'a self, _:&Expr) -> Compiled {
         unimplemented!()
     }
+    /// Compile a template literal, which is tagged if the first expression is given
+    fn compile_template(&'a self, _:Option<Box<Expr>>, _:Vec<(String, String)>, _:Vec<Expr>) -> Compiled {
+        unimplemented!()
+    }
+    /// Compile a class with a name, parent class, constructor and members
+    fn compile_class_decl(&'a self, _:Option<String>, _:Option<Box<Expr>>, _:&Expr, _:Vec<ClassMember>) -> Compiled {
+        unimplemented!()
+    }
+    /// Compile an import declaration, whose bindings are linked by the module record before the module runs
+    fn compile_import(&'a self, _:Vec<ImportSpecifier>, _:String) -> Compiled {
+        unimplemented!()
+    }
+    /// Compile an export declaration, which declares the bindings it exports if it has a declaration
+    fn compile_export(&'a self, _:Export) -> Compiled {
+        unimplemented!()
+    }
+    /// Compile a statement that couldn't be parsed, with why it couldn't be
+    fn compile_error(&'a self, _:String) -> Compiled {
+        unimplemented!()
+    }
 }
\ No newline at end of file
@@ -1,8 +1,17 @@
 use front::stdlib::value::{
     Value, 
-    ResultValue
+    ResultValue,
+    VFunction
 };
 use std::default::Default;
+local_data_key!(JOB_QUEUE: Vec<Job>)
+/// A job on the microtask queue, which calls a function with an argument once the script that
+/// queued it has finished
+struct Job {
+    global: Value,
+    job: Value,
+    argument: Value
+}
 
 /// An execution engine which runs whatever is generated by the `Compiler`
 pub trait Executor<Compiled> {
@@ -20,8 +29,48 @@ pub trait Executor<Compiled> {
     fn set_global<'a>(&self, field: &'a str, value:Value) -> Value {
         self.get_global_obj().set_field(field, value)
     }
-    /// Execute a compiled expression
+    /// Execute a compiled expression, then run the jobs it queued
     fn execute(&self, comp:&Compiled) -> ResultValue;
+    #[inline]
+    /// Run the jobs in the microtask queue until it's empty, including those queued while running
+    /// them, which lets an embedder run the reactions to promises it settled from outside a script
+    fn run_jobs(&self) -> Result<(), Value> {
+        run_jobs()
+    }
+}
+/// Queue a job on the microtask queue, which calls `job` with `argument` and the global object
+/// it was queued from once the script running has finished
+pub fn enqueue_job(global:Value, job:Value, argument:Value) {
+    let mut queue = JOB_QUEUE.replace(None).unwrap_or(Vec::new());
+    queue.push(Job {
+        global: global,
+        job: job,
+        argument: argument
+    });
+    JOB_QUEUE.replace(Some(queue));
+}
+/// Take the job at the front of the microtask queue
+fn next_job() -> Option<Job> {
+    let mut queue = JOB_QUEUE.replace(None).unwrap_or(Vec::new());
+    let job = queue.remove(0);
+    JOB_QUEUE.replace(Some(queue));
+    job
+}
+/// Run the jobs on the microtask queue in the order they were queued, until it's empty or one of
+/// them throws, which leaves the jobs after it on the queue
+pub fn run_jobs() -> Result<(), Value> {
+    // jobs can queue more jobs, so the queue is checked again after each of them
+    loop {
+        let Job {global, job, argument} = match next_job() {
+            Some(job) => job,
+            None => return Ok(())
+        };
+        let ran = match *job {
+            VFunction(ref func) => func.borrow().call(vec!(argument), global, global, Value::undefined()),
+            _ => Ok(Value::undefined())
+        };
+        try!(ran);
+    }
 }
 /// Configuration for the executor
 pub struct ExecutorConfig {
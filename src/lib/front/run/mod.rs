@@ -1,4 +1,6 @@
 /// For compiling Javascript values
 pub mod compiler;
 /// For executing the compiled Javascript values
-pub mod executor;
\ No newline at end of file
+pub mod executor;
+/// For loading, linking and evaluating modules
+pub mod module;
\ No newline at end of file
@@ -0,0 +1,438 @@
+use front::stdlib::object::{INSTANCE_PROTOTYPE, Property};
+use front::stdlib::value::{Value, VObject, ResultValue, to_value};
+use front::stdlib::function::Function;
+use syntax::ast::expr::*;
+use syntax::ast::pos::Position;
+use syntax::error::{SyntaxError, LexFailure};
+use syntax::lexer::Lexer;
+use syntax::parser::Parser;
+use collections::treemap::TreeMap;
+use std::fmt;
+/// The name that the default export of a module is bound to in its environment when it isn't a
+/// named function or class declaration
+pub static DEFAULT_BINDING: &'static str = "*default*";
+/// Finds modules and loads their source text
+pub trait ModuleLoader {
+    /// Resolve the specifier of a module imported by the module with the key `referrer`, or by the
+    /// embedder if there is no referrer, into the key that identifies the module
+    fn resolve(&self, specifier:&str, referrer:Option<&str>) -> Result<String, String>;
+    /// Load the source text of the module with the key given
+    fn load(&self, key:&str) -> Result<String, String>;
+}
+#[deriving(Clone)]
+/// An error loading, linking or evaluating a module
+pub enum ModuleError {
+    /// When the specifier given couldn't be resolved, and why
+    ResolveError(String, String),
+    /// When the source text of the module with the key given couldn't be loaded, and why
+    LoadError(String, String),
+    /// When the source text of the module with the key given isn't a valid module
+    ModuleSyntaxError(String, SyntaxError),
+    /// When a name is imported or re-exported from the module with the key given, which doesn't export it
+    MissingExport(String, String),
+    /// When a name is exported by more than one of the modules that the module with the key given
+    /// exports all the exports of, so it's not clear which binding it refers to
+    AmbiguousExport(String, String),
+    /// When evaluating the module with the key given threw a value
+    EvaluationError(String, Value)
+}
+impl fmt::Show for ModuleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ResolveError(ref specifier, ref why) => write!(f, "Couldn't resolve module \"{}\": {}", specifier, why),
+            LoadError(ref key, ref why) => write!(f, "Couldn't load module {}: {}", key, why),
+            ModuleSyntaxError(ref key, ref err) => write!(f, "{}: {}", key, err),
+            MissingExport(ref key, ref name) => write!(f, "SyntaxError: module {} doesn't export {}", key, name),
+            AmbiguousExport(ref key, ref name) => write!(f, "SyntaxError: module {} exports {} from more than one module", key, name),
+            EvaluationError(ref key, ref thrown) => write!(f, "{}: Failed with {}", key, thrown)
+        }
+    }
+}
+#[deriving(Clone, PartialEq, Show)]
+/// How far a module has got towards being evaluated
+pub enum ModuleStatus {
+    /// A module whose imports haven't been bound yet
+    UnlinkedModule,
+    /// A module whose imports are bound but which hasn't been evaluated
+    LinkedModule,
+    /// A module which is being evaluated, or whose dependencies are
+    EvaluatingModule,
+    /// A module which has been evaluated
+    EvaluatedModule
+}
+#[deriving(Clone, PartialEq)]
+/// Where a binding that a module imports or exports lives
+pub enum Binding {
+    /// The binding with the name given in the environment of the module with the key given
+    LocalBinding(String, String),
+    /// The namespace object of the module with the key given
+    NamespaceBinding(String)
+}
+/// A module that has been parsed, with what it imports and exports
+pub struct ModuleRecord {
+    /// The key that identifies the module
+    pub key : String,
+    /// The body of the module, which is evaluated with the module's environment as its global object
+    pub body : Expr,
+    /// The specifiers of the modules this module depends on, in the order it refers to them
+    pub requests : Vec<String>,
+    /// The key of the module that each specifier resolved to
+    pub resolved : TreeMap<String, String>,
+    /// The bindings imported by this module, with the specifier of the module each is from
+    pub imports : Vec<(String, ImportSpecifier)>,
+    /// The names exported by this module with the bindings in its environment they refer to
+    pub local_exports : Vec<(String, String)>,
+    /// The names exported by this module with the specifier of the module they're re-exported
+    /// from and the names they have there
+    pub indirect_exports : Vec<(String, String, String)>,
+    /// The specifiers of the modules whose exports are all exported by this module
+    pub star_exports : Vec<String>,
+    /// The object holding the bindings at the top level of the module, which inherits from the global object
+    pub environment : Value,
+    /// The object holding the exports of the module, which is what `import * as` binds to
+    pub namespace : Value,
+    /// How far the module has got towards being evaluated
+    pub status : ModuleStatus
+}
+impl ModuleRecord {
+    /// Make a record of the module with the key and parsed body given
+    pub fn new(key:String, module:Expr, global:Value) -> ModuleRecord {
+        let environment = Value::new_obj(Some(global));
+        environment.set_field(INSTANCE_PROTOTYPE, global);
+        let mut record = ModuleRecord {
+            key: key,
+            body: module.clone(),
+            requests: Vec::new(),
+            resolved: TreeMap::new(),
+            imports: Vec::new(),
+            local_exports: Vec::new(),
+            indirect_exports: Vec::new(),
+            star_exports: Vec::new(),
+            environment: environment,
+            namespace: Value::new_obj(None),
+            status: UnlinkedModule
+        };
+        let statements = match module.def {
+            BlockExpr(ref statements) => statements.clone(),
+            _ => vec!(module.clone())
+        };
+        for statement in statements.move_iter() {
+            match statement.def {
+                ImportExpr(specifiers, specifier) => {
+                    record.request(&specifier);
+                    for import in specifiers.move_iter() {
+                        record.imports.push((specifier.clone(), import));
+                    }
+                },
+                ExportExpr(ExportDeclaration(box decl)) => for name in declared_names(&decl).move_iter() {
+                    record.local_exports.push((name.clone(), name));
+                },
+                // a named function or class declaration is exported as its binding, and anything
+                // else is bound to a name of its own when the module runs
+                ExportExpr(ExportDefault(box decl)) => {
+                    let name = declared_names(&decl).move_iter().next().unwrap_or(DEFAULT_BINDING.into_string());
+                    record.local_exports.push(("default".into_string(), name));
+                },
+                ExportExpr(ExportNames(names, None)) => for (local, exported) in names.move_iter() {
+                    record.local_exports.push((exported, local));
+                },
+                ExportExpr(ExportNames(names, Some(specifier))) => {
+                    record.request(&specifier);
+                    for (name, exported) in names.move_iter() {
+                        record.indirect_exports.push((exported, specifier.clone(), name));
+                    }
+                },
+                ExportExpr(ExportAll(specifier)) => {
+                    record.request(&specifier);
+                    record.star_exports.push(specifier);
+                },
+                _ => ()
+            }
+        }
+        record
+    }
+    /// Add a module this module depends on, unless it already depends on it
+    fn request(&mut self, specifier:&String) {
+        if !self.requests.contains(specifier) {
+            self.requests.push(specifier.clone());
+        }
+    }
+    /// Get the key of the module that a specifier in this module resolved to
+    pub fn dependency<'a>(&'a self, specifier:&String) -> &'a String {
+        self.resolved.find(specifier).expect("module dependencies are resolved when it's loaded")
+    }
+    /// Get the keys of the modules this module depends on, in the order it refers to them
+    pub fn dependencies(&self) -> Vec<String> {
+        self.requests.iter().map(|specifier| self.dependency(specifier).clone()).collect()
+    }
+}
+/// Get the names that a declaration binds
+pub fn declared_names(decl:&Expr) -> Vec<String> {
+    match decl.def {
+        VarDeclExpr(_, ref vars) => vars.iter().flat_map(|&(ref pattern, _)| pattern.names().move_iter()).collect(),
+        FunctionDeclExpr(Some(ref name), _, _) | GeneratorDeclExpr(Some(ref name), _, _) |
+        AsyncFunctionDeclExpr(Some(ref name), _, _) | ClassDeclExpr(Some(ref name), _, _, _) => vec!(name.clone()),
+        _ => Vec::new()
+    }
+}
+/// Remove a field that refers to a binding from an object, whatever its attributes are
+fn unbind(target:Value, name:&str) {
+    match *target {
+        VObject(ref obj) => { obj.borrow_mut().remove(&name.into_string()); },
+        _ => ()
+    }
+}
+/// Get the value of a binding in the environment of a module, which is how imported bindings
+/// stay live as the module that exports them changes them
+fn get_binding(_:Vec<Value>, _:Value, scope:Value, _:Value) -> ResultValue {
+    let name = scope.get_field("name").to_string();
+    Ok(scope.get_field("environment").get_field(name.as_slice()))
+}
+/// Parse the source text of a module
+pub fn parse_module(key:&String, source:&str) -> Result<Expr, ModuleError> {
+    let tokens = match Lexer::lex_str(source) {
+        Ok(tokens) => tokens,
+        Err(err) => return Err(ModuleSyntaxError(key.clone(), LexFailure(err)))
+    };
+    let end = tokens.last().map(|token| token.end).unwrap_or(Position::new(1, 1, 0));
+    match Parser::new(tokens).parse_module() {
+        Ok(module) => Ok(module),
+        Err(err) => Err(ModuleSyntaxError(key.clone(), SyntaxError::from_parse(err, end)))
+    }
+}
+/// The modules that have been loaded with a loader, which links the bindings they import to the
+/// bindings they're exported from and evaluates them after the modules they depend on
+pub struct ModuleMap<L> {
+    /// The loader that finds modules and loads their source text
+    pub loader : L,
+    /// The global object that the environment of each module inherits from
+    pub global : Value,
+    /// The modules that have been loaded, by their keys
+    pub modules : TreeMap<String, ModuleRecord>
+}
+impl<L:ModuleLoader> ModuleMap<L> {
+    /// Make an empty module map that loads modules with `loader`
+    pub fn new(loader:L, global:Value) -> ModuleMap<L> {
+        ModuleMap {
+            loader: loader,
+            global: global,
+            modules: TreeMap::new()
+        }
+    }
+    /// Get the record of the module with the key given
+    pub fn get<'a>(&'a self, key:&String) -> &'a ModuleRecord {
+        self.modules.find(key).expect("module isn't loaded")
+    }
+    /// Load the module with the specifier given and every module it depends on, giving its key
+    pub fn load(&mut self, specifier:&str, referrer:Option<&str>) -> Result<String, ModuleError> {
+        let key = match self.loader.resolve(specifier, referrer) {
+            Ok(key) => key,
+            Err(why) => return Err(ResolveError(specifier.into_string(), why))
+        };
+        if self.modules.contains_key(&key) {
+            return Ok(key);
+        }
+        let source = match self.loader.load(key.as_slice()) {
+            Ok(source) => source,
+            Err(why) => return Err(LoadError(key, why))
+        };
+        let module = try!(parse_module(&key, source.as_slice()));
+        let record = ModuleRecord::new(key.clone(), module, self.global);
+        let requests = record.requests.clone();
+        // the record is added before its dependencies are loaded, so a cycle of imports ends here
+        self.modules.insert(key.clone(), record);
+        for specifier in requests.move_iter() {
+            let dependency = try!(self.load(specifier.as_slice(), Some(key.as_slice())));
+            self.modules.find_mut(&key).unwrap().resolved.insert(specifier, dependency);
+        }
+        Ok(key)
+    }
+    /// Find the binding that the module with the key given exports with a name, or `None` if it
+    /// doesn't export it
+    pub fn resolve_export(&self, key:&String, name:&str, visited:&mut Vec<(String, String)>) -> Result<Option<Binding>, ModuleError> {
+        let pair = (key.clone(), name.into_string());
+        // a name that's already being resolved is re-exported in a cycle, so it isn't exported
+        if visited.contains(&pair) {
+            return Ok(None);
+        }
+        visited.push(pair);
+        let module = self.get(key);
+        for &(ref exported, ref local) in module.local_exports.iter() {
+            if exported.as_slice() != name {
+                continue;
+            }
+            // exporting an imported binding re-exports the binding it's imported from
+            return match module.imports.iter().find(|&&(_, ref import)| import.local() == local) {
+                Some(&(ref specifier, ImportNamespace(_))) => Ok(Some(NamespaceBinding(module.dependency(specifier).clone()))),
+                Some(&(ref specifier, ImportDefault(_))) => self.resolve_export(module.dependency(specifier), "default", visited),
+                Some(&(ref specifier, ImportNamed(ref imported, _))) => self.resolve_export(module.dependency(specifier), imported.as_slice(), visited),
+                None => Ok(Some(LocalBinding(key.clone(), local.clone())))
+            };
+        }
+        for &(ref exported, ref specifier, ref imported) in module.indirect_exports.iter() {
+            if exported.as_slice() == name {
+                return self.resolve_export(module.dependency(specifier), imported.as_slice(), visited);
+            }
+        }
+        // exporting all of a module's exports doesn't export its default export
+        if name == "default" {
+            return Ok(None);
+        }
+        let mut found = None;
+        for specifier in module.star_exports.iter() {
+            match try!(self.resolve_export(module.dependency(specifier), name, visited)) {
+                Some(ref binding) if found.is_some() && found.as_ref() != Some(binding) =>
+                    return Err(AmbiguousExport(key.clone(), name.into_string())),
+                Some(binding) => found = Some(binding),
+                None => ()
+            }
+        }
+        Ok(found)
+    }
+    /// Find the binding that a module imports with a name, which is an error if it isn't exported
+    fn resolve_import(&self, key:&String, name:&str) -> Result<Binding, ModuleError> {
+        match try!(self.resolve_export(key, name, &mut Vec::new())) {
+            Some(binding) => Ok(binding),
+            None => Err(MissingExport(key.clone(), name.into_string()))
+        }
+    }
+    /// Collect the names that the module with the key given exports
+    fn export_names(&self, key:&String, visited:&mut Vec<String>, names:&mut Vec<String>) {
+        if visited.contains(key) {
+            return;
+        }
+        visited.push(key.clone());
+        let module = self.get(key);
+        let exported = module.local_exports.iter().map(|&(ref name, _)| name).chain(module.indirect_exports.iter().map(|&(ref name, _, _)| name));
+        for name in exported {
+            if !names.contains(name) {
+                names.push(name.clone());
+            }
+        }
+        for specifier in module.star_exports.iter() {
+            let mut star_names = Vec::new();
+            self.export_names(module.dependency(specifier), visited, &mut star_names);
+            for name in star_names.move_iter() {
+                if name.as_slice() != "default" && !names.contains(&name) {
+                    names.push(name);
+                }
+            }
+        }
+    }
+    /// Define a field of an object that refers to a binding
+    fn bind(&self, target:Value, name:&str, binding:&Binding) {
+        match *binding {
+            NamespaceBinding(ref key) => {
+                target.set_field(name, self.get(key).namespace);
+            },
+            LocalBinding(ref key, ref local) => {
+                let scope = js!(self.global, {
+                    "environment": self.get(key).environment,
+                    "name": local.as_slice()
+                });
+                target.set_prop(name, Property {
+                    enumerable: true,
+                    get: Function::make_closure(get_binding, [], scope),
+                    ..Property::new(Value::undefined())
+                });
+            }
+        }
+    }
+    /// Bind the imports of the module with the key given and every module it depends on to the
+    /// bindings they're exported from, and fill in their namespace objects
+    ///
+    /// If that fails, every module linked along the way is left as it was before, so linking it
+    /// can be tried again
+    pub fn link(&mut self, key:&String) -> Result<(), ModuleError> {
+        let mut linked = Vec::new();
+        let result = self.link_module(key, &mut linked);
+        if result.is_err() {
+            for key in linked.iter() {
+                self.unlink(key);
+            }
+        }
+        result
+    }
+    /// Undo linking the module with the key given, removing the bindings it imports and the
+    /// bindings in its namespace object
+    fn unlink(&mut self, key:&String) {
+        let mut names = Vec::new();
+        self.export_names(key, &mut Vec::new(), &mut names);
+        let module = self.modules.find_mut(key).unwrap();
+        module.status = UnlinkedModule;
+        for &(_, ref import) in module.imports.iter() {
+            unbind(module.environment, import.local().as_slice());
+        }
+        for name in names.iter() {
+            unbind(module.namespace, name.as_slice());
+        }
+    }
+    /// Link the module with the key given and every module it depends on, collecting the keys of
+    /// the modules it links in `linked`
+    fn link_module(&mut self, key:&String, linked:&mut Vec<String>) -> Result<(), ModuleError> {
+        if self.get(key).status != UnlinkedModule {
+            return Ok(());
+        }
+        self.modules.find_mut(key).unwrap().status = LinkedModule;
+        linked.push(key.clone());
+        let dependencies = self.get(key).dependencies();
+        for dependency in dependencies.iter() {
+            try!(self.link_module(dependency, linked));
+        }
+        let module = self.get(key);
+        for &(ref specifier, ref import) in module.imports.iter() {
+            let from = module.dependency(specifier);
+            let binding = match *import {
+                ImportNamespace(_) => NamespaceBinding(from.clone()),
+                ImportDefault(_) => try!(self.resolve_import(from, "default")),
+                ImportNamed(ref name, _) => try!(self.resolve_import(from, name.as_slice()))
+            };
+            self.bind(module.environment, import.local().as_slice(), &binding);
+        }
+        for &(_, ref specifier, ref name) in module.indirect_exports.iter() {
+            try!(self.resolve_import(module.dependency(specifier), name.as_slice()));
+        }
+        let mut names = Vec::new();
+        self.export_names(key, &mut Vec::new(), &mut names);
+        for name in names.iter() {
+            match self.resolve_export(key, name.as_slice(), &mut Vec::new()) {
+                Ok(Some(binding)) => self.bind(module.namespace, name.as_slice(), &binding),
+                // a name exported ambiguously by `export *` is left out of the namespace
+                Ok(None) | Err(AmbiguousExport(_, _)) => (),
+                Err(err) => return Err(err)
+            }
+        }
+        Ok(())
+    }
+    /// Evaluate the module with the key given after the modules it depends on, linking them first
+    /// if they aren't linked, by calling `run` with the record of each module that hasn't been
+    /// evaluated yet
+    pub fn evaluate(&mut self, key:&String, run:|&ModuleRecord| -> ResultValue) -> Result<(), ModuleError> {
+        try!(self.link(key));
+        let mut run = run;
+        self.evaluate_module(key, &mut run)
+    }
+    fn evaluate_module(&mut self, key:&String, run:&mut |&ModuleRecord| -> ResultValue) -> Result<(), ModuleError> {
+        // a module that is being evaluated is depended on by a module it depends on, which goes on without it
+        if self.get(key).status != LinkedModule {
+            return Ok(());
+        }
+        self.modules.find_mut(key).unwrap().status = EvaluatingModule;
+        let dependencies = self.get(key).dependencies();
+        for dependency in dependencies.iter() {
+            try!(self.evaluate_module(dependency, run));
+        }
+        let result = (*run)(self.get(key));
+        self.modules.find_mut(key).unwrap().status = EvaluatedModule;
+        match result {
+            Ok(_) => Ok(()),
+            Err(thrown) => Err(EvaluationError(key.clone(), thrown))
+        }
+    }
+    /// Load, link and evaluate the module with the specifier given, giving its namespace object
+    pub fn import(&mut self, specifier:&str, run:|&ModuleRecord| -> ResultValue) -> Result<Value, ModuleError> {
+        let key = try!(self.load(specifier, None));
+        try!(self.evaluate(&key, run));
+        Ok(self.get(&key).namespace)
+    }
+}
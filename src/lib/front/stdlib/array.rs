@@ -1,6 +1,15 @@
 use front::stdlib::value::{Value, ResultValue, to_value};
 use front::stdlib::function::Function;
 
+/// Create a new array value holding the items
+pub fn new_array(global:Value, items:Vec<Value>) -> Value {
+    let array = Value::new_obj(Some(global));
+    array.set_field("length", to_value(items.len() as i32));
+    for (i, item) in items.move_iter().enumerate() {
+        array.set_field(i.to_string().as_slice(), item);
+    }
+    array
+}
 /// Create a new array
 pub fn make_array(_:Vec<Value>, _:Value, _:Value, this:Value) -> ResultValue {
     this.set_field("length", to_value(0i32));
@@ -1,4 +1,4 @@
-use front::stdlib::object::PROTOTYPE;
+use front::stdlib::object::{PROTOTYPE, INSTANCE_PROTOTYPE};
 use front::stdlib::value::{Value, ResultValue, to_value};
 use front::stdlib::function::Function;
 
@@ -26,9 +26,30 @@ pub fn _create(global: Value) -> Value {
     error.set_field(PROTOTYPE, prototype);
     error
 }
+/// Create a new native error type, such as `ReferenceError`, which inherits from `Error`
+pub fn _create_native(global: Value, name: &'static str) -> Value {
+    let prototype = js!(global, {
+        "message": "",
+        "name": name
+    });
+    prototype.set_field(INSTANCE_PROTOTYPE, global.get_field("Error").get_field(PROTOTYPE));
+    let error = Function::make(make_error, ["message"]);
+    error.set_field(PROTOTYPE, prototype);
+    error
+}
+/// Create an instance of the error type called `name` on the global object, with the message given
+pub fn new_error(global:Value, name:&str, message:&str) -> Value {
+    let error = Value::new_obj(Some(global));
+    error.set_field(INSTANCE_PROTOTYPE, global.get_field(name).get_field(PROTOTYPE));
+    error.set_field("message", to_value(message));
+    error
+}
 /// Initialise the global object with the `Error` object
 pub fn init(global:Value) {
     js_extend!(global, {
-        "Error": _create(global)
+        "Error": _create(global),
+        "ReferenceError": _create_native(global, "ReferenceError"),
+        "SyntaxError": _create_native(global, "SyntaxError"),
+        "TypeError": _create_native(global, "TypeError")
     });
 }
\ No newline at end of file
@@ -12,21 +12,43 @@ pub struct Function {
     /// This function's JIT representation
     pub repr : FunctionData,
     /// The argument names of the function
-    pub args : Vec<String>
+    pub args : Vec<String>,
+    /// The scope the function was made in, which it's called with instead of the caller's if it has one
+    pub scope : Option<Value>,
+    /// Whether the function is strict mode code, which is called with `this` as it's given instead
+    /// of the global object when that's undefined or null
+    pub strict : bool
 }
 impl Function {
-    /// Make a new function
-    pub fn new(repr : FunctionData, args: Vec<String>) -> Function {
+    /// Make a new function, which is called with `this` as it's given if it's strict
+    pub fn new(repr : FunctionData, args: Vec<String>, strict: bool) -> Function {
         let mut obj = TreeMap::new();
         obj.insert("arguments".into_string(), Property::new(to_value(args.len() as i32)));
-        Function {object: obj, repr: repr, args: args}
+        Function {object: obj, repr: repr, args: args, scope: None, strict: strict}
     }
     /// Create a function from function data and arguments
     pub fn make(repr: FunctionData, args:&[&'static str]) -> Value {
-        Value::new(VFunction(RefCell::new(Function::new(repr, FromIterator::from_iter(args.iter().map(|arg|arg.to_string()))))))
+        // native functions get `this` as it's given, like strict functions
+        Value::new(VFunction(RefCell::new(Function::new(repr, FromIterator::from_iter(args.iter().map(|arg|arg.to_string())), true))))
+    }
+    /// Create a function from function data and arguments that is always called with `scope`,
+    /// which lets it keep values between calls
+    pub fn make_closure(repr: FunctionData, args:&[&'static str], scope:Value) -> Value {
+        let mut func = Function::new(repr, FromIterator::from_iter(args.iter().map(|arg|arg.to_string())), true);
+        func.scope = Some(scope);
+        Value::new(VFunction(RefCell::new(func)))
     }
     /// Call with some args
     pub fn call(&self, args: Vec<Value>, global:Value, scope:Value, this:Value) -> ResultValue {
+        let scope = match self.scope {
+            Some(scope) => scope,
+            None => scope
+        };
+        let this = if !self.strict && this.is_null_or_undefined() {
+            global
+        } else {
+            this
+        };
         (self.repr)(args, global, scope, this)
     }
 }
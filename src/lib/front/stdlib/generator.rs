@@ -0,0 +1,104 @@
+use front::stdlib::object::INSTANCE_PROTOTYPE;
+use front::stdlib::value::{Value, VFunction, ResultValue, to_value, from_value};
+use front::stdlib::function::Function;
+use front::stdlib::error::new_error;
+/// The field of the global object holding the prototype of generator objects
+pub static GENERATOR_PROTOTYPE: &'static str = "__generator_prototype__";
+/// The field of a generator object holding the function that runs its body from where it was
+/// last suspended, which is called with the value it's resumed with and how it's resumed
+pub static RESUME: &'static str = "__resume__";
+/// The field of a generator object holding what state it's in
+pub static STATE: &'static str = "__state__";
+/// A generator that hasn't started running its body
+static SUSPENDED_START: i32 = 0;
+/// A generator that is suspended at a `yield`
+static SUSPENDED_YIELD: i32 = 1;
+/// A generator that is running its body
+static EXECUTING: i32 = 2;
+/// A generator that has returned or thrown
+static COMPLETED: i32 = 3;
+#[deriving(Clone, PartialEq)]
+/// How a generator is resumed
+pub enum ResumeKind {
+    /// Carry on, with the `yield` giving the value resumed with
+    ResumeNext,
+    /// Return the value resumed with from the `yield`, running any `finally` blocks around it
+    ResumeReturn,
+    /// Throw the value resumed with from the `yield`
+    ResumeThrow
+}
+/// Make an iterator result, which is an object with the value and whether the iterator is done
+pub fn iter_result(global:Value, value:Value, done:bool) -> Value {
+    js!(global, {
+        "value": value,
+        "done": done
+    })
+}
+/// Make a generator object that runs its body with `resume`, which gives an iterator result for
+/// each `yield` and for the value the body returns
+pub fn new_generator(global:Value, resume:Value) -> Value {
+    let generator = Value::new_obj(Some(global));
+    generator.set_field(INSTANCE_PROTOTYPE, global.get_field(GENERATOR_PROTOTYPE));
+    generator.set_field(RESUME, resume);
+    generator.set_field(STATE, to_value(SUSPENDED_START));
+    generator
+}
+/// Resume a generator in the way given, following the iterator protocol
+fn resume(generator:Value, global:Value, value:Value, kind:ResumeKind) -> ResultValue {
+    let state = match from_value::<i32>(generator.get_field(STATE)) {
+        Ok(state) if !generator.get_field(RESUME).is_undefined() => state,
+        _ => return Err(new_error(global, "TypeError", "not a generator"))
+    };
+    if state == EXECUTING {
+        return Err(new_error(global, "TypeError", "generator is already running"));
+    }
+    // a generator that hasn't started finishes straight away if it's returned from or thrown into
+    if state == COMPLETED || (state == SUSPENDED_START && kind != ResumeNext) {
+        generator.set_field(STATE, to_value(COMPLETED));
+        return match kind {
+            ResumeNext => Ok(iter_result(global, Value::undefined(), true)),
+            ResumeReturn => Ok(iter_result(global, value, true)),
+            ResumeThrow => Err(value)
+        };
+    }
+    generator.set_field(STATE, to_value(EXECUTING));
+    let result = match *generator.get_field(RESUME) {
+        VFunction(ref func) => func.borrow().call(vec!(value, to_value(kind as i32)), global, Value::undefined(), generator),
+        _ => Err(new_error(global, "TypeError", "not a generator"))
+    };
+    let done = match result {
+        Ok(result) => from_value::<bool>(result.get_field("done")).unwrap_or(true),
+        Err(_) => true
+    };
+    generator.set_field(STATE, to_value(if done {COMPLETED} else {SUSPENDED_YIELD}));
+    result
+}
+/// Run a generator until its next `yield` or until it returns
+pub fn next(args:Vec<Value>, global:Value, _:Value, this:Value) -> ResultValue {
+    let value = args.as_slice().head().map(|arg| *arg).unwrap_or(Value::undefined());
+    resume(this, global, value, ResumeNext)
+}
+/// Return a value from where a generator is suspended
+pub fn return_(args:Vec<Value>, global:Value, _:Value, this:Value) -> ResultValue {
+    let value = args.as_slice().head().map(|arg| *arg).unwrap_or(Value::undefined());
+    resume(this, global, value, ResumeReturn)
+}
+/// Throw a value from where a generator is suspended
+pub fn throw(args:Vec<Value>, global:Value, _:Value, this:Value) -> ResultValue {
+    let value = args.as_slice().head().map(|arg| *arg).unwrap_or(Value::undefined());
+    resume(this, global, value, ResumeThrow)
+}
+/// Create the prototype of generator objects
+pub fn _create(global: Value) -> Value {
+    js!(global, {
+        "next": Function::make(next, ["value"]),
+        "return": Function::make(return_, ["value"]),
+        "throw": Function::make(throw, ["exception"])
+    })
+}
+/// Initialise the global object with the prototype of generator objects
+pub fn init(global:Value) {
+    js_extend!(global, {
+        GENERATOR_PROTOTYPE: _create(global)
+    });
+}
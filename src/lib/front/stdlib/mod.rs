@@ -8,6 +8,8 @@ pub mod console;
 pub mod error;
 /// The `Function` global object
 pub mod function;
+/// The prototype of generator objects
+pub mod generator;
 /// The `JSON` global object
 pub mod json;
 /// The `Math` global object
@@ -16,6 +18,10 @@ pub mod math;
 pub mod number;
 /// The `Object` global object
 pub mod object;
+/// The `Promise` global object
+pub mod promise;
+/// The `RegExp` global object and its regular expression engine
+pub mod regexp;
 /// The `String` global object
 pub mod string;
 /// The global URI methods
@@ -107,7 +107,7 @@ pub fn _create(global:Value) -> Value {
     js_extend!(object, {
         "length": 1i32,
         PROTOTYPE: prototype,
-        "setPrototypeOf": Function::make(get_proto_of, ["object", "prototype"]),
+        "setPrototypeOf": Function::make(set_proto_of, ["object", "prototype"]),
         "getPrototypeOf": Function::make(get_proto_of, ["object"]),
         "defineProperty": Function::make(define_prop, ["object", "property"])
     });
@@ -0,0 +1,278 @@
+use front::stdlib::object::{PROTOTYPE, INSTANCE_PROTOTYPE};
+use front::stdlib::value::{Value, VFunction, ResultValue, to_value};
+use front::stdlib::function::Function;
+use front::stdlib::array::new_array;
+use front::stdlib::error::new_error;
+use front::stdlib::generator::{ResumeKind, ResumeNext, ResumeThrow};
+use front::run::executor::enqueue_job;
+/// The field of a promise holding what state it's in
+pub static PROMISE_STATE: &'static str = "__promise_state__";
+/// The field of a promise holding the value it was fulfilled with or the reason it was rejected with
+pub static PROMISE_RESULT: &'static str = "__promise_result__";
+/// The field of a pending promise holding the reactions to run once it settles
+static REACTIONS: &'static str = "__reactions__";
+/// A promise that hasn't settled
+static PENDING: i32 = 0;
+/// A promise that was fulfilled with a value
+static FULFILLED: i32 = 1;
+/// A promise that was rejected with a reason
+static REJECTED: i32 = 2;
+/// Returns true if the value is a function
+fn is_callable(value:Value) -> bool {
+    match *value {
+        VFunction(_) => true,
+        _ => false
+    }
+}
+/// Call a function with the arguments and `this` given
+fn call(global:Value, func:Value, args:Vec<Value>, this:Value) -> ResultValue {
+    match *func {
+        VFunction(ref func) => func.borrow().call(args, global, global, this),
+        _ => Err(new_error(global, "TypeError", "not a function"))
+    }
+}
+/// Returns true if the value is a promise
+pub fn is_promise(value:Value) -> bool {
+    !value.get_field(PROMISE_STATE).is_undefined()
+}
+/// Make a value into a pending promise
+fn init_promise(global:Value, promise:Value) {
+    promise.set_field(PROMISE_STATE, to_value(PENDING));
+    promise.set_field(PROMISE_RESULT, Value::undefined());
+    promise.set_field(REACTIONS, new_array(global, Vec::new()));
+}
+/// Make a pending promise
+pub fn new_promise(global:Value) -> Value {
+    let promise = Value::new_obj(Some(global));
+    promise.set_field(INSTANCE_PROTOTYPE, global.get_field("Promise").get_field(PROTOTYPE));
+    init_promise(global, promise);
+    promise
+}
+/// Make the functions that resolve and reject a promise, which only do anything the first time
+/// either of them is called
+fn resolving_functions(global:Value, promise:Value) -> (Value, Value) {
+    let scope = js!(global, {
+        "promise": promise,
+        "resolved": false
+    });
+    (Function::make_closure(resolve_function, ["resolution"], scope),
+     Function::make_closure(reject_function, ["reason"], scope))
+}
+/// Resolve the promise that a resolving function was made for
+fn resolve_function(args:Vec<Value>, global:Value, scope:Value, _:Value) -> ResultValue {
+    if !scope.get_field("resolved").is_true() {
+        scope.set_field("resolved", to_value(true));
+        let resolution = args.as_slice().head().map(|arg| *arg).unwrap_or(Value::undefined());
+        resolve_promise(global, scope.get_field("promise"), resolution);
+    }
+    Ok(Value::undefined())
+}
+/// Reject the promise that a resolving function was made for
+fn reject_function(args:Vec<Value>, global:Value, scope:Value, _:Value) -> ResultValue {
+    if !scope.get_field("resolved").is_true() {
+        scope.set_field("resolved", to_value(true));
+        let reason = args.as_slice().head().map(|arg| *arg).unwrap_or(Value::undefined());
+        reject_promise(global, scope.get_field("promise"), reason);
+    }
+    Ok(Value::undefined())
+}
+/// Resolve a promise with a value, which makes it follow the value if that has a `then` method
+/// and fulfills it with the value otherwise
+pub fn resolve_promise(global:Value, promise:Value, resolution:Value) {
+    if resolution.ptr.ptr_eq(&promise.ptr) {
+        reject_promise(global, promise, new_error(global, "TypeError", "a promise can't be resolved with itself"));
+        return;
+    }
+    let then = resolution.get_field("then");
+    if is_callable(then) {
+        // the `then` method is called in a job of its own so it can't run in the middle of a script
+        let scope = js!(global, {
+            "promise": promise,
+            "thenable": resolution,
+            "then": then
+        });
+        enqueue_job(global, Function::make_closure(resolve_thenable_job, [], scope), Value::undefined());
+    } else {
+        settle(global, promise, FULFILLED, resolution);
+    }
+}
+/// Reject a promise with a reason
+pub fn reject_promise(global:Value, promise:Value, reason:Value) {
+    settle(global, promise, REJECTED, reason);
+}
+/// Make a promise follow a value with a `then` method by calling it with resolving functions for the promise
+fn resolve_thenable_job(_:Vec<Value>, global:Value, scope:Value, _:Value) -> ResultValue {
+    let (resolve, reject) = resolving_functions(global, scope.get_field("promise"));
+    match call(global, scope.get_field("then"), vec!(resolve, reject), scope.get_field("thenable")) {
+        Ok(_) => Ok(Value::undefined()),
+        Err(thrown) => call(global, reject, vec!(thrown), Value::undefined())
+    }
+}
+/// Fulfill or reject a pending promise, queueing the reactions waiting for it
+fn settle(global:Value, promise:Value, state:i32, result:Value) {
+    if promise.get_field(PROMISE_STATE).to_int() != PENDING {
+        return;
+    }
+    promise.set_field(PROMISE_STATE, to_value(state));
+    promise.set_field(PROMISE_RESULT, result);
+    let reactions = promise.get_field(REACTIONS);
+    promise.remove_field(REACTIONS);
+    for i in range(0, reactions.get_field("length").to_int()) {
+        trigger_reaction(global, reactions.get_field(i.to_string().as_slice()), state, result);
+    }
+}
+/// Queue the job that runs the handler of a reaction for how its promise settled
+fn trigger_reaction(global:Value, reaction:Value, state:i32, result:Value) {
+    let scope = js!(global, {
+        "promise": reaction.get_field("promise"),
+        "handler": reaction.get_field(if state == FULFILLED {"onFulfilled"} else {"onRejected"}),
+        "state": state
+    });
+    enqueue_job(global, Function::make_closure(reaction_job, ["argument"], scope), result);
+}
+/// Run the handler of a reaction, settling the promise `then` gave with what it returns or throws
+///
+/// A reaction without a handler passes the value or reason on to that promise
+fn reaction_job(args:Vec<Value>, global:Value, scope:Value, _:Value) -> ResultValue {
+    let argument = args.as_slice().head().map(|arg| *arg).unwrap_or(Value::undefined());
+    let handler = scope.get_field("handler");
+    let result = if is_callable(handler) {
+        call(global, handler, vec!(argument), Value::undefined())
+    } else if scope.get_field("state").to_int() == FULFILLED {
+        Ok(argument)
+    } else {
+        Err(argument)
+    };
+    let promise = scope.get_field("promise");
+    match result {
+        Ok(value) => resolve_promise(global, promise, value),
+        Err(reason) => reject_promise(global, promise, reason)
+    }
+    Ok(Value::undefined())
+}
+/// Add handlers to run once a promise settles, giving a promise of what they return
+pub fn perform_then(global:Value, promise:Value, on_fulfilled:Value, on_rejected:Value) -> Value {
+    let derived = new_promise(global);
+    let reaction = js!(global, {
+        "promise": derived,
+        "onFulfilled": on_fulfilled,
+        "onRejected": on_rejected
+    });
+    let state = promise.get_field(PROMISE_STATE).to_int();
+    if state == PENDING {
+        let reactions = promise.get_field(REACTIONS);
+        let length = reactions.get_field("length").to_int();
+        reactions.set_field(length.to_string().as_slice(), reaction);
+        reactions.set_field("length", to_value(length + 1));
+    } else {
+        trigger_reaction(global, reaction, state, promise.get_field(PROMISE_RESULT));
+    }
+    derived
+}
+/// Make a promise resolved with a value, or give the value if it's already a promise
+pub fn promise_resolve(global:Value, value:Value) -> Value {
+    if is_promise(value) {
+        return value;
+    }
+    let promise = new_promise(global);
+    resolve_promise(global, promise, value);
+    promise
+}
+/// Start running the body of an async function with `resume`, which runs it like the body of a
+/// generator where each `yield` awaits the value it gives, and give a promise of what it returns
+pub fn run_async(global:Value, resume:Value) -> Value {
+    let promise = new_promise(global);
+    let scope = js!(global, {
+        "promise": promise,
+        "resume": resume
+    });
+    async_step(global, scope, Value::undefined(), ResumeNext);
+    promise
+}
+/// Resume the body of an async function until its next `await` or until it finishes
+fn async_step(global:Value, scope:Value, value:Value, kind:ResumeKind) {
+    let promise = scope.get_field("promise");
+    match call(global, scope.get_field("resume"), vec!(value, to_value(kind as i32)), Value::undefined()) {
+        Ok(result) if result.get_field("done").is_true() =>
+            resolve_promise(global, promise, result.get_field("value")),
+        Ok(result) => {
+            let awaited = promise_resolve(global, result.get_field("value"));
+            let on_fulfilled = Function::make_closure(async_fulfilled, ["value"], scope);
+            let on_rejected = Function::make_closure(async_rejected, ["reason"], scope);
+            perform_then(global, awaited, on_fulfilled, on_rejected);
+        },
+        Err(thrown) => reject_promise(global, promise, thrown)
+    }
+}
+/// Resume an async function with the value the promise it awaited was fulfilled with
+fn async_fulfilled(args:Vec<Value>, global:Value, scope:Value, _:Value) -> ResultValue {
+    let value = args.as_slice().head().map(|arg| *arg).unwrap_or(Value::undefined());
+    async_step(global, scope, value, ResumeNext);
+    Ok(Value::undefined())
+}
+/// Throw the reason the promise an async function awaited was rejected with from its `await`
+fn async_rejected(args:Vec<Value>, global:Value, scope:Value, _:Value) -> ResultValue {
+    let reason = args.as_slice().head().map(|arg| *arg).unwrap_or(Value::undefined());
+    async_step(global, scope, reason, ResumeThrow);
+    Ok(Value::undefined())
+}
+/// Create a new promise, calling the executor with functions that resolve and reject it
+pub fn make_promise(args:Vec<Value>, global:Value, _:Value, this:Value) -> ResultValue {
+    let executor = args.as_slice().head().map(|arg| *arg).unwrap_or(Value::undefined());
+    if !is_callable(executor) {
+        return Err(new_error(global, "TypeError", "Promise resolver is not a function"));
+    }
+    init_promise(global, this);
+    let (resolve, reject) = resolving_functions(global, this);
+    match call(global, executor, vec!(resolve, reject), Value::undefined()) {
+        Ok(_) => Ok(Value::undefined()),
+        Err(thrown) => call(global, reject, vec!(thrown), Value::undefined())
+    }
+}
+/// Add handlers to run once the promise is fulfilled or rejected
+pub fn then(args:Vec<Value>, global:Value, _:Value, this:Value) -> ResultValue {
+    if !is_promise(this) {
+        return Err(new_error(global, "TypeError", "not a promise"));
+    }
+    let on_fulfilled = args.as_slice().get(0).map(|arg| *arg).unwrap_or(Value::undefined());
+    let on_rejected = args.as_slice().get(1).map(|arg| *arg).unwrap_or(Value::undefined());
+    Ok(perform_then(global, this, on_fulfilled, on_rejected))
+}
+/// Add a handler to run once the promise is rejected
+pub fn catch(args:Vec<Value>, global:Value, _:Value, this:Value) -> ResultValue {
+    if !is_promise(this) {
+        return Err(new_error(global, "TypeError", "not a promise"));
+    }
+    let on_rejected = args.as_slice().head().map(|arg| *arg).unwrap_or(Value::undefined());
+    Ok(perform_then(global, this, Value::undefined(), on_rejected))
+}
+/// Get a promise resolved with the value given
+pub fn resolve(args:Vec<Value>, global:Value, _:Value, _:Value) -> ResultValue {
+    let value = args.as_slice().head().map(|arg| *arg).unwrap_or(Value::undefined());
+    Ok(promise_resolve(global, value))
+}
+/// Get a promise rejected with the reason given
+pub fn reject(args:Vec<Value>, global:Value, _:Value, _:Value) -> ResultValue {
+    let reason = args.as_slice().head().map(|arg| *arg).unwrap_or(Value::undefined());
+    let promise = new_promise(global);
+    reject_promise(global, promise, reason);
+    Ok(promise)
+}
+/// Create a new `Promise` object
+pub fn _create(global: Value) -> Value {
+    let prototype = js!(global, {
+        "then": Function::make(then, ["onFulfilled", "onRejected"]),
+        "catch": Function::make(catch, ["onRejected"])
+    });
+    let promise = Function::make(make_promise, ["executor"]);
+    promise.set_field(PROTOTYPE, prototype);
+    promise.set_field("resolve", Function::make(resolve, ["value"]));
+    promise.set_field("reject", Function::make(reject, ["reason"]));
+    promise
+}
+/// Initialise the global object with the `Promise` object
+pub fn init(global:Value) {
+    js_extend!(global, {
+        "Promise": _create(global)
+    });
+}
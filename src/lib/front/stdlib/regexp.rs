@@ -0,0 +1,186 @@
+use front::stdlib::value::{Value, ResultValue, VNull, to_value};
+use front::stdlib::function::Function;
+use front::stdlib::object::{PROTOTYPE, INSTANCE_PROTOTYPE};
+use front::stdlib::array;
+use front::stdlib::error::new_error;
+use front::stdlib::string::get_arg;
+use syntax::regexp::{RegExp, RegExpFlags, Match};
+local_data_key!(REGEXPS: Vec<(Value, RegExp)>)
+/// Keep the regular expression a `RegExp` object was created with, where scripts can't change it
+fn register_regexp(obj:Value, regexp:RegExp) {
+    let mut regexps = REGEXPS.replace(None).unwrap_or(Vec::new());
+    regexps.push((obj, regexp));
+    REGEXPS.replace(Some(regexps));
+}
+/// Find the regular expression a `RegExp` object was created with
+fn find_regexp(value:Value) -> Option<RegExp> {
+    match REGEXPS.get() {
+        Some(regexps) => regexps.iter()
+            .find(|&&(ref obj, _)| obj.ptr.ptr_eq(&value.ptr))
+            .map(|&(_, ref regexp)| regexp.clone()),
+        None => None
+    }
+}
+/// Returns true if the value is a `RegExp` object
+pub fn is_regexp(value:Value) -> bool {
+    value.is_object() && find_regexp(value).is_some()
+}
+/// Get the regular expression a `RegExp` object was created with
+pub fn get_regexp(global:Value, this:Value) -> Result<RegExp, Value> {
+    match find_regexp(this) {
+        Some(regexp) => Ok(regexp),
+        None => Err(new_error(global, "TypeError", "Receiver is not a RegExp"))
+    }
+}
+/// Compile a regular expression, throwing a `SyntaxError` if it isn't valid
+fn compile_regexp(global:Value, source:&str, flags:&str) -> Result<RegExp, Value> {
+    RegExp::new(source, flags).map_err(|msg| new_error(global, "SyntaxError", msg.as_slice()))
+}
+/// Get the flags as they are written after a regular expression literal
+fn flag_string(flags:&RegExpFlags) -> String {
+    let mut flag_str = String::new();
+    for &(flag, ch) in [(flags.global, 'g'), (flags.ignore_case, 'i'), (flags.multiline, 'm'), (flags.dot_all, 's'), (flags.unicode, 'u'), (flags.sticky, 'y')].iter() {
+        if flag {
+            flag_str.push_char(ch);
+        }
+    }
+    flag_str
+}
+/// Set the fields of a `RegExp` object from the regular expression
+fn set_regexp_fields(this:Value, regexp:&RegExp) {
+    let flags = regexp.flags.clone();
+    js_extend!(this, {
+        "source": regexp.source.clone(),
+        "flags": flag_string(&flags),
+        "global": flags.global,
+        "ignoreCase": flags.ignore_case,
+        "multiline": flags.multiline,
+        "dotAll": flags.dot_all,
+        "unicode": flags.unicode,
+        "sticky": flags.sticky,
+        "lastIndex": 0i32
+    });
+}
+/// Create a new `RegExp` object from the source and flags of a regular expression
+pub fn new_regexp(global:Value, source:&str, flags:&str) -> ResultValue {
+    let regexp = try!(compile_regexp(global, source, flags));
+    let obj = Value::new_obj(None);
+    obj.set_field(INSTANCE_PROTOTYPE, global.get_field("RegExp").get_field(PROTOTYPE));
+    set_regexp_fields(obj, &regexp);
+    register_regexp(obj, regexp);
+    Ok(obj)
+}
+/// Convert a value to a `RegExp` object, compiling its string value as the pattern if it isn't one
+pub fn to_regexp(global:Value, value:Value) -> ResultValue {
+    if is_regexp(value) {
+        Ok(value)
+    } else if value.is_undefined() {
+        new_regexp(global, "(?:)", "")
+    } else {
+        new_regexp(global, value.to_string().as_slice(), "")
+    }
+}
+/// Match a `RegExp` object against the input, starting at and updating `lastIndex` if it is global or sticky
+pub fn exec_regexp(this:Value, regexp:&RegExp, input:&[char]) -> Option<Match> {
+    let uses_last_index = regexp.flags.global || regexp.flags.sticky;
+    let start = if uses_last_index {
+        this.get_field("lastIndex").to_int()
+    } else {
+        0
+    };
+    let result = if start < 0 || start as uint > input.len() {
+        None
+    } else {
+        regexp.match_from(input, start as uint)
+    };
+    if uses_last_index {
+        this.set_field("lastIndex", to_value(match result {
+            Some(ref found) => found.end() as i32,
+            None => 0i32
+        }));
+    }
+    result
+}
+/// Get the value of a group, which is undefined if it didn't participate in the match
+pub fn group_value(found:&Match, input:&[char], index:uint) -> Value {
+    match found.group(input, index) {
+        Some(text) => to_value(text),
+        None => Value::undefined()
+    }
+}
+/// Create the array that `exec` returns from a match
+pub fn match_value(global:Value, regexp:&RegExp, input:&[char], found:&Match) -> Value {
+    let items = range(0, regexp.group_count).map(|i| group_value(found, input, i)).collect();
+    let result = array::new_array(global, items);
+    result.set_field("index", to_value(found.start() as i32));
+    result.set_field("input", to_value(String::from_chars(input)));
+    result.set_field("groups", if regexp.names.is_empty() {
+        Value::undefined()
+    } else {
+        let groups = Value::new_obj(Some(global));
+        for &(ref name, index) in regexp.names.iter() {
+            groups.set_field(name.as_slice(), group_value(found, input, index));
+        }
+        groups
+    });
+    result
+}
+/// Create a new regular expression
+pub fn make_regexp(args:Vec<Value>, global:Value, _:Value, this:Value) -> ResultValue {
+    let pattern = get_arg(&args, 0);
+    let source = match find_regexp(pattern) {
+        Some(regexp) => regexp.source,
+        None if pattern.is_undefined() => "(?:)".into_string(),
+        None => pattern.to_string()
+    };
+    let flags = if !get_arg(&args, 1).is_undefined() {
+        args[1].to_string()
+    } else {
+        match find_regexp(pattern) {
+            Some(regexp) => flag_string(&regexp.flags),
+            None => String::new()
+        }
+    };
+    let regexp = try!(compile_regexp(global, source.as_slice(), flags.as_slice()));
+    set_regexp_fields(this, &regexp);
+    register_regexp(this, regexp);
+    Ok(this)
+}
+/// Search a string for a match, returning the match array or null
+pub fn exec(args:Vec<Value>, global:Value, _:Value, this:Value) -> ResultValue {
+    let regexp = try!(get_regexp(global, this));
+    let input : Vec<char> = get_arg(&args, 0).to_string().as_slice().chars().collect();
+    Ok(match exec_regexp(this, &regexp, input.as_slice()) {
+        Some(found) => match_value(global, &regexp, input.as_slice(), &found),
+        None => Value::new(VNull)
+    })
+}
+/// Check if a string matches
+pub fn test(args:Vec<Value>, global:Value, _:Value, this:Value) -> ResultValue {
+    let regexp = try!(get_regexp(global, this));
+    let input : Vec<char> = get_arg(&args, 0).to_string().as_slice().chars().collect();
+    Ok(to_value(exec_regexp(this, &regexp, input.as_slice()).is_some()))
+}
+/// Get the string representation of the regular expression
+pub fn to_string(_:Vec<Value>, _:Value, _:Value, this:Value) -> ResultValue {
+    let source = this.get_field("source");
+    let flags = this.get_field("flags");
+    Ok(to_value(format!("/{}/{}", source, flags)))
+}
+/// Create a new `RegExp` object
+pub fn _create(global: Value) -> Value {
+    let prototype = js!(global, {
+        "exec": Function::make(exec, ["string"]),
+        "test": Function::make(test, ["string"]),
+        "toString": Function::make(to_string, [])
+    });
+    let regexp = Function::make(make_regexp, ["pattern", "flags"]);
+    regexp.set_field(PROTOTYPE, prototype);
+    regexp
+}
+/// Initialise the `RegExp` object on the global object
+pub fn init(global:Value) {
+    js_extend!(global, {
+        "RegExp": _create(global)
+    });
+}
@@ -1,6 +1,10 @@
-use front::stdlib::value::{Value, ResultValue, to_value, from_value};
+use front::stdlib::value::{Value, ResultValue, VFunction, VNull, to_value, from_value};
 use front::stdlib::function::Function;
 use front::stdlib::object::{PROTOTYPE, Property};
+use syntax::regexp::Match;
+use front::stdlib::regexp::{is_regexp, get_regexp, to_regexp, exec, exec_regexp, group_value};
+use front::stdlib::array::new_array;
+use std::uint;
 
 /// Create new string
 pub fn make_string(_:Vec<Value>, _:Value, _:Value, this:Value) -> ResultValue {
@@ -12,6 +16,261 @@ pub fn get_string_length(_:Vec<Value>, _:Value, _:Value, this:Value) -> ResultVa
     let this_str: String = from_value(this).unwrap();
     Ok(to_value::<i32>(this_str.len() as i32))
 }
+/// Get the argument at `index`, or undefined if it wasn't passed
+pub fn get_arg(args:&Vec<Value>, index:uint) -> Value {
+    if index < args.len() {
+        args[index]
+    } else {
+        Value::undefined()
+    }
+}
+/// Find the first index of `pattern` in `text` at or after `start`
+fn find_chars(text:&[char], pattern:&[char], start:uint) -> Option<uint> {
+    if pattern.len() > text.len() {
+        return None;
+    }
+    range(start, text.len() - pattern.len() + 1).find(|&i| text.slice(i, i + pattern.len()) == pattern)
+}
+/// Expand the `$` patterns in a replacement string for a match
+fn expand_replacement(template:&[char], input:&[char], found:&Match, names:&[(String, uint)]) -> String {
+    let mut result = String::new();
+    let mut i = 0;
+    while i < template.len() {
+        let ch = template[i];
+        i += 1;
+        if ch != '$' || i == template.len() {
+            result.push_char(ch);
+            continue;
+        }
+        match template[i] {
+            '$' => result.push_char('$'),
+            '&' => result.push_str(String::from_chars(input.slice(found.start(), found.end())).as_slice()),
+            '`' => result.push_str(String::from_chars(input.slice_to(found.start())).as_slice()),
+            '\'' => result.push_str(String::from_chars(input.slice_from(found.end())).as_slice()),
+            '<' if !names.is_empty() => {
+                let end = match template.slice_from(i).iter().position(|&ch| ch == '>') {
+                    Some(end) => i + end,
+                    None => {
+                        result.push_char('$');
+                        continue;
+                    }
+                };
+                let name = String::from_chars(template.slice(i + 1, end));
+                match names.iter().find(|&&(ref other, _)| *other == name) {
+                    Some(&(_, index)) => match found.group(input, index) {
+                        Some(text) => result.push_str(text.as_slice()),
+                        None => ()
+                    },
+                    None => ()
+                }
+                i = end;
+            },
+            digit if digit.is_digit_radix(10) => {
+                // use two digits if they name a group, otherwise one
+                let one = digit.to_digit(10).unwrap();
+                let two = if i + 1 < template.len() && template[i + 1].is_digit_radix(10) {
+                    Some(one * 10 + template[i + 1].to_digit(10).unwrap())
+                } else {
+                    None
+                };
+                let index = match two {
+                    Some(two) if two >= 1 && two < found.groups.len() => {
+                        i += 1;
+                        two
+                    },
+                    _ if one >= 1 && one < found.groups.len() => one,
+                    _ => {
+                        result.push_char('$');
+                        continue;
+                    }
+                };
+                match found.group(input, index) {
+                    Some(text) => result.push_str(text.as_slice()),
+                    None => ()
+                }
+            },
+            _ => {
+                result.push_char('$');
+                continue;
+            }
+        }
+        i += 1;
+    }
+    result
+}
+/// Match a string against a regular expression
+pub fn match_regexp(args:Vec<Value>, global:Value, scope:Value, this:Value) -> ResultValue {
+    let text = from_value::<String>(this).unwrap();
+    let regexp_obj = try!(to_regexp(global, get_arg(&args, 0)));
+    let regexp = try!(get_regexp(global, regexp_obj));
+    if !regexp.flags.global {
+        return exec(vec!(to_value(text)), global, scope, regexp_obj);
+    }
+    let input : Vec<char> = text.as_slice().chars().collect();
+    let mut matches = Vec::new();
+    regexp_obj.set_field("lastIndex", to_value(0i32));
+    loop {
+        match exec_regexp(regexp_obj, &regexp, input.as_slice()) {
+            Some(found) => {
+                if found.start() == found.end() {
+                    regexp_obj.set_field("lastIndex", to_value(found.end() as i32 + 1));
+                }
+                matches.push(group_value(&found, input.as_slice(), 0));
+            },
+            None => break
+        }
+    }
+    Ok(if matches.is_empty() {
+        Value::new(VNull)
+    } else {
+        new_array(global, matches)
+    })
+}
+/// Replace the first match of a string or regular expression, or every match of a global regular expression
+pub fn replace(args:Vec<Value>, global:Value, scope:Value, this:Value) -> ResultValue {
+    let text = from_value::<String>(this).unwrap();
+    let input : Vec<char> = text.as_slice().chars().collect();
+    let pattern = get_arg(&args, 0);
+    let replacement = get_arg(&args, 1);
+    let (matches, names) = if is_regexp(pattern) {
+        let regexp = try!(get_regexp(global, pattern));
+        let mut matches = Vec::new();
+        let mut pos = 0;
+        while pos <= input.len() {
+            match regexp.match_from(input.as_slice(), pos) {
+                Some(found) => {
+                    pos = if found.start() == found.end() {
+                        found.end() + 1
+                    } else {
+                        found.end()
+                    };
+                    matches.push(found);
+                    if !regexp.flags.global {
+                        break;
+                    }
+                },
+                None => break
+            }
+        }
+        if regexp.flags.global {
+            pattern.set_field("lastIndex", to_value(0i32));
+        }
+        (matches, regexp.names)
+    } else {
+        let pattern : Vec<char> = pattern.to_string().as_slice().chars().collect();
+        let matches = match find_chars(input.as_slice(), pattern.as_slice(), 0) {
+            Some(start) => vec!(Match {
+                groups: vec!(Some((start, start + pattern.len())))
+            }),
+            None => Vec::new()
+        };
+        (matches, Vec::new())
+    };
+    let template : Vec<char> = replacement.to_string().as_slice().chars().collect();
+    let mut result = String::new();
+    let mut last = 0;
+    for found in matches.iter() {
+        result.push_str(String::from_chars(input.slice(last, found.start())).as_slice());
+        let replaced = match *replacement {
+            VFunction(ref func) => {
+                let mut call_args : Vec<Value> = range(0, found.groups.len()).map(|i| group_value(found, input.as_slice(), i)).collect();
+                call_args.push(to_value(found.start() as i32));
+                call_args.push(to_value(text.clone()));
+                try!(func.borrow().call(call_args, global, scope, Value::undefined())).to_string()
+            },
+            _ => expand_replacement(template.as_slice(), input.as_slice(), found, names.as_slice())
+        };
+        result.push_str(replaced.as_slice());
+        last = found.end();
+    }
+    result.push_str(String::from_chars(input.slice_from(last)).as_slice());
+    Ok(to_value(result))
+}
+/// Get the index of the first match of a regular expression, or -1 if it doesn't match
+pub fn search(args:Vec<Value>, global:Value, _:Value, this:Value) -> ResultValue {
+    let input : Vec<char> = from_value::<String>(this).unwrap().as_slice().chars().collect();
+    let regexp = try!(get_regexp(global, try!(to_regexp(global, get_arg(&args, 0)))));
+    Ok(to_value(match regexp.match_from(input.as_slice(), 0) {
+        Some(found) => found.start() as i32,
+        None => -1i32
+    }))
+}
+/// Split a string into an array by a string or regular expression separator
+pub fn split(args:Vec<Value>, global:Value, _:Value, this:Value) -> ResultValue {
+    let text = from_value::<String>(this).unwrap();
+    let input : Vec<char> = text.as_slice().chars().collect();
+    let separator = get_arg(&args, 0);
+    let limit = match get_arg(&args, 1) {
+        limit if limit.is_undefined() => uint::MAX,
+        limit => limit.to_int() as uint
+    };
+    let mut pieces = Vec::new();
+    if limit == 0 {
+        return Ok(new_array(global, pieces));
+    }
+    if separator.is_undefined() {
+        pieces.push(to_value(text));
+        return Ok(new_array(global, pieces));
+    }
+    if is_regexp(separator) {
+        let regexp = try!(get_regexp(global, separator));
+        if input.is_empty() {
+            if regexp.match_from(input.as_slice(), 0).is_none() {
+                pieces.push(to_value(text));
+            }
+            return Ok(new_array(global, pieces));
+        }
+        let mut last = 0;
+        let mut pos = 0;
+        while pos < input.len() {
+            let found = match regexp.match_from(input.as_slice(), pos) {
+                Some(found) if found.start() < input.len() => found,
+                _ => break
+            };
+            // an empty match where the last piece ended doesn't split
+            if found.end() == last {
+                pos = found.start() + 1;
+                continue;
+            }
+            pieces.push(to_value(String::from_chars(input.slice(last, found.start()))));
+            if pieces.len() == limit {
+                return Ok(new_array(global, pieces));
+            }
+            for i in range(1, found.groups.len()) {
+                pieces.push(group_value(&found, input.as_slice(), i));
+                if pieces.len() == limit {
+                    return Ok(new_array(global, pieces));
+                }
+            }
+            last = found.end();
+            pos = last;
+        }
+        pieces.push(to_value(String::from_chars(input.slice_from(last))));
+    } else {
+        let separator : Vec<char> = separator.to_string().as_slice().chars().collect();
+        if separator.is_empty() {
+            for ch in input.iter().take(limit) {
+                pieces.push(to_value(*ch));
+            }
+            return Ok(new_array(global, pieces));
+        }
+        let mut last = 0;
+        loop {
+            match find_chars(input.as_slice(), separator.as_slice(), last) {
+                Some(start) => {
+                    pieces.push(to_value(String::from_chars(input.slice(last, start))));
+                    if pieces.len() == limit {
+                        return Ok(new_array(global, pieces));
+                    }
+                    last = start + separator.len();
+                },
+                None => break
+            }
+        }
+        pieces.push(to_value(String::from_chars(input.slice_from(last))));
+    }
+    Ok(new_array(global, pieces))
+}
 /// Create a new `String` object
 pub fn _create(global: Value) -> Value {
     let string = Function::make(make_string, ["string"]);
@@ -25,6 +284,12 @@ pub fn _create(global: Value) -> Value {
         set: Value::undefined()
     };
     proto.set_prop("length", prop);
+    js_extend!(proto, {
+        "match": Function::make(match_regexp, ["regexp"]),
+        "replace": Function::make(replace, ["pattern", "replacement"]),
+        "search": Function::make(search, ["regexp"]),
+        "split": Function::make(split, ["separator", "limit"])
+    });
     string.set_field(PROTOTYPE, proto);
     string
 }
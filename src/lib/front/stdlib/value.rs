@@ -62,12 +62,25 @@ impl Value {
         console::init(global);
         error::init(global);
         function::init(global);
+        generator::init(global);
         json::init(global);
         math::init(global);
         number::init(global);
         object::init(global);
+        promise::init(global);
+        regexp::init(global);
         string::init(global);
         uri::init(global);
+        // the builtins, their fields and the fields of their prototypes aren't visited by for-in loops
+        let builtins : Vec<Value> = match *global {
+            VObject(ref data) => data.borrow().values().map(|prop| prop.value).collect(),
+            _ => Vec::new()
+        };
+        for builtin in builtins.move_iter() {
+            hide_fields(builtin.get_field(PROTOTYPE));
+            hide_fields(builtin);
+        }
+        hide_fields(global);
         global
     }
     /// Returns a new empty object
@@ -121,15 +134,15 @@ impl Value {
             _ => false
         }
     }
-    /// Returns true if the value is true
+    /// Returns true if the value is true when it's converted to a boolean
     pub fn is_true(&self) -> bool {
         match **self {
-            VObject(_) => true,
-            VString(ref s) if s.as_slice() == "1" => true,
-            VNumber(n) if n >= 1.0 && n % 1.0 == 0.0 => true,
-            VInteger(n) if n > 1 => true,
+            VObject(_) | VFunction(_) => true,
+            VString(ref s) => !s.is_empty(),
+            VNumber(n) => n != 0.0 && !n.is_nan(),
+            VInteger(n) => n != 0,
             VBoolean(v) => v,
-            _ => false
+            VNull | VUndefined => false
         }
     }
     /// Converts the value into a 64-bit floating point number
@@ -168,7 +181,7 @@ impl Value {
         };
         match obj.find(&field.into_string()) {
             Some(val) => Some(*val),
-            None => match obj.find(&PROTOTYPE.into_string()) {
+            None => match obj.find(&INSTANCE_PROTOTYPE.into_string()) {
                 Some(prop) => 
                     prop.value.get_prop(field),
                 None => None
@@ -178,23 +191,94 @@ impl Value {
     /// Resolve the property in the object and get its value, or undefined if this is not an object or the field doesn't exist
     pub fn get_field<'a>(&self, field:&'a str) -> Value {
         match self.get_prop(field) {
-            Some(prop) => prop.value,
+            Some(prop) => match *prop.get {
+                // a property with a getter gets its value by calling the getter on this value
+                VFunction(ref getter) => match getter.borrow().call(Vec::new(), Value::undefined(), Value::undefined(), *self) {
+                    Ok(val) | Err(val) => val
+                },
+                _ => prop.value
+            },
             None => Value::new(VUndefined)
         }
     }
     /// Set the field in the value
     pub fn set_field<'a>(&self, field:&'a str, val:Value) -> Value {
-        match **self {
-            VObject(ref obj) => {
-                obj.borrow_mut().insert(field.into_string(), Property::new(val));
-            },
-            VFunction(ref func) => {
-                func.borrow_mut().object.insert(field.into_string(), Property::new(val));
+        match self.get_prop(field) {
+            Some(prop) => match *prop.set {
+                // a property with a setter is set by calling the setter on this value
+                VFunction(ref setter) => {
+                    let _ = setter.borrow().call(vec!(val), Value::undefined(), Value::undefined(), *self);
+                    return val;
+                },
+                // a property with only a getter can't be set
+                _ if !prop.get.is_undefined() => return val,
+                _ => ()
             },
+            None => ()
+        }
+        // an existing field keeps its attributes, but a new one can be changed, enumerated and deleted
+        fn assign(obj:&mut ObjectData, field:String, val:Value) {
+            let prop = match obj.find(&field) {
+                Some(prop) => Property {value: val, ..*prop},
+                None => Property {
+                    configurable: true,
+                    enumerable: true,
+                    writable: true,
+                    ..Property::new(val)
+                }
+            };
+            obj.insert(field, prop);
+        }
+        match **self {
+            VObject(ref obj) => assign(&mut *obj.borrow_mut(), field.into_string(), val),
+            VFunction(ref func) => assign(&mut func.borrow_mut().object, field.into_string(), val),
             _ => ()
         }
         val
     }
+    /// Returns true if setting the field would change it, which it wouldn't if it's read-only or
+    /// only has a getter, so strict mode code can throw instead
+    pub fn can_set_field<'a>(&self, field:&'a str) -> bool {
+        match self.get_prop(field) {
+            Some(prop) if prop.get.is_undefined() && prop.set.is_undefined() => prop.writable,
+            Some(prop) => !prop.set.is_undefined(),
+            None => true
+        }
+    }
+    /// Returns true if the field is in the value or its prototype chain
+    pub fn has_field<'a>(&self, field:&'a str) -> bool {
+        self.get_prop(field).is_some()
+    }
+    /// Remove the field from the value, returning false if it can't be removed because it isn't configurable
+    pub fn remove_field<'a>(&self, field:&'a str) -> bool {
+        fn remove(obj:&mut ObjectData, field:String) -> bool {
+            match obj.find(&field).map(|prop| prop.configurable) {
+                Some(false) => false,
+                Some(true) => {
+                    obj.remove(&field);
+                    true
+                },
+                None => true
+            }
+        }
+        match **self {
+            VObject(ref obj) => remove(&mut *obj.borrow_mut(), field.into_string()),
+            VFunction(ref func) => remove(&mut func.borrow_mut().object, field.into_string()),
+            _ => true
+        }
+    }
+    /// Returns true if the prototype of the constructor is in the prototype chain of the value
+    pub fn is_instance_of(&self, constructor:Value) -> bool {
+        let proto = constructor.get_field(PROTOTYPE);
+        let mut curr = self.get_field(INSTANCE_PROTOTYPE);
+        while !curr.is_null_or_undefined() {
+            if curr.ptr.ptr_eq(&proto.ptr) {
+                return true;
+            }
+            curr = curr.get_field(INSTANCE_PROTOTYPE);
+        }
+        false
+    }
     /// Set the property in the value
     pub fn set_prop<'a>(&self, field:&'a str, prop:Property) -> Property {
         match **self {
@@ -374,6 +458,16 @@ impl Shr<Value, Value> for Value {
         to_value(self.to_int() >> other.to_int() as uint)
     }
 }
+impl Value {
+    /// Bit-shift the value rightwards, filling in zeroes, as `>>>` does
+    pub fn ushr(&self, other:&Value) -> Value {
+        to_value((self.to_int() as u32 >> (other.to_int() & 31) as uint) as f64)
+    }
+    /// Raise the value to the power of another, as `**` does
+    pub fn pow(&self, other:&Value) -> Value {
+        to_value(self.to_num().powf(other.to_num()))
+    }
+}
 impl Not<Value> for Value {
     fn not(&self) -> Value {
         to_value(!self.is_true())
@@ -389,6 +483,19 @@ impl PartialOrd for Value {
         self.to_num().partial_cmp(&other.to_num())
     }
 }
+/// Make the fields of an object or function not enumerable
+fn hide_fields(obj:Value) {
+    fn hide(data:&mut ObjectData) {
+        for (_, prop) in data.mut_iter() {
+            prop.enumerable = false;
+        }
+    }
+    match *obj {
+        VObject(ref data) => hide(&mut *data.borrow_mut()),
+        VFunction(ref func) => hide(&mut func.borrow_mut().object),
+        _ => ()
+    }
+}
 /// Conversion to Javascript values from Rust values
 pub trait ToValue {
     /// Convert this value to a Rust value
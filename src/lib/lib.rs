@@ -12,6 +12,7 @@
 	uppercase_variables, non_camel_case_types, unused_must_use)]
 //! This is a library with seperate modules for Javascript parsing, the Javascript
 //! standard library, and Javascript execution through LibJIT
+extern crate arena;
 extern crate collections;
 extern crate jit;
 #[phase(plugin, link)]
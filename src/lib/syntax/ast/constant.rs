@@ -5,12 +5,14 @@ use std::fmt::{Formatter, Result, Show};
 pub enum Const {
     /// A UTF-8 string, such as `"Hello, world"`
     CString(String),
-    /// A regular expression, such as `/where('s| is) [wW]ally/`
-    CRegExp(String, bool, bool),
+    /// A regular expression and its flags, such as `/where('s| is) [wW]ally/gi`
+    CRegExp(String, String),
     /// A 64-bit floating-point number, such as `3.1415`
     CNum(f64),
     /// A 32-bit integer, such as `42`
     CInt(i32),
+    /// A BigInt of any size, as the digits it was written with, such as `0xffn`
+    CBigInt(String),
     /// A boolean, which is either `true` or `false` and is used to check if criteria are met
     CBool(bool),
     /// The `null` value, which represents a non-existant value
@@ -22,9 +24,10 @@ impl Show for Const {
     fn fmt(&self, f: &mut Formatter) -> Result {
         return match *self {
             CString(ref st) => write!(f, "\"{}\"", st),
-            CRegExp(ref reg, _, _) => write!(f, "~/{}/", reg),
+            CRegExp(ref reg, ref flags) => write!(f, "/{}/{}", reg, flags),
             CNum(num) => write!(f, "{}", num),
             CInt(num) => write!(f, "{}", num),
+            CBigInt(ref digits) => write!(f, "{}n", digits),
             CBool(v) => write!(f, "{}", v),
             CNull => write!(f, "null"),
             CUndefined => write!(f, "undefined")
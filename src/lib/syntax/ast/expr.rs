@@ -1,8 +1,8 @@
 use std::fmt::{Formatter, Result, Show};
 use syntax::ast::op::*;
 use syntax::ast::constant::Const;
+use syntax::ast::pattern::{Pattern, Params};
 use syntax::ast::pos::Position;
-use collections::treemap::TreeMap;
 #[deriving(Clone, PartialEq)]
 /// A Javascript expression, including its position
 pub struct Expr {
@@ -18,6 +18,131 @@ impl Expr {
     pub fn new(def: ExprDef, start:Position, end:Position) -> Expr {
         Expr{def: def, start: start, end: end}
     }
+    /// Returns true if this is the body of a script or function with a `"use strict"` directive,
+    /// which makes it and the functions inside it strict mode code
+    pub fn has_strict_directive(&self) -> bool {
+        match self.def {
+            BlockExpr(ref exprs) => exprs.iter().take_while(|expr| match expr.def {
+                DirectiveExpr(_) => true,
+                _ => false
+            }).any(|expr| expr.def == DirectiveExpr("use strict".into_string())),
+            _ => false
+        }
+    }
+    /// Get the expressions directly inside this expression
+    pub fn children<'a>(&'a self) -> Vec<&'a Expr> {
+        let mut children = Vec::new();
+        match self.def {
+            ConstExpr(_) | DirectiveExpr(_) | LocalExpr(_) | ThisExpr | SuperExpr | BreakExpr(_) | ContinueExpr(_) | ReturnExpr(None) | YieldExpr(None) |
+            ImportExpr(_, _) | ExportExpr(ExportNames(_, _)) | ExportExpr(ExportAll(_)) | ErrorExpr(_) => (),
+            UnaryOpExpr(_, box ref a) | GetConstFieldExpr(box ref a, _) | LabelledExpr(_, box ref a) |
+            ReturnExpr(Some(box ref a)) | ThrowExpr(box ref a) | TypeOfExpr(box ref a) |
+            YieldExpr(Some(box ref a)) | DelegateYieldExpr(box ref a) | AwaitExpr(box ref a) |
+            ExportExpr(ExportDeclaration(box ref a)) | ExportExpr(ExportDefault(box ref a)) =>
+                children.push(a),
+            FunctionDeclExpr(_, ref params, box ref body) | GeneratorDeclExpr(_, ref params, box ref body) |
+            AsyncFunctionDeclExpr(_, ref params, box ref body) | ArrowFunctionDeclExpr(ref params, box ref body) |
+            AsyncArrowFunctionDeclExpr(ref params, box ref body) => {
+                children.push_all(params.exprs().as_slice());
+                children.push(body);
+            },
+            BinOpExpr(_, box ref a, box ref b) | GetFieldExpr(box ref a, box ref b) |
+            WhileLoopExpr(box ref a, box ref b) | DoWhileLoopExpr(box ref a, box ref b) => {
+                children.push(a);
+                children.push(b);
+            },
+            AssignExpr(box ref target, box ref val) | AssignOpExpr(_, box ref target, box ref val) => {
+                children.push_all(target.exprs().as_slice());
+                children.push(val);
+            },
+            BlockExpr(ref exprs) =>
+                children.extend(exprs.iter()),
+            ArrayDeclExpr(ref items) =>
+                children.extend(items.iter().map(|item| item.expr())),
+            CallExpr(box ref func, ref args) | ConstructExpr(box ref func, ref args) => {
+                children.push(func);
+                children.extend(args.iter().map(|arg| arg.expr()));
+            },
+            ForLoopExpr(ref init, ref cond, ref step, box ref body) => {
+                for expr in init.iter().chain(cond.iter()).chain(step.iter()) {
+                    children.push(&**expr);
+                }
+                children.push(body);
+            },
+            ForInLoopExpr(box ref var, box ref obj, box ref body) => {
+                children.push(var);
+                children.push(obj);
+                children.push(body);
+            },
+            ConditionalExpr(box ref cond, box ref if_e, box ref else_e) => {
+                children.push(cond);
+                children.push(if_e);
+                children.push(else_e);
+            },
+            IfExpr(box ref cond, box ref expr, ref else_e) => {
+                children.push(cond);
+                children.push(expr);
+                match *else_e {
+                    Some(box ref else_e) => children.push(else_e),
+                    None => ()
+                }
+            },
+            SwitchExpr(box ref value, ref cases, ref default) => {
+                children.push(value);
+                for &(ref cond, ref block) in cases.iter() {
+                    children.push(cond);
+                    children.extend(block.iter());
+                }
+                match *default {
+                    Some(box ref default) => children.push(default),
+                    None => ()
+                }
+            },
+            ObjectDeclExpr(ref props) => for prop in props.iter() {
+                match prop.key() {
+                    Some(&ComputedKey(ref key)) => children.push(key),
+                    _ => ()
+                }
+                children.push(prop.expr());
+            },
+            TryExpr(box ref expr, ref catch, ref finally) => {
+                children.push(expr);
+                match *catch {
+                    Some((_, box ref catch)) => children.push(catch),
+                    None => ()
+                }
+                match *finally {
+                    Some(box ref finally) => children.push(finally),
+                    None => ()
+                }
+            },
+            VarDeclExpr(_, ref vars) => {
+                for &(ref pattern, ref val) in vars.iter() {
+                    children.push_all(pattern.exprs().as_slice());
+                    match *val {
+                        Some(ref val) => children.push(val),
+                        None => ()
+                    }
+                }
+            },
+            TemplateExpr(ref tag, _, ref exprs) => {
+                match *tag {
+                    Some(box ref tag) => children.push(tag),
+                    None => ()
+                }
+                children.extend(exprs.iter());
+            },
+            ClassDeclExpr(_, ref parent, box ref constructor, ref members) => {
+                match *parent {
+                    Some(box ref parent) => children.push(parent),
+                    None => ()
+                }
+                children.push(constructor);
+                children.extend(members.iter().map(|member| &member.func));
+            }
+        }
+        children
+    }
 }
 impl Show for Expr {
     fn fmt(&self, f: &mut Formatter) -> Result {
@@ -33,60 +158,307 @@ pub enum ExprDef {
     UnaryOpExpr(UnaryOp, Box<Expr>),
     /// Make a constant value
     ConstExpr(Const),
+    /// A string on its own at the start of a script or function body, which gives its value like a
+    /// constant but can also change how the body runs, as `"use strict"` does
+    DirectiveExpr(String),
     /// Run several expressions from top-to-bottom
     BlockExpr(Vec<Expr>),
     /// Load a reference to a value
     LocalExpr(String),
+    /// Get the `this` value of the enclosing function
+    ThisExpr,
+    /// Refer to the parent class, which is only allowed in class methods, as in `super.method()`,
+    /// or call the parent constructor from a derived constructor, as in `super()`
+    SuperExpr,
     /// Gets the constant field of a value
     GetConstFieldExpr(Box<Expr>, String),
     /// Gets the field of a value
     GetFieldExpr(Box<Expr>, Box<Expr>),
     /// Call a function with some values
-    CallExpr(Box<Expr>, Vec<Expr>),
+    CallExpr(Box<Expr>, Vec<Element>),
     /// Repeatedly run an expression while the conditional expression resolves to true
     WhileLoopExpr(Box<Expr>, Box<Expr>),
+    /// Run an expression once, then repeatedly while the conditional expression resolves to true
+    DoWhileLoopExpr(Box<Expr>, Box<Expr>),
+    /// Run the initialiser, then repeatedly run an expression and the step while the conditional expression resolves to true
+    ForLoopExpr(Option<Box<Expr>>, Option<Box<Expr>>, Option<Box<Expr>>, Box<Expr>),
+    /// Run an expression for each enumerable key of an object, binding the key to the first expression
+    ForInLoopExpr(Box<Expr>, Box<Expr>, Box<Expr>),
+    /// Leave the innermost loop or switch, or the labelled statement with the given label
+    BreakExpr(Option<String>),
+    /// Skip to the next iteration of the innermost loop, or the loop with the given label
+    ContinueExpr(Option<String>),
+    /// Run an expression with a label that `break` and `continue` can refer to
+    LabelledExpr(String, Box<Expr>),
     /// Check if a conditional expression is true and run an expression if it is and another expression if it isn't
     IfExpr(Box<Expr>, Box<Expr>, Option<Box<Expr>>),
+    /// Evaluate the first expression, then give the value of the second if it's true or the third if it isn't
+    ConditionalExpr(Box<Expr>, Box<Expr>, Box<Expr>),
     /// Run blocks whose cases match the expression
     SwitchExpr(Box<Expr>, Vec<(Expr, Vec<Expr>)>, Option<Box<Expr>>),
-    /// Create an object out of the binary tree given
-    ObjectDeclExpr(Box<TreeMap<String, Expr>>),
+    /// Create an object with the properties given, in the order they're defined
+    ObjectDeclExpr(Vec<PropertyDef>),
     /// Create an array with items inside
-    ArrayDeclExpr(Vec<Expr>),
-    /// Create a function with the given name, arguments, and expression
-    FunctionDeclExpr(Option<String>, Vec<String>, Box<Expr>),
-    /// Create an arrow function with the given arguments and expression
-    ArrowFunctionDeclExpr(Vec<String>, Box<Expr>),
+    ArrayDeclExpr(Vec<Element>),
+    /// Create a function with the given name, parameters, and expression
+    FunctionDeclExpr(Option<String>, Params, Box<Expr>),
+    /// Create a generator function with the given name, parameters, and expression, which makes a
+    /// generator object that runs the expression a piece at a time when it's called
+    GeneratorDeclExpr(Option<String>, Params, Box<Expr>),
+    /// Create an async function with the given name, parameters, and expression, which gives a
+    /// promise of what the expression returns when it's called
+    AsyncFunctionDeclExpr(Option<String>, Params, Box<Expr>),
+    /// Create an arrow function with the given parameters and expression
+    ArrowFunctionDeclExpr(Params, Box<Expr>),
+    /// Create an async arrow function with the given parameters and expression
+    AsyncArrowFunctionDeclExpr(Params, Box<Expr>),
     /// Construct an object from the function and arguments given
-    ConstructExpr(Box<Expr>, Vec<Expr>),
+    ConstructExpr(Box<Expr>, Vec<Element>),
     /// Return the expression from a function
     ReturnExpr(Option<Box<Expr>>),
+    /// Suspend a generator, giving the expression as its next value, and resume with the value it is resumed with
+    YieldExpr(Option<Box<Expr>>),
+    /// Suspend a generator for each value of the iterator given, resuming with the value it returns
+    DelegateYieldExpr(Box<Expr>),
+    /// Suspend an async function until the promise given settles, resuming with its value or throwing its reason
+    AwaitExpr(Box<Expr>),
     /// Throw a value
     ThrowExpr(Box<Expr>),
-    /// Assign an expression to a value
-    AssignExpr(Box<Expr>, Box<Expr>),
-    /// A variable declaration
-    VarDeclExpr(Vec<(String, Option<Expr>)>),
+    /// Run an expression, running the catch block with the thrown value bound to the name if it throws, then always running the finally block
+    TryExpr(Box<Expr>, Option<(String, Box<Expr>)>, Option<Box<Expr>>),
+    /// Assign an expression to a name, field or destructuring pattern
+    AssignExpr(Box<Pattern>, Box<Expr>),
+    /// Apply a binary operator to a name or field and an expression, assigning the result back to
+    /// it, where the object and key of a field are only evaluated once
+    AssignOpExpr(BinOp, Box<Pattern>, Box<Expr>),
+    /// A variable declaration of the given kind, which destructures each value into a pattern
+    VarDeclExpr(DeclKind, Vec<(Pattern, Option<Expr>)>),
     /// Return a string representing the type of the given expression
-    TypeOfExpr(Box<Expr>)
+    TypeOfExpr(Box<Expr>),
+    /// Join a template literal's cooked strings with its substituted expressions, or call the tag
+    /// function with the strings and the values of the expressions if it has one
+    TemplateExpr(Option<Box<Expr>>, Vec<(String, String)>, Vec<Expr>),
+    /// Create a class with the given name, parent class, constructor function and members
+    ClassDeclExpr(Option<String>, Option<Box<Expr>>, Box<Expr>, Vec<ClassMember>),
+    /// Bring bindings exported by the module with the given specifier into scope
+    ImportExpr(Vec<ImportSpecifier>, String),
+    /// Export bindings from a module
+    ExportExpr(Export),
+    /// Stand in for a statement that couldn't be parsed when recovering from syntax errors, with
+    /// why it couldn't be parsed
+    ErrorExpr(String)
+}
+#[deriving(Clone, PartialEq)]
+/// A binding brought into scope by an import declaration
+pub enum ImportSpecifier {
+    /// Bind the default export of the module to the name, as in `import name from "module"`
+    ImportDefault(String),
+    /// Bind an object holding all the exports of the module to the name, as in `import * as name from "module"`
+    ImportNamespace(String),
+    /// Bind the export with the first name to the second name, as in `import {name as local} from "module"`
+    ImportNamed(String, String)
+}
+impl ImportSpecifier {
+    /// Get the name this binds in the importing module
+    pub fn local<'a>(&'a self) -> &'a String {
+        match *self {
+            ImportDefault(ref local) | ImportNamespace(ref local) | ImportNamed(_, ref local) => local
+        }
+    }
+}
+#[deriving(Clone, PartialEq)]
+/// What an export declaration exports
+pub enum Export {
+    /// Declare bindings and export each of them under its own name, as in `export var a = 1`
+    ExportDeclaration(Box<Expr>),
+    /// Export the value of the expression, or the function or class it declares, as the default export
+    ExportDefault(Box<Expr>),
+    /// Export each binding with the first name under the second name, taking them from the module
+    /// with the given specifier if there is one, as in `export {a as b} from "module"`
+    ExportNames(Vec<(String, String)>, Option<String>),
+    /// Export all the exports of the module with the given specifier except its default export
+    ExportAll(String)
+}
+impl Show for Export {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        match *self {
+            ExportDeclaration(ref decl) => write!(f, "export {}", decl),
+            ExportDefault(ref expr) => write!(f, "export default {}", expr),
+            ExportNames(ref names, ref module) => {
+                try!(write!(f, "export {}", "{"));
+                for (i, &(ref local, ref exported)) in names.iter().enumerate() {
+                    try!(write!(f, "{}{} as {}", if i == 0 {""} else {", "}, local, exported));
+                }
+                try!(write!(f, "{}", "}"));
+                match *module {
+                    Some(ref module) => write!(f, " from \"{}\"", module),
+                    None => Ok(())
+                }
+            },
+            ExportAll(ref module) => write!(f, "export * from \"{}\"", module)
+        }
+    }
+}
+#[deriving(Clone, PartialEq)]
+/// A method, getter or setter in a class body
+pub struct ClassMember {
+    /// The name of the member
+    pub name: String,
+    /// Whether the member is defined on the class itself rather than its prototype
+    pub is_static: bool,
+    /// Whether the member is a method, getter or setter
+    pub kind: MethodKind,
+    /// The function of the member
+    pub func: Expr
+}
+impl Show for ClassMember {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        if self.is_static {
+            try!(write!(f, "static "));
+        }
+        match self.func.def {
+            FunctionDeclExpr(_, ref params, ref body) => write!(f, "{}{}({}) {}", self.kind, self.name, params, body),
+            GeneratorDeclExpr(_, ref params, ref body) => write!(f, "{}*{}({}) {}", self.kind, self.name, params, body),
+            AsyncFunctionDeclExpr(_, ref params, ref body) => write!(f, "async {}{}({}) {}", self.kind, self.name, params, body),
+            _ => write!(f, "{}{} {}", self.kind, self.name, self.func)
+        }
+    }
+}
+#[deriving(Clone, PartialEq)]
+/// The kind of a member of a class body or a method property of an object literal
+pub enum MethodKind {
+    /// A method, which is a plain function property
+    PlainMethod,
+    /// A getter, which is called to get the property
+    GetMethod,
+    /// A setter, which is called with the value the property is set to
+    SetMethod
+}
+impl Show for MethodKind {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        write!(f, "{}", match *self {
+            PlainMethod => "",
+            GetMethod => "get ",
+            SetMethod => "set "
+        })
+    }
+}
+#[deriving(Clone, PartialEq)]
+/// A property of an object literal
+pub enum PropertyDef {
+    /// A property with a value, as in `key: value`, or `key` alone for a shorthand property
+    ValueProperty(PropertyKey, Expr),
+    /// A method, getter or setter, as in `get key() {}`
+    MethodProperty(PropertyKey, MethodKind, Expr),
+    /// The fields of another object, as in `{...obj}`
+    SpreadProperty(Expr)
+}
+impl PropertyDef {
+    /// Get the key of this property, if it isn't a spread
+    pub fn key<'a>(&'a self) -> Option<&'a PropertyKey> {
+        match *self {
+            ValueProperty(ref key, _) | MethodProperty(ref key, _, _) => Some(key),
+            SpreadProperty(_) => None
+        }
+    }
+    /// Get the value, function or spread object of this property
+    pub fn expr<'a>(&'a self) -> &'a Expr {
+        match *self {
+            ValueProperty(_, ref expr) | MethodProperty(_, _, ref expr) | SpreadProperty(ref expr) => expr
+        }
+    }
+}
+impl Show for PropertyDef {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        match *self {
+            ValueProperty(ref key, ref value) => write!(f, "{}: {}", key, value),
+            MethodProperty(ref key, ref kind, ref func) => match func.def {
+                FunctionDeclExpr(_, ref params, ref body) => write!(f, "{}{}({}) {}", kind, key, params, body),
+                GeneratorDeclExpr(_, ref params, ref body) => write!(f, "{}*{}({}) {}", kind, key, params, body),
+                AsyncFunctionDeclExpr(_, ref params, ref body) => write!(f, "async {}{}({}) {}", kind, key, params, body),
+                _ => write!(f, "{}{} {}", kind, key, func)
+            },
+            SpreadProperty(ref expr) => write!(f, "...{}", expr)
+        }
+    }
+}
+#[deriving(Clone, PartialEq)]
+/// The key of a property in an object literal
+pub enum PropertyKey {
+    /// A key written as a name, string or number, which is converted to a string when parsed
+    NamedKey(String),
+    /// A key that is worked out when the object is made, as in `[key]: value`
+    ComputedKey(Expr)
+}
+impl Show for PropertyKey {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        match *self {
+            NamedKey(ref name) => write!(f, "{}", name),
+            ComputedKey(ref key) => write!(f, "[{}]", key)
+        }
+    }
+}
+#[deriving(Clone, PartialEq)]
+/// The keyword a variable was declared with
+pub enum DeclKind {
+    /// A `var` declaration, which is scoped to the enclosing function
+    VarDecl,
+    /// A `let` declaration, which is scoped to the enclosing block
+    LetDecl,
+    /// A `const` declaration, which is scoped to the enclosing block and can't be assigned to
+    ConstDecl
+}
+impl Show for DeclKind {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        write!(f, "{}", match *self {
+            VarDecl => "var",
+            LetDecl => "let",
+            ConstDecl => "const"
+        })
+    }
+}
+#[deriving(Clone, PartialEq)]
+/// An argument of a call or an item of an array literal
+pub enum Element {
+    /// A single value
+    ExprElement(Expr),
+    /// Each item of an array-like value, as in `f(...args)`
+    SpreadElement(Expr)
+}
+impl Element {
+    /// Get the expression of this element
+    pub fn expr<'a>(&'a self) -> &'a Expr {
+        match *self {
+            ExprElement(ref expr) | SpreadElement(ref expr) => expr
+        }
+    }
+}
+impl Show for Element {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        match *self {
+            ExprElement(ref expr) => write!(f, "{}", expr),
+            SpreadElement(ref expr) => write!(f, "...{}", expr)
+        }
+    }
 }
 impl Operator for ExprDef {
     fn get_assoc(&self) -> bool {
         match *self {
-            ConstructExpr(_, _) | UnaryOpExpr(_, _) | TypeOfExpr(_) | IfExpr(_, _, _) | AssignExpr(_, _) => false,
+            ConstructExpr(_, _) | UnaryOpExpr(_, _) | TypeOfExpr(_) | ConditionalExpr(_, _, _) | AssignExpr(_, _) | AssignOpExpr(_, _, _) => false,
             _ => true
         }
     }
     fn get_precedence(&self) -> uint {
         match *self {
             GetFieldExpr(_, _) | GetConstFieldExpr(_, _) => 1,
-            CallExpr(_, _) | ConstructExpr(_, _) => 2,
+            CallExpr(_, _) | ConstructExpr(_, _) | TemplateExpr(Some(_), _, _) => 2,
             UnaryOpExpr(UnaryIncrementPost, _) | UnaryOpExpr(UnaryIncrementPre, _) | UnaryOpExpr(UnaryDecrementPost, _) | UnaryOpExpr(UnaryDecrementPre, _) => 3,
-            UnaryOpExpr(UnaryNot, _) | UnaryOpExpr(UnaryMinus, _) | TypeOfExpr(_) => 4,
+            UnaryOpExpr(UnaryNot, _) | UnaryOpExpr(UnaryMinus, _) | UnaryOpExpr(UnaryPlus, _) |
+            UnaryOpExpr(UnaryDelete, _) | UnaryOpExpr(UnaryVoid, _) | TypeOfExpr(_) | AwaitExpr(_) => 4,
             BinOpExpr(op, _, _) => op.get_precedence(),
-            IfExpr(_, _, _) => 15,
-            // 16 should be yield
-            AssignExpr(_, _) => 17,
+            ConditionalExpr(_, _, _) => 15,
+            YieldExpr(_) | DelegateYieldExpr(_) => 16,
+            AssignExpr(_, _) | AssignOpExpr(_, _, _) => 17,
             _ => 19
         }
     }
@@ -95,6 +467,7 @@ impl Show for ExprDef {
     fn fmt(&self, f: &mut Formatter) -> Result {
         return match *self {
             ConstExpr(ref c) => write!(f, "{}", c),
+            DirectiveExpr(ref directive) => write!(f, "\"{}\"", directive),
             BlockExpr(ref block) => {
                 try!(write!(f, "{}", "{"));
                 for expr in block.iter() {
@@ -103,6 +476,9 @@ impl Show for ExprDef {
                 write!(f, "{}", "}")
             },
             LocalExpr(ref s) => write!(f, "{}", s),
+            ThisExpr => write!(f, "this"),
+            SuperExpr => write!(f, "super"),
+            ErrorExpr(_) => write!(f, "<error>"),
             GetConstFieldExpr(ref ex, ref field) => write!(f, "{}.{}", ex, field),
             GetFieldExpr(ref ex, ref field) => write!(f, "{}[{}]", ex, field),
             CallExpr(ref ex, ref args) => {
@@ -112,22 +488,120 @@ impl Show for ExprDef {
             },
             ConstructExpr(ref func, ref args) => write!(f, "new {}({})", func, args),
             WhileLoopExpr(ref cond, ref expr) => write!(f, "while({}) {}", cond, expr),
+            DoWhileLoopExpr(ref expr, ref cond) => write!(f, "do {} while({})", expr, cond),
+            ForLoopExpr(ref init, ref cond, ref step, ref expr) => {
+                try!(write!(f, "for("));
+                match *init {
+                    Some(ref init) => try!(write!(f, "{}", init)),
+                    None => ()
+                }
+                try!(write!(f, ";"));
+                match *cond {
+                    Some(ref cond) => try!(write!(f, "{}", cond)),
+                    None => ()
+                }
+                try!(write!(f, ";"));
+                match *step {
+                    Some(ref step) => try!(write!(f, "{}", step)),
+                    None => ()
+                }
+                write!(f, ") {}", expr)
+            },
+            ForInLoopExpr(ref var, ref obj, ref expr) => write!(f, "for({} in {}) {}", var, obj, expr),
+            BreakExpr(Some(ref label)) => write!(f, "break {}", label),
+            BreakExpr(None) => write!(f, "{}", "break"),
+            ContinueExpr(Some(ref label)) => write!(f, "continue {}", label),
+            ContinueExpr(None) => write!(f, "{}", "continue"),
+            LabelledExpr(ref label, ref expr) => write!(f, "{}: {}", label, expr),
             IfExpr(ref cond, ref expr, None) => write!(f, "if({}) {}", cond, expr),
             IfExpr(ref cond, ref expr, Some(ref else_e)) => write!(f, "if({}) {} else {}", cond, expr, else_e),
+            ConditionalExpr(ref cond, ref if_e, ref else_e) => write!(f, "{} ? {} : {}", cond, if_e, else_e),
             SwitchExpr(ref val, ref vals, None) => write!(f, "switch({}){}", val, vals),
             SwitchExpr(ref val, ref vals, Some(ref def)) => write!(f, "switch({}){}default:{}", val, vals, def),
-            ObjectDeclExpr(ref map) => write!(f, "{}", map),
+            ObjectDeclExpr(ref props) => {
+                let props:Vec<String> = props.iter().map(|prop| prop.to_string()).collect();
+                write!(f, "{}{}{}", "{", props.connect(", "), "}")
+            },
             ArrayDeclExpr(ref arr) => write!(f, "{}", arr),
-            FunctionDeclExpr(ref name, ref args, ref expr) => write!(f, "function {}({}){}", name, args.connect(", "), expr),
-            ArrowFunctionDeclExpr(ref args, ref expr) => write!(f, "({}) => {}", args.connect(", "), expr),
+            FunctionDeclExpr(ref name, ref params, ref expr) => write!(f, "function {}({}){}", name, params, expr),
+            GeneratorDeclExpr(ref name, ref params, ref expr) => write!(f, "function* {}({}){}", name, params, expr),
+            AsyncFunctionDeclExpr(ref name, ref params, ref expr) => write!(f, "async function {}({}){}", name, params, expr),
+            ArrowFunctionDeclExpr(ref params, ref expr) => write!(f, "({}) => {}", params, expr),
+            AsyncArrowFunctionDeclExpr(ref params, ref expr) => write!(f, "async ({}) => {}", params, expr),
             BinOpExpr(ref op, ref a, ref b) => write!(f, "{} {} {}", a, op, b),
             UnaryOpExpr(ref op, ref a) => write!(f, "{}{}", op, a),
             ReturnExpr(Some(ref ex)) => write!(f, "return {}", ex),
             ReturnExpr(None) => write!(f, "{}", "return"),
+            YieldExpr(Some(ref ex)) => write!(f, "yield {}", ex),
+            YieldExpr(None) => write!(f, "{}", "yield"),
+            DelegateYieldExpr(ref ex) => write!(f, "yield* {}", ex),
+            AwaitExpr(ref ex) => write!(f, "await {}", ex),
             ThrowExpr(ref ex) => write!(f, "throw {}", ex),
+            TryExpr(ref expr, ref catch, ref finally) => {
+                try!(write!(f, "try {}", expr));
+                match *catch {
+                    Some((ref name, ref catch)) => try!(write!(f, " catch({}) {}", name, catch)),
+                    None => ()
+                }
+                match *finally {
+                    Some(ref finally) => write!(f, " finally {}", finally),
+                    None => Ok(())
+                }
+            },
             AssignExpr(ref ref_e, ref val) => write!(f, "{} = {}", ref_e, val),
-            VarDeclExpr(ref vars) => write!(f, "var {}", vars),
+            AssignOpExpr(ref op, ref ref_e, ref val) => write!(f, "{} {}= {}", ref_e, op, val),
+            VarDeclExpr(ref kind, ref vars) => write!(f, "{} {}", kind, vars),
             TypeOfExpr(ref e) => write!(f, "typeof {}", e),
+            TemplateExpr(ref tag, ref strings, ref exprs) => {
+                match *tag {
+                    Some(ref tag) => try!(write!(f, "{}", tag)),
+                    None => ()
+                }
+                try!(write!(f, "`"));
+                for (i, &(_, ref raw)) in strings.iter().enumerate() {
+                    try!(write!(f, "{}", raw));
+                    if i < exprs.len() {
+                        try!(write!(f, "${}{}{}", "{", exprs[i], "}"));
+                    }
+                }
+                write!(f, "`")
+            },
+            ImportExpr(ref specifiers, ref module) => {
+                let mut parts = Vec::new();
+                let mut named = Vec::new();
+                for specifier in specifiers.iter() {
+                    match *specifier {
+                        ImportDefault(ref local) => parts.push(local.clone()),
+                        ImportNamespace(ref local) => parts.push(format!("* as {}", local)),
+                        ImportNamed(ref name, ref local) => named.push(format!("{} as {}", name, local))
+                    }
+                }
+                if !named.is_empty() {
+                    parts.push(format!("{}{}{}", "{", named.connect(", "), "}"));
+                }
+                if parts.is_empty() {
+                    write!(f, "import \"{}\"", module)
+                } else {
+                    write!(f, "import {} from \"{}\"", parts.connect(", "), module)
+                }
+            },
+            ExportExpr(ref export) => write!(f, "{}", export),
+            ClassDeclExpr(ref name, ref parent, ref constructor, ref members) => {
+                try!(write!(f, "class"));
+                match *name {
+                    Some(ref name) => try!(write!(f, " {}", name)),
+                    None => ()
+                }
+                match *parent {
+                    Some(ref parent) => try!(write!(f, " extends {}", parent)),
+                    None => ()
+                }
+                try!(write!(f, " {}constructor: {}", "{", constructor));
+                for member in members.iter() {
+                    try!(write!(f, "; {}", member));
+                }
+                write!(f, "{}", "}")
+            }
         }
     }
 }
\ No newline at end of file
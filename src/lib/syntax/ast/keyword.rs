@@ -3,6 +3,8 @@ use std::from_str::FromStr;
 #[deriving(Clone, PartialEq)]
 /// A Javascript Keyword
 pub enum Keyword {
+    /// The `await` keyword
+    KAwait,
     /// The `break` keyword
     KBreak,
     /// The `case` keyword
@@ -11,6 +13,8 @@ pub enum Keyword {
     KCatch,
     /// The `class` keyword, which is reserved for future use
     KClass,
+    /// The `const` keyword
+    KConst,
     /// The `continue` keyword
     KContinue,
     /// The `debugger` keyword
@@ -25,6 +29,8 @@ pub enum Keyword {
     KElse,
     /// The `enum` keyword
     KEnum,
+    /// The `export` keyword
+    KExport,
     /// The `extends` keyword
     KExtends,
     /// The `finally` keyword
@@ -41,6 +47,8 @@ pub enum Keyword {
     KInstanceOf,
     /// The `import` keyword
     KImport,
+    /// The `let` keyword
+    KLet,
     /// The `new` keyword
     KNew,
     /// The `return` keyword
@@ -64,15 +72,19 @@ pub enum Keyword {
     /// The `while` keyword
     KWhile,
     /// The `with` keyword
-    KWith
+    KWith,
+    /// The `yield` keyword
+    KYield
 }
 impl FromStr for Keyword {
     fn from_str(s: &str) -> Option<Keyword> {
         match s {
+            "await" => Some(KAwait),
             "break" => Some(KBreak),
             "case" => Some(KCase),
             "catch" => Some(KCatch),
             "class" => Some(KClass),
+            "const" => Some(KConst),
             "continue" => Some(KContinue),
             "debugger" => Some(KDebugger),
             "default" => Some(KDefault),
@@ -80,6 +92,7 @@ impl FromStr for Keyword {
             "do" => Some(KDo),
             "else" => Some(KElse),
             "enum" => Some(KEnum),
+            "export" => Some(KExport),
             "extends" => Some(KExtends),
             "finally" => Some(KFinally),
             "for" => Some(KFor),
@@ -88,6 +101,7 @@ impl FromStr for Keyword {
             "in" => Some(KIn),
             "instanceof" => Some(KInstanceOf),
             "import" => Some(KImport),
+            "let" => Some(KLet),
             "new" => Some(KNew),
             "return" => Some(KReturn),
             "super" => Some(KSuper),
@@ -100,6 +114,7 @@ impl FromStr for Keyword {
             "void" => Some(KVoid),
             "while" => Some(KWhile),
             "with" => Some(KWith),
+            "yield" => Some(KYield),
             _ => None
         }
     }
@@ -107,10 +122,12 @@ impl FromStr for Keyword {
 impl Show for Keyword {
     fn fmt(&self, f: &mut Formatter) -> Result {
         write!(f, "{}", match *self {
+            KAwait => "await",
             KBreak => "break",
             KCase => "case",
             KCatch => "catch",
             KClass => "class",
+            KConst => "const",
             KContinue => "continue",
             KDebugger => "debugger",
             KDefault => "default",
@@ -118,6 +135,7 @@ impl Show for Keyword {
             KDo => "do",
             KElse => "else",
             KEnum => "enum",
+            KExport => "export",
             KExtends => "extends",
             KFinally => "finally",
             KFor => "for",
@@ -126,6 +144,7 @@ impl Show for Keyword {
             KIn => "in",
             KInstanceOf => "instanceof",
             KImport => "import",
+            KLet => "let",
             KNew => "new",
             KReturn => "return",
             KSuper => "super",
@@ -137,7 +156,8 @@ impl Show for Keyword {
             KVar => "var",
             KVoid => "void",
             KWhile => "while",
-            KWith => "with"
+            KWith => "with",
+            KYield => "yield"
         })
     }
 }
\ No newline at end of file
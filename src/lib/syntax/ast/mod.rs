@@ -6,6 +6,8 @@ pub mod expr;
 pub mod keyword;
 /// Operators
 pub mod op;
+/// Destructuring patterns
+pub mod pattern;
 /// Positions
 pub mod pos;
 /// Punctuators
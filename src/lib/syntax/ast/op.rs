@@ -23,7 +23,9 @@ pub enum NumOp {
     /// `a * b` - Multiplication
     OpMul,
     /// `a % b` - Modulus
-    OpMod
+    OpMod,
+    /// `a ** b` - Exponentiation
+    OpExp
 }
 impl Show for NumOp {
     fn fmt(&self, f: &mut Formatter) -> Result {
@@ -32,7 +34,8 @@ impl Show for NumOp {
             OpSub => "-",
             OpDiv => "/",
             OpMul => "*",
-            OpMod => "%"
+            OpMod => "%",
+            OpExp => "**"
         })
     }
 }
@@ -52,7 +55,11 @@ pub enum UnaryOp {
     /// `+a` - convert to a number
     UnaryPlus,
     /// `!a` - get the opposite of the boolean value
-    UnaryNot
+    UnaryNot,
+    /// `delete a.b` - remove the field from the object
+    UnaryDelete,
+    /// `void a` - evaluate the value then give undefined
+    UnaryVoid
 }
 impl Show for UnaryOp {
     fn fmt(&self, f: &mut Formatter) -> Result {
@@ -61,7 +68,9 @@ impl Show for UnaryOp {
             UnaryDecrementPost | UnaryDecrementPre => "--",
             UnaryPlus => "+",
             UnaryMinus => "-",
-            UnaryNot => "!"
+            UnaryNot => "!",
+            UnaryDelete => "delete ",
+            UnaryVoid => "void "
         })
     }
 }
@@ -77,7 +86,9 @@ pub enum BitOp {
     /// `a << b` - Bit-shift leftwards
     BitShl,
     /// `a >> b` - Bit-shift rightrights
-    BitShr
+    BitShr,
+    /// `a >>> b` - Bit-shift rightwards, filling in zeroes
+    BitUShr
 }
 impl Show for BitOp {
     fn fmt(&self, f: &mut Formatter) -> Result {
@@ -86,7 +97,8 @@ impl Show for BitOp {
             BitOr => "|",
             BitXor => "^",
             BitShl => "<<",
-            BitShr => ">>"
+            BitShr => ">>",
+            BitUShr => ">>>"
         })
     }
 }
@@ -109,6 +121,10 @@ pub enum CompOp {
     CompLessThan,
     /// `a <= b` - If `a` is less than or equal to `b`
     CompLessThanOrEqual,
+    /// `a in b` - If the object `b` has a field called `a`
+    CompIn,
+    /// `a instanceof b` - If the constructor `b`'s prototype is in the prototype chain of `a`
+    CompInstanceOf
 }
 impl Show for CompOp {
     fn fmt(&self, f: &mut Formatter) -> Result {
@@ -120,7 +136,9 @@ impl Show for CompOp {
             CompGreaterThan => ">",
             CompGreaterThanOrEqual => ">=",
             CompLessThan => "<",
-            CompLessThanOrEqual => "<="
+            CompLessThanOrEqual => "<=",
+            CompIn => "in",
+            CompInstanceOf => "instanceof"
         })
     }
 }
@@ -158,10 +176,11 @@ impl Operator for BinOp {
     }
     fn get_precedence(&self) -> uint {
         match *self {
+            BinNum(OpExp) => 4,
             BinNum(OpMul) | BinNum(OpDiv) | BinNum(OpMod) => 5,
             BinNum(OpAdd) | BinNum(OpSub) => 6,
-            BinBit(BitShl) | BinBit(BitShr) => 7,
-            BinComp(CompLessThan) | BinComp(CompLessThanOrEqual) | BinComp(CompGreaterThan) | BinComp(CompGreaterThanOrEqual) => 8,
+            BinBit(BitShl) | BinBit(BitShr) | BinBit(BitUShr) => 7,
+            BinComp(CompLessThan) | BinComp(CompLessThanOrEqual) | BinComp(CompGreaterThan) | BinComp(CompGreaterThanOrEqual) | BinComp(CompIn) | BinComp(CompInstanceOf) => 8,
             BinComp(CompEqual) | BinComp(CompNotEqual) | BinComp(CompStrictEqual) | BinComp(CompStrictNotEqual) => 9,
             BinBit(BitAnd) => 10,
             BinBit(BitXor) => 11,
@@ -0,0 +1,192 @@
+use std::fmt::{Formatter, Result, Show};
+use syntax::ast::expr::Expr;
+#[deriving(Clone, PartialEq)]
+/// A pattern that a value is destructured into by a declaration, assignment or parameter
+pub enum Pattern {
+    /// Bind the value to a name
+    NamePattern(String),
+    /// Assign the value to a field, which can only be done by an assignment
+    ExprPattern(Box<Expr>),
+    /// Destructure the fields with the given keys into patterns, using the default values if they are
+    /// undefined, then put the remaining fields into an object bound to the rest pattern if there is one
+    ObjectPattern(Vec<(String, Pattern, Option<Expr>)>, Option<Box<Pattern>>),
+    /// Destructure the items of an array into patterns, skipping holes and using the default values
+    /// if they are undefined, then put the remaining items into an array bound to the rest pattern
+    ArrayPattern(Vec<Option<(Pattern, Option<Expr>)>>, Option<Box<Pattern>>)
+}
+impl Pattern {
+    /// Get the names this pattern binds
+    pub fn names(&self) -> Vec<String> {
+        let mut names = Vec::new();
+        self.push_names(&mut names);
+        names
+    }
+    fn push_names(&self, names:&mut Vec<String>) {
+        match *self {
+            NamePattern(ref name) => names.push(name.clone()),
+            ExprPattern(_) => (),
+            ObjectPattern(ref fields, ref rest) => {
+                for &(_, ref pattern, _) in fields.iter() {
+                    pattern.push_names(names);
+                }
+                match *rest {
+                    Some(box ref rest) => rest.push_names(names),
+                    None => ()
+                }
+            },
+            ArrayPattern(ref items, ref rest) => {
+                for item in items.iter() {
+                    match *item {
+                        Some((ref pattern, _)) => pattern.push_names(names),
+                        None => ()
+                    }
+                }
+                match *rest {
+                    Some(box ref rest) => rest.push_names(names),
+                    None => ()
+                }
+            }
+        }
+    }
+    /// Get the expressions inside this pattern, which are its default values and the fields it assigns to
+    pub fn exprs<'a>(&'a self) -> Vec<&'a Expr> {
+        let mut exprs = Vec::new();
+        self.push_exprs(&mut exprs);
+        exprs
+    }
+    fn push_exprs<'a>(&'a self, exprs:&mut Vec<&'a Expr>) {
+        match *self {
+            NamePattern(_) => (),
+            ExprPattern(box ref expr) => exprs.push(expr),
+            ObjectPattern(ref fields, ref rest) => {
+                for &(_, ref pattern, ref default) in fields.iter() {
+                    pattern.push_exprs(exprs);
+                    exprs.extend(default.iter());
+                }
+                match *rest {
+                    Some(box ref rest) => rest.push_exprs(exprs),
+                    None => ()
+                }
+            },
+            ArrayPattern(ref items, ref rest) => {
+                for item in items.iter() {
+                    match *item {
+                        Some((ref pattern, ref default)) => {
+                            pattern.push_exprs(exprs);
+                            exprs.extend(default.iter());
+                        },
+                        None => ()
+                    }
+                }
+                match *rest {
+                    Some(box ref rest) => rest.push_exprs(exprs),
+                    None => ()
+                }
+            }
+        }
+    }
+}
+#[deriving(Clone, PartialEq)]
+/// The parameters of a function, which destructure the arguments it's called with
+pub struct Params {
+    /// The pattern of each argument and its default value for when it's undefined
+    pub args: Vec<(Pattern, Option<Expr>)>,
+    /// The pattern the remaining arguments are put into as an array
+    pub rest: Option<Box<Pattern>>
+}
+impl Params {
+    /// Make parameters that bind each argument to a name
+    pub fn from_names(names: Vec<String>) -> Params {
+        Params {
+            args: names.move_iter().map(|name| (NamePattern(name), None)).collect(),
+            rest: None
+        }
+    }
+    /// Get the names these parameters bind
+    pub fn names(&self) -> Vec<String> {
+        let mut names = Vec::new();
+        for &(ref pattern, _) in self.args.iter() {
+            pattern.push_names(&mut names);
+        }
+        match self.rest {
+            Some(box ref rest) => rest.push_names(&mut names),
+            None => ()
+        }
+        names
+    }
+    /// Get the expressions inside these parameters, which are their default values and the ones inside their patterns
+    pub fn exprs<'a>(&'a self) -> Vec<&'a Expr> {
+        let mut exprs = Vec::new();
+        for &(ref pattern, ref default) in self.args.iter() {
+            pattern.push_exprs(&mut exprs);
+            exprs.extend(default.iter());
+        }
+        match self.rest {
+            Some(box ref rest) => rest.push_exprs(&mut exprs),
+            None => ()
+        }
+        exprs
+    }
+}
+impl Show for Params {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        for (i, &(ref pattern, ref default)) in self.args.iter().enumerate() {
+            if i > 0 {
+                try!(write!(f, ", "));
+            }
+            try!(write!(f, "{}", pattern));
+            match *default {
+                Some(ref default) => try!(write!(f, " = {}", default)),
+                None => ()
+            }
+        }
+        match self.rest {
+            Some(ref rest) => write!(f, "{}...{}", if self.args.is_empty() {""} else {", "}, rest),
+            None => Ok(())
+        }
+    }
+}
+impl Show for Pattern {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        match *self {
+            NamePattern(ref name) => write!(f, "{}", name),
+            ExprPattern(ref expr) => write!(f, "{}", expr),
+            ObjectPattern(ref fields, ref rest) => {
+                try!(write!(f, "{}", "{"));
+                for (i, &(ref key, ref pattern, ref default)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        try!(write!(f, ", "));
+                    }
+                    try!(write!(f, "{}: {}", key, pattern));
+                    match *default {
+                        Some(ref default) => try!(write!(f, " = {}", default)),
+                        None => ()
+                    }
+                }
+                match *rest {
+                    Some(ref rest) => try!(write!(f, "{}...{}", if fields.is_empty() {""} else {", "}, rest)),
+                    None => ()
+                }
+                write!(f, "{}", "}")
+            },
+            ArrayPattern(ref items, ref rest) => {
+                try!(write!(f, "["));
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        try!(write!(f, ", "));
+                    }
+                    match *item {
+                        Some((ref pattern, Some(ref default))) => try!(write!(f, "{} = {}", pattern, default)),
+                        Some((ref pattern, None)) => try!(write!(f, "{}", pattern)),
+                        None => ()
+                    }
+                }
+                match *rest {
+                    Some(ref rest) => try!(write!(f, "{}...{}", if items.is_empty() {""} else {", "}, rest)),
+                    None => ()
+                }
+                write!(f, "]")
+            }
+        }
+    }
+}
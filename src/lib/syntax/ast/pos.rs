@@ -4,14 +4,17 @@ pub struct Position {
     /// The column number
     pub column_number : uint,
     /// The line number
-    pub line_number : uint
+    pub line_number : uint,
+    /// The number of bytes into the script
+    pub offset : uint
 }
 impl Position {
     /// Create a new position
-    pub fn new(line_number: uint, column_number: uint) -> Position {
+    pub fn new(line_number: uint, column_number: uint, offset: uint) -> Position {
         Position {
             line_number: line_number,
-            column_number: column_number
+            column_number: column_number,
+            offset: offset
         }
     }
-}
\ No newline at end of file
+}
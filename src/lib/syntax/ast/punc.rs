@@ -46,6 +46,8 @@ pub enum Punctuator {
     PDiv,
     /// `%`
     PMod,
+    /// `**`
+    PExp,
     /// `++`
     PInc,
     /// `--`
@@ -86,6 +88,8 @@ pub enum Punctuator {
     PAssignDiv,
     /// `%=`
     PAssignMod,
+    /// `**=`
+    PAssignExp,
     /// `<<=`
     PAssignLeftSh,
     /// `>>=`
@@ -99,7 +103,9 @@ pub enum Punctuator {
     /// `^=`
     PAssignXor,
     /// `=>`
-    PArrow
+    PArrow,
+    /// `...`
+    PSpread
 }
 impl Show for Punctuator {
     fn fmt(&self, f: &mut Formatter) -> Result {
@@ -126,6 +132,7 @@ impl Show for Punctuator {
             PMul => "*",
             PDiv => "/",
             PMod => "%",
+            PExp => "**",
             PInc => "++",
             PDec => "--",
             PLeftSh => "<<",
@@ -146,13 +153,15 @@ impl Show for Punctuator {
             PAssignMul => "*=",
             PAssignDiv => "/=",
             PAssignMod => "%=",
+            PAssignExp => "**=",
             PAssignLeftSh => "<<=",
             PAssignRightSh => ">>=",
             PAssignURightSh => ">>>=",
             PAssignAnd => "&=",
             PAssignOr => "|=",
             PAssignXor => "^=",
-            PArrow => "=>"
+            PArrow => "=>",
+            PSpread => "..."
         })
     }
 }
\ No newline at end of file
@@ -7,15 +7,26 @@ use syntax::ast::keyword::Keyword;
 pub struct Token {
     /// The token
     pub data : TokenData,
-    /// The token's position
-    pub pos : Position
+    /// The position of the token's first character
+    pub pos : Position,
+    /// The position just after the token's last character
+    pub end : Position,
+    /// Whether there's a line terminator between this token and the last one that isn't a comment,
+    /// which decides where semicolons are inserted
+    pub newline_before : bool,
+    /// Whether this is a number written with a leading zero such as `017`, which isn't allowed in
+    /// strict mode code
+    pub legacy_octal : bool
 }
 impl Token {
-    /// Create a new detailed token from the token data, line number and column number
-    pub fn new(data: TokenData, line_number: uint, column_number: uint) -> Token {
+    /// Create a new detailed token from the token data and the positions it starts and ends at
+    pub fn new(data: TokenData, pos: Position, end: Position) -> Token {
         Token {
             data: data,
-            pos: Position::new(line_number, column_number)
+            pos: pos,
+            end: end,
+            newline_before: false,
+            legacy_octal: false
         }
     }
 }
@@ -39,12 +50,22 @@ pub enum TokenData {
     TNullLiteral,
     /// A numeric literal
     TNumericLiteral(f64),
+    /// A BigInt literal, as the digits it was written with and their prefix
+    TBigIntLiteral(String),
     /// A piece of punctuation
     TPunctuator(Punctuator),
     /// A string literal
     TStringLiteral(String),
-    /// A regular expression
-    TRegularExpression(String),
+    /// A regular expression and its flags
+    TRegularExpression(String, String),
+    /// A whole template literal without substitutions, as its cooked and raw strings
+    TNoSubstitutionTemplate(String, String),
+    /// The start of a template literal up to its first substitution, as its cooked and raw strings
+    TTemplateHead(String, String),
+    /// The part of a template literal between two substitutions, as its cooked and raw strings
+    TTemplateMiddle(String, String),
+    /// The end of a template literal after its last substitution, as its cooked and raw strings
+    TTemplateTail(String, String),
     /// A comment
     TComment(String)
 }
@@ -57,9 +78,14 @@ impl Show for TokenData {
             TKeyword(word) => write!(f, "{}", word),
             TNullLiteral => write!(f, "null"),
             TNumericLiteral(num) => write!(f, "{}", num),
+            TBigIntLiteral(digits) => write!(f, "{}n", digits),
             TPunctuator(punc) => write!(f, "{}", punc),
             TStringLiteral(lit) => write!(f, "{}", lit),
-            TRegularExpression(reg) => write!(f, "{}", reg),
+            TRegularExpression(reg, flags) => write!(f, "/{}/{}", reg, flags),
+            TNoSubstitutionTemplate(_, raw) => write!(f, "`{}`", raw),
+            TTemplateHead(_, raw) => write!(f, "`{}${}", raw, "{"),
+            TTemplateMiddle(_, raw) => write!(f, "{}{}${}", "}", raw, "{"),
+            TTemplateTail(_, raw) => write!(f, "{}{}`", "}", raw),
             TComment(comm) => write!(f, "/*{}*/", comm)
         }
     }
@@ -10,7 +10,9 @@ pub fn resolve_type(expr:&Expr) -> Type {
             IntegerType,
         ConstExpr(CNum(_)) =>
             NumberType,
-        ConstExpr(CRegExp(_, _, _)) =>
+        ConstExpr(CBigInt(_)) =>
+            BigIntType,
+        ConstExpr(CRegExp(_, _)) =>
             NativeObjectType,
         ConstExpr(CBool(_)) =>
             BooleanType,
@@ -18,7 +20,7 @@ pub fn resolve_type(expr:&Expr) -> Type {
             NullType,
         ConstExpr(CUndefined) =>
             UndefinedType,
-        ConstExpr(CString(_)) =>
+        ConstExpr(CString(_)) | DirectiveExpr(_) =>
             StringType,
         ObjectDeclExpr(_) =>
             ObjectType,
@@ -35,14 +37,19 @@ pub fn resolve_type(expr:&Expr) -> Type {
         },
         BinOpExpr(BinNum(_), _, _) =>
             NumberType,
+        // an unsigned shift can give a number too big for an integer
+        BinOpExpr(BinBit(BitUShr), _, _) =>
+            NumberType,
         BinOpExpr(BinBit(_), _, _) =>
             IntegerType,
         BinOpExpr(BinComp(_), _, _) =>
             BooleanType,
         BinOpExpr(BinLog(_), _, _) =>
             BooleanType,
-        UnaryOpExpr(UnaryNot, _) =>
+        UnaryOpExpr(UnaryNot, _) | UnaryOpExpr(UnaryDelete, _) =>
             BooleanType,
+        UnaryOpExpr(UnaryVoid, _) =>
+            UndefinedType,
         UnaryOpExpr(UnaryPlus, box ref inner) | UnaryOpExpr(UnaryMinus, box ref inner) =>
             resolve_type(inner),
         UnaryOpExpr(_, _) =>
@@ -57,9 +64,13 @@ pub fn resolve_type(expr:&Expr) -> Type {
             AnyType,
         CallExpr(_, _) =>
             AnyType,
-        WhileLoopExpr(_, _) =>
+        WhileLoopExpr(_, _) | DoWhileLoopExpr(_, _) | ForLoopExpr(_, _, _, _) | ForInLoopExpr(_, _, _) =>
             UndefinedType,
-        IfExpr(_, box ref if_expr, Some(box ref else_expr)) => {
+        BreakExpr(_) | ContinueExpr(_) =>
+            UndefinedType,
+        LabelledExpr(_, box ref expr) =>
+            resolve_type(expr),
+        IfExpr(_, box ref if_expr, Some(box ref else_expr)) | ConditionalExpr(_, box ref if_expr, box ref else_expr) => {
             let if_type = resolve_type(if_expr);
             let else_type = resolve_type(else_expr);
             if if_type == else_type {
@@ -85,7 +96,8 @@ pub fn resolve_type(expr:&Expr) -> Type {
             types.push(resolve_type(def));
             AnyOfType(types)
         },
-        FunctionDeclExpr(_, _, _) | ArrowFunctionDeclExpr(_, _) =>
+        FunctionDeclExpr(_, _, _) | GeneratorDeclExpr(_, _, _) | AsyncFunctionDeclExpr(_, _, _) | ArrowFunctionDeclExpr(_, _) |
+        AsyncArrowFunctionDeclExpr(_, _) | ClassDeclExpr(_, _, _, _) =>
             FunctionType,
         ConstructExpr(_, _) =>
             ObjectType,
@@ -93,11 +105,22 @@ pub fn resolve_type(expr:&Expr) -> Type {
             UndefinedType,
         ThrowExpr(_) =>
             UndefinedType,
+        TryExpr(_, _, _) =>
+            UndefinedType,
         AssignExpr(_, box ref what) =>
             resolve_type(what),
-        VarDeclExpr(_) =>
+        // adding to a target of an unknown type could give a string or a number
+        AssignOpExpr(BinNum(OpAdd), _, _) =>
+            AnyType,
+        AssignOpExpr(BinNum(_), _, _) | AssignOpExpr(BinBit(BitUShr), _, _) =>
+            NumberType,
+        AssignOpExpr(_, _, _) =>
+            IntegerType,
+        VarDeclExpr(_, _) | ImportExpr(_, _) | ExportExpr(_) =>
             UndefinedType,
-        TypeOfExpr(_) =>
-            StringType
+        TypeOfExpr(_) | TemplateExpr(None, _, _) =>
+            StringType,
+        TemplateExpr(Some(_), _, _) | ThisExpr | SuperExpr | YieldExpr(_) | DelegateYieldExpr(_) | AwaitExpr(_) | ErrorExpr(_) =>
+            AnyType
     }
 }
\ No newline at end of file
@@ -0,0 +1,232 @@
+use syntax::ast::expr::Expr;
+use syntax::ast::pos::Position;
+use syntax::ast::token::{Token, TComment};
+use syntax::lexer::Lexer;
+use syntax::parser::Parser;
+use syntax::error::{SyntaxError, LexFailure};
+use std::mem::replace;
+#[deriving(Clone, PartialEq, Show)]
+/// The kind of a piece of trivia
+pub enum TriviaKind {
+    /// A run of whitespace on one line
+    WhitespaceTrivia,
+    /// A line terminator, where `\r\n` is a single one
+    NewlineTrivia,
+    /// A `//` comment
+    LineCommentTrivia,
+    /// A `/* */` comment
+    BlockCommentTrivia
+}
+#[deriving(Clone, PartialEq, Show)]
+/// A piece of a script that doesn't change what it means, such as whitespace and comments
+pub struct Trivia {
+    /// What kind of trivia this is
+    pub kind : TriviaKind,
+    /// The exact text of the trivia in the script, including the delimiters of a comment
+    pub text : String
+}
+impl Trivia {
+    /// Returns true if this is a comment
+    pub fn is_comment(&self) -> bool {
+        self.kind == LineCommentTrivia || self.kind == BlockCommentTrivia
+    }
+    /// Get the text of a comment without its delimiters, or `None` if this isn't a comment
+    pub fn comment<'a>(&'a self) -> Option<&'a str> {
+        let text = self.text.as_slice();
+        match self.kind {
+            LineCommentTrivia => Some(text.slice_from(2).trim_right_chars(|ch:char| ch == '\u2028' || ch == '\u2029')),
+            BlockCommentTrivia => Some(text.slice(2, text.len() - 2)),
+            _ => None
+        }
+    }
+}
+#[deriving(Clone, PartialEq, Show)]
+/// A token with the exact text it was read from and the trivia before it
+pub struct SourceToken {
+    /// The token
+    pub token : Token,
+    /// The exact text of the token in the script
+    pub text : String,
+    /// The whitespace and comments between the token before this one and this one
+    pub leading : Vec<Trivia>
+}
+/// A parsed script that keeps all of its source, so the comments before each expression can be
+/// found and the script can be emitted again exactly as it was written
+///
+/// The trivia of an expression is the trivia before the first token of its span
+pub struct SyntaxTree {
+    /// The tokens of the script other than comments, with their text and trivia
+    pub tokens : Vec<SourceToken>,
+    /// The whitespace and comments after the last token
+    pub trailing : Vec<Trivia>,
+    /// The parsed script
+    pub script : Expr
+}
+impl SyntaxTree {
+    /// Lex and parse a script, keeping its comments and whitespace
+    pub fn parse(source:&str) -> Result<SyntaxTree, SyntaxError> {
+        SyntaxTree::parse_as(source, false)
+    }
+    /// Lex and parse a module, keeping its comments and whitespace
+    pub fn parse_module(source:&str) -> Result<SyntaxTree, SyntaxError> {
+        SyntaxTree::parse_as(source, true)
+    }
+    fn parse_as(source:&str, module:bool) -> Result<SyntaxTree, SyntaxError> {
+        let tokens = match Lexer::lex_str(source) {
+            Ok(tokens) => tokens,
+            Err(err) => return Err(LexFailure(err))
+        };
+        let mut source_tokens = Vec::new();
+        let mut trivia = Vec::new();
+        let mut offset = 0;
+        for tk in tokens.iter() {
+            split_whitespace(source.slice(offset, tk.pos.offset), &mut trivia);
+            let text = source.slice(tk.pos.offset, tk.end.offset).into_string();
+            offset = tk.end.offset;
+            match tk.data {
+                TComment(_) => trivia.push(Trivia {
+                    kind: if text.as_slice().starts_with("//") {LineCommentTrivia} else {BlockCommentTrivia},
+                    text: text
+                }),
+                _ => source_tokens.push(SourceToken {
+                    token: tk.clone(),
+                    text: text,
+                    leading: replace(&mut trivia, Vec::new())
+                })
+            }
+        }
+        split_whitespace(source.slice_from(offset), &mut trivia);
+        let end = tokens.last().map(|token| token.end).unwrap_or(Position::new(1, 1, 0));
+        let mut parser = Parser::new(tokens);
+        let parsed = if module {parser.parse_module()} else {parser.parse_all()};
+        let script = match parsed {
+            Ok(script) => script,
+            Err(err) => return Err(SyntaxError::from_parse(err, end))
+        };
+        Ok(SyntaxTree {
+            tokens: source_tokens,
+            trailing: trivia,
+            script: script
+        })
+    }
+    /// Find the index of the token starting at a position
+    fn token_index(&self, pos:Position) -> Option<uint> {
+        self.tokens.as_slice().bsearch(|tk| tk.token.pos.offset.cmp(&pos.offset))
+    }
+    /// Get the whitespace and comments before an expression
+    pub fn leading_trivia<'a>(&'a self, expr:&Expr) -> &'a [Trivia] {
+        match self.token_index(expr.start) {
+            Some(index) => self.tokens[index].leading.as_slice(),
+            None => &[]
+        }
+    }
+    /// Get the comments before an expression
+    pub fn comments<'a>(&'a self, expr:&Expr) -> Vec<&'a Trivia> {
+        self.leading_trivia(expr).iter().filter(|trivia| trivia.is_comment()).collect()
+    }
+    /// Get the JSDoc comment of an expression, which is the last comment before it if that
+    /// comment starts with `/**`
+    pub fn doc_comment<'a>(&'a self, expr:&Expr) -> Option<&'a Trivia> {
+        match self.comments(expr).last() {
+            Some(&comment) if comment.text.as_slice().starts_with("/**") && comment.text.len() > 4 => Some(comment),
+            _ => None
+        }
+    }
+    /// Emit the exact source of an expression, including the trivia inside it
+    pub fn source_of(&self, expr:&Expr) -> String {
+        let mut source = String::new();
+        let start = match self.token_index(expr.start) {
+            Some(index) => index,
+            None => return source
+        };
+        for (i, tk) in self.tokens.slice_from(start).iter().enumerate() {
+            if tk.token.pos.offset >= expr.end.offset {
+                break;
+            }
+            if i > 0 {
+                for trivia in tk.leading.iter() {
+                    source.push_str(trivia.text.as_slice());
+                }
+            }
+            source.push_str(tk.text.as_slice());
+        }
+        source
+    }
+    /// Emit the exact source of the whole script
+    pub fn to_source(&self) -> String {
+        let mut source = String::new();
+        for tk in self.tokens.iter() {
+            for trivia in tk.leading.iter() {
+                source.push_str(trivia.text.as_slice());
+            }
+            source.push_str(tk.text.as_slice());
+        }
+        for trivia in self.trailing.iter() {
+            source.push_str(trivia.text.as_slice());
+        }
+        source
+    }
+}
+/// Split the whitespace between two tokens or comments into trivia, adding it onto `trivia`
+fn split_whitespace(text:&str, trivia:&mut Vec<Trivia>) {
+    for ch in text.chars() {
+        let kind = match ch {
+            '\n' | '\r' | '\u2028' | '\u2029' => NewlineTrivia,
+            _ => WhitespaceTrivia
+        };
+        // a run of whitespace is one piece of trivia, as is `\r\n`
+        let joins = match trivia.last() {
+            Some(last) if last.kind == kind => kind == WhitespaceTrivia || (last.text.as_slice() == "\r" && ch == '\n'),
+            _ => false
+        };
+        if joins {
+            trivia.mut_last().unwrap().text.push_char(ch);
+        } else {
+            trivia.push(Trivia {kind: kind, text: ch.to_string()});
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use syntax::ast::expr::{Expr, BlockExpr};
+    use super::SyntaxTree;
+    /// Check that a script is emitted again exactly as it was written
+    fn round_trip(source:&str) {
+        let tree = SyntaxTree::parse(source).unwrap();
+        assert_eq!(tree.to_source(), source.into_string());
+    }
+    /// Get the statements of a script
+    fn statements(tree:&SyntaxTree) -> Vec<Expr> {
+        match tree.script.def {
+            BlockExpr(ref exprs) => exprs.clone(),
+            _ => fail!("A script should parse to a block")
+        }
+    }
+    #[test]
+    fn round_trip_crlf() {
+        round_trip("var a = 1;\r\nvar b = 2;\r\n");
+    }
+    #[test]
+    fn round_trip_line_separator() {
+        round_trip("var a = 1;\u2028var b = 2;");
+    }
+    #[test]
+    fn round_trip_comments() {
+        round_trip("// line\n/* block */ var a = 1; /** doc\n * more\n */\nfunction f() {}\n");
+    }
+    #[test]
+    fn round_trip_trailing_trivia() {
+        round_trip("a;  // done\n\n  ");
+    }
+    #[test]
+    fn round_trip_template() {
+        round_trip("var t = `x\r\n${a + `y`}z`;");
+    }
+    #[test]
+    fn doc_comment() {
+        let tree = SyntaxTree::parse("/** Adds two numbers */\nfunction add(a, b) {\n    return a + b;\n}\n/* not a doc comment */\nvar total = add(1, 2);").unwrap();
+        let statements = statements(&tree);
+        assert_eq!(tree.doc_comment(&statements[0]).map(|trivia| trivia.text.clone()), Some("/** Adds two numbers */".into_string()));
+        assert!(tree.doc_comment(&statements[1]).is_none());
+    }
+}
@@ -0,0 +1,127 @@
+use syntax::ast::expr::Expr;
+use syntax::ast::pos::Position;
+use syntax::lexer::{Lexer, LexError};
+use syntax::parser::{Parser, ParseError, AbruptEnd};
+use std::fmt;
+use std::io::{BufReader, BufferedReader};
+#[deriving(Clone, PartialEq)]
+/// An error in the syntax of a script, found while either lexing or parsing it
+pub enum SyntaxError {
+    /// When the script couldn't be split into tokens
+    LexFailure(LexError),
+    /// When the tokens couldn't be parsed, and where in the script that happened
+    ParseFailure(ParseError, Position)
+}
+impl SyntaxError {
+    /// Make a syntax error from a parse error, using `end` as the position of an abrupt end
+    pub fn from_parse(err:ParseError, end:Position) -> SyntaxError {
+        let pos = err.pos().unwrap_or(end);
+        ParseFailure(err, pos)
+    }
+    /// Get the position in the script where this error happened
+    pub fn pos(&self) -> Position {
+        match *self {
+            LexFailure(ref err) => err.pos(),
+            ParseFailure(_, pos) => pos
+        }
+    }
+    /// Get the line number where this error happened
+    pub fn line_number(&self) -> uint {
+        self.pos().line_number
+    }
+    /// Get the column number where this error happened
+    pub fn column_number(&self) -> uint {
+        self.pos().column_number
+    }
+}
+impl fmt::Show for SyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            LexFailure(ref err) => write!(f, "SyntaxError: {}", err),
+            ParseFailure(AbruptEnd, pos) => write!(f, "SyntaxError: {}:{}: Abrupt end", pos.line_number, pos.column_number),
+            ParseFailure(ref err, _) => write!(f, "SyntaxError: {}", err)
+        }
+    }
+}
+#[deriving(Clone, PartialEq)]
+/// How serious a problem found in a script is
+pub enum Severity {
+    /// A problem that stops the script from running
+    ErrorSeverity,
+    /// A problem that doesn't stop the script from running, but is probably a mistake
+    WarningSeverity
+}
+impl fmt::Show for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", match *self {
+            ErrorSeverity => "error",
+            WarningSeverity => "warning"
+        })
+    }
+}
+#[deriving(Clone, PartialEq)]
+/// A problem found in a script, with the part of the script it's about
+pub struct Diagnostic {
+    /// How serious the problem is
+    pub severity : Severity,
+    /// Where the part of the script the problem is about starts
+    pub start : Position,
+    /// Where the part of the script the problem is about ends
+    pub end : Position,
+    /// A description of the problem
+    pub message : String
+}
+impl Diagnostic {
+    /// Make an error diagnostic from a lex error
+    pub fn from_lex(err:&LexError) -> Diagnostic {
+        Diagnostic {
+            severity: ErrorSeverity,
+            start: err.pos(),
+            end: err.pos(),
+            message: err.message()
+        }
+    }
+    /// Make an error diagnostic from a parse error, using `end` as the position of an abrupt end
+    pub fn from_parse(err:&ParseError, end:Position) -> Diagnostic {
+        let (start, end) = err.span().unwrap_or((end, end));
+        Diagnostic {
+            severity: ErrorSeverity,
+            start: start,
+            end: end,
+            message: err.message()
+        }
+    }
+}
+impl fmt::Show for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}: {}: {}", self.start.line_number, self.start.column_number, self.severity, self.message)
+    }
+}
+/// Lex and parse a script into an expression
+pub fn parse_str(script:&str) -> Result<Expr, SyntaxError> {
+    let tokens = match Lexer::lex_str(script) {
+        Ok(tokens) => tokens,
+        Err(err) => return Err(LexFailure(err))
+    };
+    let end = match tokens.last() {
+        Some(token) => token.end,
+        None => Position::new(1, 1, 0)
+    };
+    Parser::new(tokens).parse_all().map_err(|err| SyntaxError::from_parse(err, end))
+}
+/// Lex and parse a script, carrying on past the statements that can't be parsed, which gives the
+/// script with an `ErrorExpr` in place of each of them and a diagnostic for every error found
+///
+/// The first diagnostic is the error that `parse_str` gives, if there are any
+pub fn parse_str_recovering(script:&str) -> (Expr, Vec<Diagnostic>) {
+    let reader = BufReader::new(script.as_bytes());
+    let mut lexer = Lexer::new(BufferedReader::new(reader));
+    // the tokens before a lex error are still parsed
+    let lexed = lexer.lex();
+    let (expr, mut diagnostics) = Parser::new(lexer.tokens).parse_all_recovering();
+    match lexed {
+        Ok(()) => (),
+        Err(err) => diagnostics.insert(0, Diagnostic::from_lex(&err))
+    }
+    (expr, diagnostics)
+}
@@ -1,6 +1,10 @@
 use syntax::ast::punc::*;
 use syntax::ast::token::*;
-use std::io::{BufReader, BufferedReader, Buffer, IoError, IoResult, EndOfFile};
+use syntax::ast::keyword::{KThis, KSuper};
+use syntax::ast::pos::Position;
+use syntax::regexp::RegExp;
+use std::fmt;
+use std::io::{BufReader, BufferedReader, Buffer, IoError, EndOfFile};
 use std::char::from_u32;
 use std::num::from_str_radix;
 use std::from_str::FromStr;
@@ -8,8 +12,8 @@ macro_rules! vop(
     ($this:ident, $assign_op:expr, $op:expr) => ({
         let preview = try!($this.preview_next());
         match preview {
-            '=' => {
-                $this.buffer.consume(1);
+            Some('=') => {
+                try!($this.next());
                 $assign_op
             },
             _ => $op
@@ -18,18 +22,24 @@ macro_rules! vop(
     ($this:ident, $assign_op:expr, $op:expr, {$($case:pat => $block:expr),+}) => ({
         let preview = try!($this.preview_next());
         match preview {
-            '=' => {
-                $this.buffer.consume(1);
+            Some('=') => {
+                try!($this.next());
                 $assign_op
             },
-            $($case => $block)+,
+            $(Some($case) => {
+                try!($this.next());
+                $block
+            })+,
             _ => $op
         }
     });
     ($this:ident, $op:expr, {$($case:pat => $block:expr),+}) => ({
         let preview = try!($this.preview_next());
         match preview {
-            $($case => $block)+,
+            $(Some($case) => {
+                try!($this.next());
+                $block
+            })+,
             _ => $op
         }
     });
@@ -45,17 +55,81 @@ macro_rules! op(
     });
     ($this:ident, $op:expr, {$($case:pat => $block:expr),+}) => ({
         let punc = vop!($this, $op, {$($case => $block),+});
-        $this.push_punc();
+        $this.push_punc(punc);
     });
 )
+#[deriving(Clone, PartialEq)]
+/// An error encountered while lexing a script
+pub enum LexError {
+    /// When a character can't start any token
+    UnexpectedChar(char, Position),
+    /// When a string contains an escape sequence that doesn't exist
+    InvalidEscape(char, Position),
+    /// When an escape sequence names a number that isn't a unicode scalar value
+    InvalidCodePoint(u32, Position),
+    /// When a numeric literal can't be read as a number
+    InvalidNumber(String, Position),
+    /// When a regular expression isn't closed before the end of its line
+    UnterminatedRegExp(Position),
+    /// When a regular expression has an unknown or repeated flag
+    InvalidRegExpFlag(char, Position),
+    /// When the pattern of a regular expression can't be compiled, and why
+    InvalidRegExp(String, Position),
+    /// When the script ends in the middle of a token
+    UnexpectedEnd(Position),
+    /// When the underlying reader fails
+    ReadFailure(IoError, Position)
+}
+impl LexError {
+    /// Get the position in the script where this error happened
+    pub fn pos(&self) -> Position {
+        match *self {
+            UnexpectedChar(_, pos) | InvalidEscape(_, pos) | InvalidCodePoint(_, pos) |
+            InvalidNumber(_, pos) | UnterminatedRegExp(pos) | InvalidRegExpFlag(_, pos) |
+            InvalidRegExp(_, pos) | UnexpectedEnd(pos) | ReadFailure(_, pos) => pos
+        }
+    }
+    /// Describe this error without its position
+    pub fn message(&self) -> String {
+        match *self {
+            UnexpectedChar(ch, _) => format!("Unexpected '{}'", ch),
+            InvalidEscape(ch, _) => format!("Invalid escape `{}`", ch),
+            InvalidCodePoint(num, _) => format!("{} is not a valid unicode scalar value", num),
+            InvalidNumber(ref num, _) => format!("{} is not a valid number", num),
+            UnterminatedRegExp(_) => "Unterminated regular expression".into_string(),
+            InvalidRegExpFlag(ch, _) => format!("Invalid regular expression flag `{}`", ch),
+            InvalidRegExp(ref why, _) => format!("Invalid regular expression: {}", why),
+            UnexpectedEnd(_) => "Unexpected end of script".into_string(),
+            ReadFailure(ref err, _) => format!("Failed to read script: {}", err)
+        }
+    }
+}
+impl fmt::Show for LexError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let pos = self.pos();
+        write!(f, "{}:{}: {}", pos.line_number, pos.column_number, self.message())
+    }
+}
+pub type LexResult = Result<(), LexError>;
 /// A Javascript lexer
 pub struct Lexer<B> {
     /// The list of tokens generated so far
     pub tokens : Vec<Token>,
-    /// The current line number in the script
+    /// The line number of the next character
     line_number : uint,
-    /// The current column number in the script
+    /// The column number of the next character
     column_number : uint,
+    /// The byte offset of the next character
+    offset : uint,
+    /// Whether the last character read was a `\r`, so a `\n` straight after it doesn't start
+    /// another line
+    after_cr : bool,
+    /// Where the token being lexed starts
+    start : Position,
+    /// Whether each unclosed `{` opened a template substitution rather than a block
+    braces : Vec<bool>,
+    /// Whether a line terminator has been passed since the last token that isn't a comment
+    newline : bool,
     /// The reader
     buffer: B
 }
@@ -65,51 +139,328 @@ impl<B:Buffer> Lexer<B> {
         Lexer {
             tokens: Vec::new(),
             line_number: 1,
-            column_number: 0,
+            column_number: 1,
+            offset: 0,
+            after_cr: false,
+            start: Position::new(1, 1, 0),
+            braces: Vec::new(),
+            newline: false,
             buffer: buffer
         }
     }
     #[inline(always)]
     fn push_token(&mut self, tk:TokenData) {
-        self.tokens.push(Token::new(tk, self.line_number, self.column_number))
+        let mut token = Token::new(tk, self.start, self.pos());
+        token.newline_before = self.newline;
+        match token.data {
+            TComment(_) => (),
+            _ => self.newline = false
+        }
+        self.tokens.push(token)
     }
     #[inline(always)]
     fn push_punc(&mut self, punc:Punctuator) {
         self.push_token(TPunctuator(punc));
     }
     /// Processes an input stream from a string into an array of tokens
-    pub fn lex_str(script:&str) -> Vec<Token> {
+    pub fn lex_str(script:&str) -> Result<Vec<Token>, LexError> {
         let script_bytes:&[u8] = script.as_bytes();
         let reader = BufReader::new(script_bytes);
         let buf_reader = BufferedReader::new(reader);
         let mut lexer = Lexer::new(buf_reader);
-        lexer.lex().unwrap();
-        lexer.tokens
+        try!(lexer.lex());
+        Ok(lexer.tokens)
+    }
+    #[inline(always)]
+    fn pos(&self) -> Position {
+        Position::new(self.line_number, self.column_number, self.offset)
+    }
+    /// Move the position past a character that has just been read
+    fn advance(&mut self, ch:char) {
+        self.offset += ch.len_utf8_bytes();
+        match ch {
+            '\n' if self.after_cr => (),
+            '\n' | '\r' | '\u2028' | '\u2029' => {
+                self.line_number += 1;
+                self.column_number = 1;
+            },
+            _ => self.column_number += 1
+        }
+        self.after_cr = ch == '\r';
+    }
+    fn read_failure(&self, err:IoError) -> LexError {
+        match err.kind {
+            EndOfFile => UnexpectedEnd(self.pos()),
+            _ => ReadFailure(err, self.pos())
+        }
     }
     #[inline(always)]
-    fn next(&mut self) -> IoResult<char> {
-        self.buffer.read_char()
+    fn next(&mut self) -> Result<char, LexError> {
+        match self.buffer.read_char() {
+            Ok(ch) => {
+                self.advance(ch);
+                Ok(ch)
+            },
+            Err(err) => Err(self.read_failure(err))
+        }
     }
-    fn preview_next(&mut self) -> IoResult<char> {
-        let buf = try!(self.buffer.fill_buf());
-        Ok(buf[0] as char)
+    /// Look at the next character without consuming it, which is `None` at the end of the script
+    fn preview_next(&mut self) -> Result<Option<char>, LexError> {
+        let pos = self.pos();
+        match self.buffer.fill_buf() {
+            Ok(buf) => Ok(Some(buf[0] as char)),
+            Err(IoError {kind: EndOfFile, ..}) => Ok(None),
+            Err(err) => Err(ReadFailure(err, pos))
+        }
     }
-    fn next_is(&mut self, peek:char) -> IoResult<bool> {
-        let result = try!(self.preview_next()) == peek;
+    fn next_is(&mut self, peek:char) -> Result<bool, LexError> {
+        let result = try!(self.preview_next()) == Some(peek);
         if result {
-            self.buffer.consume(1);
+            try!(self.next());
         }
         Ok(result)
     }
+    /// Returns true if a `/` at this point starts a regular expression rather than a division,
+    /// which is decided by the last token that wasn't a comment
+    fn regex_allowed(&self) -> bool {
+        let last = self.tokens.iter().rev().find(|tk| match tk.data {
+            TComment(_) => false,
+            _ => true
+        });
+        match last {
+            None => true,
+            Some(tk) => match tk.data {
+                TIdentifier(_) | TNumericLiteral(_) | TBigIntLiteral(_) | TStringLiteral(_) | TBooleanLiteral(_) | TNullLiteral | TRegularExpression(_, _) => false,
+                TNoSubstitutionTemplate(_, _) | TTemplateTail(_, _) => false,
+                TKeyword(KThis) | TKeyword(KSuper) => false,
+                TPunctuator(PCloseParen) | TPunctuator(PCloseBracket) | TPunctuator(PInc) | TPunctuator(PDec) => false,
+                _ => true
+            }
+        }
+    }
+    /// Read the rest of an escape sequence after its `\\`, pushing the characters it is written
+    /// with onto `raw`, which gives the character it stands for or `None` for a line continuation
+    fn read_escape(&mut self, raw:&mut String) -> Result<Option<char>, LexError> {
+        raw.push_char('\\');
+        let escape = try!(self.next());
+        Ok(Some(match escape {
+            '\n' | '\r' | '\u2028' | '\u2029' => {
+                if escape == '\r' {
+                    try!(self.next_is('\n'));
+                }
+                raw.push_char(if escape == '\r' {'\n'} else {escape});
+                return Ok(None);
+            },
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            'b' => '\x08',
+            'f' => '\x0c',
+            'v' => '\x0b',
+            '0' => '\0',
+            'x' | 'u' => {
+                raw.push_char(escape);
+                // `\u{...}` names a code point with any number of digits
+                let braced = escape == 'u' && try!(self.next_is('{'));
+                let mut nums = String::new();
+                if braced {
+                    loop {
+                        match try!(self.next()) {
+                            '}' => break,
+                            ch => nums.push_char(ch)
+                        }
+                    }
+                    raw.push_char('{');
+                    raw.push_str(nums.as_slice());
+                    raw.push_char('}');
+                } else {
+                    let digits = if escape == 'x' {2} else {4};
+                    for _ in range(0, digits) {
+                        nums.push_char(try!(self.next()));
+                    }
+                    raw.push_str(nums.as_slice());
+                }
+                let as_num = match from_str_radix(nums.as_slice(), 16) {
+                    Some(v) => v,
+                    None => return Err(InvalidEscape(escape, self.pos()))
+                };
+                match from_u32(as_num) {
+                    Some(v) => v,
+                    None => return Err(InvalidCodePoint(as_num, self.pos()))
+                }
+            },
+            '\'' | '"' | '\\' | '`' | '$' => escape,
+            _ => return Err(InvalidEscape(escape, self.pos()))
+        }))
+    }
+    /// Read digits in the radix given onto the end of `buf`, skipping the underscores that can
+    /// separate them
+    fn read_digits(&mut self, radix:uint, buf:&mut String) -> LexResult {
+        let mut separated = false;
+        loop {
+            match try!(self.preview_next()) {
+                // a separator has to come straight after a digit
+                Some('_') if !separated && buf.as_slice().chars().last().map_or(false, |last| last.is_digit_radix(radix)) => {
+                    separated = true;
+                },
+                Some(ch) if ch.is_digit_radix(radix) => {
+                    separated = false;
+                    buf.push_char(ch);
+                },
+                _ => break
+            }
+            try!(self.next());
+        }
+        if separated {
+            buf.push_char('_');
+            Err(InvalidNumber(buf.clone(), self.pos()))
+        } else {
+            Ok(())
+        }
+    }
+    /// Lex the rest of a numeric literal that starts with `first`, which is a digit or a `.`
+    /// followed by a digit
+    fn lex_number(&mut self, first:char) -> LexResult {
+        let mut buf = String::new();
+        let radix = match try!(self.preview_next()) {
+            Some('x') | Some('X') if first == '0' => 16,
+            Some('o') | Some('O') if first == '0' => 8,
+            Some('b') | Some('B') if first == '0' => 2,
+            _ => 10
+        };
+        let mut is_integer = true;
+        let mut legacy_octal = false;
+        let num = if radix != 10 {
+            try!(self.next());
+            try!(self.read_digits(radix, &mut buf));
+            if buf.is_empty() {
+                None
+            } else {
+                from_str_radix(buf.as_slice(), radix)
+            }
+        } else if first == '0' && try!(self.preview_next()).map_or(false, |next| next.is_digit()) {
+            // a legacy octal literal, which is read as a decimal if it has an 8 or 9 in it
+            loop {
+                match try!(self.preview_next()) {
+                    Some(ch) if ch.is_digit() => {
+                        buf.push_char(ch);
+                        try!(self.next());
+                    },
+                    _ => break
+                }
+            }
+            is_integer = false;
+            legacy_octal = true;
+            if buf.as_slice().chars().all(|ch| ch.is_digit_radix(8)) {
+                from_str_radix(buf.as_slice(), 8)
+            } else {
+                from_str(buf.as_slice())
+            }
+        } else {
+            if first == '.' {
+                buf.push_char('0');
+            } else {
+                buf.push_char(first);
+                try!(self.read_digits(10, &mut buf));
+            }
+            if first == '.' || try!(self.next_is('.')) {
+                is_integer = false;
+                let mut fraction = String::new();
+                try!(self.read_digits(10, &mut fraction));
+                if !fraction.is_empty() {
+                    buf.push_char('.');
+                    buf.push_str(fraction.as_slice());
+                }
+            }
+            match try!(self.preview_next()) {
+                Some('e') | Some('E') => {
+                    is_integer = false;
+                    try!(self.next());
+                    buf.push_char('e');
+                    match try!(self.preview_next()) {
+                        Some(sign) if sign == '+' || sign == '-' => {
+                            try!(self.next());
+                            buf.push_char(sign);
+                        },
+                        _ => ()
+                    }
+                    let len = buf.len();
+                    try!(self.read_digits(10, &mut buf));
+                    if buf.len() == len {
+                        return Err(InvalidNumber(buf, self.pos()));
+                    }
+                },
+                _ => ()
+            }
+            from_str(buf.as_slice())
+        };
+        // a BigInt literal is an integer with an `n` after it
+        let is_big_int = is_integer && try!(self.next_is('n'));
+        // a number can't run straight into an identifier or another number
+        match try!(self.preview_next()) {
+            Some(ch) if ch.is_alphanumeric() || ch == '_' || ch == '$' => {
+                buf.push_char(ch);
+                return Err(InvalidNumber(buf, self.pos()));
+            },
+            _ => ()
+        }
+        match num {
+            Some(_) if is_big_int => {
+                let prefix = match radix {
+                    16 => "0x",
+                    8 => "0o",
+                    2 => "0b",
+                    _ => ""
+                };
+                self.push_token(TBigIntLiteral(format!("{}{}", prefix, buf)));
+            },
+            Some(num) => {
+                self.push_token(TNumericLiteral(num));
+                self.tokens.mut_last().unwrap().legacy_octal = legacy_octal;
+            },
+            None => return Err(InvalidNumber(buf, self.pos()))
+        }
+        Ok(())
+    }
+    /// Lex the rest of a template literal after its opening backtick or the `}` that closes a
+    /// substitution, which gives its cooked and raw strings and whether a substitution follows
+    fn lex_template(&mut self) -> Result<(String, String, bool), LexError> {
+        let mut cooked = String::new();
+        let mut raw = String::new();
+        loop {
+            match try!(self.next()) {
+                '`' => return Ok((cooked, raw, false)),
+                '$' if try!(self.next_is('{')) => return Ok((cooked, raw, true)),
+                '\\' => match try!(self.read_escape(&mut raw)) {
+                    Some(ch) => cooked.push_char(ch),
+                    None => ()
+                },
+                ch if ch == '\n' || ch == '\r' || ch == '\u2028' || ch == '\u2029' => {
+                    // line terminators are normalised to `\n` in both the cooked and raw strings
+                    if ch == '\r' {
+                        try!(self.next_is('\n'));
+                    }
+                    let ch = if ch == '\r' {'\n'} else {ch};
+                    cooked.push_char(ch);
+                    raw.push_char(ch);
+                },
+                ch => {
+                    cooked.push_char(ch);
+                    raw.push_char(ch);
+                }
+            }
+        }
+    }
     /// Processes an input stream from the `buffer` into a vector of tokens
-    pub fn lex(&mut self) -> IoResult<()> {
+    pub fn lex(&mut self) -> LexResult {
         loop {
-            let ch = match self.next() {
+            self.start = self.pos();
+            let ch = match self.buffer.read_char() {
                 Ok(ch) => ch,
                 Err(IoError {kind: EndOfFile, ..}) => break,
-                Err(err) => return Err(err)
+                Err(err) => return Err(ReadFailure(err, self.pos()))
             };
-            self.column_number += 1;
+            self.advance(ch);
             match ch {
                 '"' | '\'' => {
                     let mut buf = String::new();
@@ -121,123 +472,23 @@ impl<B:Buffer> Lexer<B> {
                             '"' if ch == '"' => {
                                 break;
                             },
-                            '\\' => {
-                                let escape = try!(self.next());
-                                if escape != '\n' {
-                                    let escaped_ch = match escape {
-                                        'n' => '\n',
-                                        'r' => '\r',
-                                        't' => '\t',
-                                        'b' => '\x08',
-                                        'f' => '\x0c',
-                                        '0' => '\0',
-                                        'x' => {
-                                            let mut nums = String::with_capacity(2);
-                                            for _ in range(0u8, 2) {
-                                                nums.push_char(try!(self.next()));
-                                            }
-                                            self.column_number += 2;
-                                            let as_num = match from_str_radix(nums.as_slice(), 16) {
-                                                Some(v) => v,
-                                                None => 0
-                                            };
-                                            match from_u32(as_num) {
-                                                Some(v) => v,
-                                                None => fail!("{}:{}: {} is not a valid unicode scalar value", self.line_number, self.column_number, as_num)
-                                            }
-                                        },
-                                        'u' => {
-                                            let mut nums = String::new();
-                                            for _ in range(0u8, 4) {
-                                                nums.push_char(try!(self.next()));
-                                            }
-                                            self.column_number += 4;
-                                            let as_num = match from_str_radix(nums.as_slice(), 16) {
-                                                Some(v) => v,
-                                                None => 0
-                                            };
-                                            match from_u32(as_num) {
-                                                Some(v) => v,
-                                                None => fail!("{}:{}: {} is not a valid unicode scalar value", self.line_number, self.column_number, as_num)
-                                            }
-                                        },
-                                        '\'' | '"' => escape,
-                                        _ => fail!("{}:{}: Invalid escape `{}`", self.line_number, self.column_number, ch)
-                                    };
-                                    buf.push_char(escaped_ch);
-                                }
+                            '\\' => match try!(self.read_escape(&mut String::new())) {
+                                Some(escaped_ch) => buf.push_char(escaped_ch),
+                                None => ()
                             },
                             ch => buf.push_char(ch)
                         }
                     }
                     self.push_token(TStringLiteral(buf))
                 },
-                '0' => {
-                    let mut buf = String::new();
-                    let num = if try!(self.next_is('x')) {
-                        loop {
-                            let ch = try!(self.preview_next());
-                            match ch {
-                                ch if ch.is_digit_radix(16) => {
-                                    self.buffer.consume(1);
-                                    buf.push_char(ch)
-                                },
-                                _ => break
-                            }
-                        }
-                        from_str_radix(buf.as_slice(), 16).unwrap()                  
-                    } else {
-                        let mut gone_decimal = false;
-                        loop {
-                            let ch = try!(self.preview_next());
-                            match ch {
-                                ch if ch.is_digit_radix(8) => {
-                                    buf.push_char(ch);
-                                    self.buffer.consume(1);
-                                },
-                                '8' | '9' | '.' => {
-                                    gone_decimal = true;
-                                    buf.push_char(ch);
-                                    self.buffer.consume(1);
-                                },
-                                _ =>
-                                    break
-                            }
-                        }
-                        if gone_decimal {
-                            from_str(buf.as_slice())
-                        } else {
-                            from_str_radix(buf.as_slice(), 8)
-                        }.unwrap()
-                    };
-                    self.push_token(TNumericLiteral(num))
-                },
-                _ if ch.is_digit() => {
-                    let mut buf = ch.to_string();
-                    loop {
-                        let ch = try!(self.preview_next());
-                        match ch {
-                            '.' => {
-                                buf.push_char(ch);
-                                self.buffer.consume(1);
-                            },
-                            _ if ch.is_digit() => {
-                                buf.push_char(ch);
-                                self.buffer.consume(1);
-                            },
-                            _ => break
-                        }
-                    }
-                    self.push_token(TNumericLiteral(from_str(buf.as_slice()).unwrap()));
-                },
+                _ if ch.is_digit() => try!(self.lex_number(ch)),
+                '.' if try!(self.preview_next()).map_or(false, |next| next.is_digit()) => try!(self.lex_number(ch)),
                 _ if ch.is_alphabetic() || ch == '$' || ch == '_' => {
                     let mut buf = ch.to_string();
                     loop {
-                        let ch = try!(self.preview_next());
-                        match ch {
-                            _ if ch.is_alphabetic() || ch.is_digit() || ch == '_' => {
-                                buf.push_char(ch);
-                                self.buffer.consume(1);
+                        match try!(self.preview_next()) {
+                            Some(ch) if ch.is_alphabetic() || ch.is_digit() || ch == '_' => {
+                                buf.push_char(try!(self.next()));
                             },
                             _ => {
                                 break;
@@ -256,22 +507,66 @@ impl<B:Buffer> Lexer<B> {
                 },
                 ';' => self.push_punc(PSemicolon),
                 ':' => self.push_punc(PColon),
+                '.' if try!(self.next_is('.')) => {
+                    if !try!(self.next_is('.')) {
+                        return Err(UnexpectedChar('.', self.start));
+                    }
+                    self.push_punc(PSpread)
+                },
                 '.' => self.push_punc(PDot),
                 '(' => self.push_punc(POpenParen),
                 ')' => self.push_punc(PCloseParen),
                 ',' => self.push_punc(PComma),
-                '{' => self.push_punc(POpenBlock),
-                '}' => self.push_punc(PCloseBlock),
+                '`' => {
+                    let (cooked, raw, substitution) = try!(self.lex_template());
+                    if substitution {
+                        self.braces.push(true);
+                        self.push_token(TTemplateHead(cooked, raw));
+                    } else {
+                        self.push_token(TNoSubstitutionTemplate(cooked, raw));
+                    }
+                },
+                '{' => {
+                    self.braces.push(false);
+                    self.push_punc(POpenBlock);
+                },
+                '}' => match self.braces.pop() {
+                    Some(true) => {
+                        let (cooked, raw, substitution) = try!(self.lex_template());
+                        if substitution {
+                            self.braces.push(true);
+                            self.push_token(TTemplateMiddle(cooked, raw));
+                        } else {
+                            self.push_token(TTemplateTail(cooked, raw));
+                        }
+                    },
+                    _ => self.push_punc(PCloseBlock)
+                },
                 '[' => self.push_punc(POpenBracket),
                 ']' => self.push_punc(PCloseBracket),
                 '?' => self.push_punc(PQuestion),
                 '/' => {
                     let token = match try!(self.preview_next()) {
-                        '/' => {
-                            let comment = try!(self.buffer.read_line());
-                            TComment(comment.as_slice().slice_to(comment.len() - 1).into_string())
+                        Some('/') => {
+                            try!(self.next());
+                            // the line terminator that ends the comment is left for the next token
+                            let mut comment = String::new();
+                            loop {
+                                match try!(self.preview_next()) {
+                                    None | Some('\n') | Some('\r') => break,
+                                    Some(_) => match try!(self.next()) {
+                                        '\u2028' | '\u2029' => {
+                                            self.newline = true;
+                                            break;
+                                        },
+                                        ch => comment.push_char(ch)
+                                    }
+                                }
+                            }
+                            TComment(comment)
                         },
-                        '*' => {
+                        Some('*') => {
+                            try!(self.next());
                             let mut buf = String::new();
                             loop {
                                 match try!(self.next()) {
@@ -281,23 +576,71 @@ impl<B:Buffer> Lexer<B> {
                                         } else {
                                             buf.push_char('*');
                                         },
+                                    ch if ch == '\n' || ch == '\r' || ch == '\u2028' || ch == '\u2029' => {
+                                        self.newline = true;
+                                        buf.push_char(ch)
+                                    },
                                     ch =>
                                         buf.push_char(ch)
                                 }
                             }
                             TComment(buf)
                         },
-                        '=' => TPunctuator(PAssignDiv),
+                        _ if self.regex_allowed() => {
+                            let mut body = String::new();
+                            let mut in_class = false;
+                            loop {
+                                match try!(self.next()) {
+                                    '/' if !in_class => break,
+                                    '[' => {
+                                        in_class = true;
+                                        body.push_char('[');
+                                    },
+                                    ']' => {
+                                        in_class = false;
+                                        body.push_char(']');
+                                    },
+                                    '\\' => {
+                                        body.push_char('\\');
+                                        body.push_char(try!(self.next()));
+                                    },
+                                    '\n' | '\r' | '\u2028' | '\u2029' => return Err(UnterminatedRegExp(self.start)),
+                                    ch => body.push_char(ch)
+                                }
+                            }
+                            let mut flags = String::new();
+                            loop {
+                                match try!(self.preview_next()) {
+                                    Some(ch) if ch.is_alphanumeric() => {
+                                        if !"gimsuy".contains_char(ch) || flags.as_slice().contains_char(ch) {
+                                            return Err(InvalidRegExpFlag(ch, self.pos()));
+                                        }
+                                        try!(self.next());
+                                        flags.push_char(ch);
+                                    },
+                                    _ => break
+                                }
+                            }
+                            // the pattern is compiled here so a bad one is an early error
+                            match RegExp::new(body.as_slice(), flags.as_slice()) {
+                                Ok(_) => (),
+                                Err(why) => return Err(InvalidRegExp(why, self.start))
+                            }
+                            TRegularExpression(body, flags)
+                        },
+                        Some('=') => TPunctuator(PAssignDiv),
                         _ => TPunctuator(PDiv)
                     };
                     self.push_token(token)
                 },
-                '*' => op!(self, PAssignMul, PMul),
+                '*' => op!(self, PAssignMul, PMul, {
+                    '*' => vop!(self, PAssignExp, PExp)
+                }),
                 '+' => op!(self, PAssignAdd, PAdd, {
                     '+' => PInc
                 }),
                 '-' => op!(self, PAssignSub, PSub, {
-                    '+' => PDec
+                    '-' => PDec
                 }),
                 '%' => op!(self, PAssignMod, PMod),
                 '|' => op!(self, PAssignOr, POr, {
@@ -324,17 +667,43 @@ impl<B:Buffer> Lexer<B> {
                 }),
                 '!' => op!(self, vop!(self, PStrictNotEq, PNotEq), PNot),
                 '~' => self.push_punc(PNeg),
-                '\n' | '\u2028'|'\u2029' => {
-                    self.line_number += 1;
-                    self.column_number = 0;
-                },
-                '\r' => {
-                    self.column_number = 0;
-                },
-                ' ' => (),
-                ch => fail!("{}:{}: Unexpected '{}'", self.line_number, self.column_number, ch)
+                '\n' | '\r' | '\u2028' | '\u2029' => self.newline = true,
+                ' ' | '\t' | '\x0b' | '\x0c' | '\xa0' | '\ufeff' => (),
+                ch => return Err(UnexpectedChar(ch, self.start))
             };
         };
         Ok(())
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syntax::ast::pos::Position;
+    /// Lex a script that should fail, giving the error it fails with
+    fn lex_error(script:&str) -> LexError {
+        match Lexer::lex_str(script) {
+            Ok(_) => fail!("`{}` lexed without an error", script),
+            Err(err) => err
+        }
+    }
+    #[test]
+    fn unterminated_string() {
+        assert_eq!(lex_error("var a = 1;\nvar s = \"abc"), UnexpectedEnd(Position::new(2, 13, 23)));
+    }
+    #[test]
+    fn invalid_code_point() {
+        assert_eq!(lex_error("var a = 1;\nvar s = \"\\u{110000}\";"), InvalidCodePoint(0x110000, Position::new(2, 20, 30)));
+    }
+    #[test]
+    fn invalid_braced_escape() {
+        assert_eq!(lex_error("var s = \"\\u{12x}\";"), InvalidEscape('u', Position::new(1, 17, 16)));
+    }
+    #[test]
+    fn invalid_number() {
+        assert_eq!(lex_error("var a = 1;\nvar n = 1_;"), InvalidNumber("1_".into_string(), Position::new(2, 11, 21)));
+    }
+    #[test]
+    fn unterminated_regexp() {
+        assert_eq!(lex_error("var a = 1;\nvar r = /ab\n1;"), UnterminatedRegExp(Position::new(2, 9, 19)));
+    }
+}
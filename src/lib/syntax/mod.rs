@@ -2,5 +2,13 @@
 pub mod ast;
 /// Parses a string stream into a sequence of tokens
 pub mod lexer;
+/// Parses and matches the patterns of regular expressions
+pub mod regexp;
 /// Parses a sequence of tokens into expressions
-pub mod parser;
\ No newline at end of file
+pub mod parser;
+/// Checks the declarations in each scope of a parsed script
+pub mod scope;
+/// Errors found in the syntax of a script
+pub mod error;
+/// Parses a script while keeping its comments and whitespace
+pub mod cst;
\ No newline at end of file
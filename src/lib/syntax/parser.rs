@@ -4,15 +4,22 @@ use syntax::ast::constant::*;
 use syntax::ast::op::*;
 use syntax::ast::punc::*;
 use syntax::ast::keyword::*;
-use collections::treemap::TreeMap;
+use syntax::ast::pos::Position;
+use syntax::ast::pattern::*;
+use syntax::scope::check_scopes;
+use syntax::error::Diagnostic;
+use std::cmp::max;
+use std::f64;
 use std::fmt;
+use std::mem::replace;
+use std::i32;
 use std::vec::Vec;
 macro_rules! mk (
     ($this:expr, $def:expr) => (
-        Expr::new($def, try!($this.get_token($this.pos - 1)).pos, try!($this.get_token($this.pos - 1)).pos)
+        Expr::new($def, try!($this.get_token($this.pos - 1)).pos, try!($this.get_token($this.pos - 1)).end)
     );
-    ($this:expr, $def:expr, $first:expr) => (
-        Expr::new($def, $first.pos, try!($this.get_token($this.pos - 1)).pos)
+    ($this:expr, $def:expr, $start:expr) => (
+        Expr::new($def, $start, try!($this.get_token($this.pos - 1)).end)
     );
 )
 #[deriving(Clone, PartialEq)]
@@ -23,33 +30,56 @@ pub enum ParseError {
     /// When it expected a certain expression, but got another
     ExpectedExpr(&'static str, Expr),
     /// When it didn't expect this keyword
-    UnexpectedKeyword(Keyword),
+    UnexpectedKeyword(Keyword, Position),
+    /// When a `break` or `continue` refers to a label that doesn't enclose it
+    UnknownLabel(String, Position),
+    /// When a name is declared twice in the same scope, and at least once by `let` or `const`
+    Redeclaration(String, Position),
+    /// When a constant is assigned to after its declaration
+    ConstAssignment(String, Position),
+    /// When strict mode code does something that isn't allowed in it, as described
+    StrictModeViolation(String, Position),
     /// When there is an abrupt end to the parsing
     AbruptEnd
 }
 impl fmt::Show for ParseError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.pos() {
+            Some(pos) => write!(f, "{}:{}: {}", pos.line_number, pos.column_number, self.message()),
+            None => write!(f, "{}", self.message())
+        }
+    }
+}
+impl ParseError {
+    /// Get the part of the script where this error happened, which is unknown for an abrupt end
+    pub fn span(&self) -> Option<(Position, Position)> {
+        match *self {
+            Expected(_, ref got, _) => Some((got.pos, got.end)),
+            ExpectedExpr(_, ref got) => Some((got.start, got.end)),
+            UnexpectedKeyword(_, pos) | UnknownLabel(_, pos) |
+            Redeclaration(_, pos) | ConstAssignment(_, pos) | StrictModeViolation(_, pos) => Some((pos, pos)),
+            AbruptEnd => None
+        }
+    }
+    /// Get the position in the script where this error happened, which is unknown for an abrupt end
+    pub fn pos(&self) -> Option<Position> {
+        self.span().map(|(start, _)| start)
+    }
+    /// Describe this error without its position
+    pub fn message(&self) -> String {
         match *self {
-            Expected(ref wanted, ref got, ref routine) if wanted.len() == 0 => write!(f, "{}:{}: Expected expression for {}, got {}", got.pos.line_number, got.pos.column_number, routine, got.data),
+            Expected(ref wanted, ref got, ref routine) if wanted.len() == 0 => format!("Expected expression for {}, got {}", routine, got.data),
             Expected(ref wanted, ref got, ref routine) => {
-                try!(write!(f, "{}:{}: ", got.pos.line_number, got.pos.column_number));
-                try!(write!(f, "Expected "));
-                let last = wanted.last().unwrap();
-                for wanted_token in wanted.iter() {
-                    try!(write!(f, "'{}'{}", wanted_token, if wanted_token == last {""} else {", "}));
-                }
-                try!(write!(f, " for {}", routine));
-                write!(f, " but got {}", got.data)
-            },
-            UnexpectedKeyword(ref key) => {
-                write!(f, "Unexpected {}", key)
-            }
-            ExpectedExpr(ref wanted, ref got) => {
-                write!(f, "Expected {}, but got {}", wanted, got)
+                let wanted : Vec<String> = wanted.iter().map(|wanted_token| format!("'{}'", wanted_token)).collect();
+                format!("Expected {} for {} but got {}", wanted.connect(", "), routine, got.data)
             },
-            AbruptEnd => {
-                write!(f, "Abrupt end")
-            }
+            UnexpectedKeyword(ref key, _) => format!("Unexpected {}", key),
+            UnknownLabel(ref label, _) => format!("Unknown label {}", label),
+            Redeclaration(ref name, _) => format!("{} has already been declared", name),
+            ConstAssignment(ref name, _) => format!("Assignment to constant {}", name),
+            StrictModeViolation(ref what, _) => format!("{} isn't allowed in strict mode", what),
+            ExpectedExpr(ref wanted, ref got) => format!("Expected {}, but got {}", wanted, got),
+            AbruptEnd => "Abrupt end".into_string()
         }
     }
 }
@@ -59,39 +89,217 @@ pub struct Parser {
     /// The tokens being input
     tokens: Vec<Token>,
     /// The current position within the tokens
-    pos: uint
+    pos: uint,
+    /// The labels enclosing the current statement, and whether each labels a loop
+    labels: Vec<(String, bool)>,
+    /// How many loops enclose the current statement
+    loop_depth: uint,
+    /// How many switch statements enclose the current statement
+    switch_depth: uint,
+    /// Whether `in` ends the current expression instead of being an operator, which it does in
+    /// the initialiser of a for loop so it can start a for-in loop
+    no_in: bool,
+    /// Whether the current function is a generator, which makes `yield` an operator instead of a name
+    generator: bool,
+    /// Whether the current function is async, which makes `await` an operator instead of a name
+    async: bool,
+    /// Whether the current code is strict mode code, which is inside a class or module, or after a
+    /// `"use strict"` directive in the script or a function enclosing it
+    strict: bool,
+    /// Whether statements that can't be parsed are replaced with error expressions instead of
+    /// stopping the parse
+    recovering: bool,
+    /// The errors found in the statements that were replaced
+    diagnostics: Vec<Diagnostic>
 }
 impl Parser {
     #[inline(always)]
     /// Creates a new parser, using `tokens` as input
     pub fn new(tokens: Vec<Token>) -> Parser {
-        Parser {tokens: tokens, pos: 0}
+        // comments don't mean anything, and the lexer keeps the line terminators in them on the token after
+        let tokens = tokens.move_iter().filter(|tk| match tk.data {
+            TComment(_) => false,
+            _ => true
+        }).collect();
+        Parser {tokens: tokens, pos: 0, labels: Vec::new(), loop_depth: 0, switch_depth: 0, no_in: false, generator: false, async: false, strict: false, recovering: false, diagnostics: Vec::new()}
     }
     /// Parse all expressions in the token array
     pub fn parse_all(&mut self) -> ParseResult {
-        let mut exprs = Vec::new();
+        let mut exprs = try!(self.parse_directives());
+        while self.pos < self.tokens.len() {
+            let result = try!(self.parse_statement());
+            exprs.push(result);
+        }
+        let script = mk!(self, BlockExpr(exprs), try!(self.get_token(0)).pos);
+        try!(check_scopes(&script, false));
+        Ok(script)
+    }
+    /// Parse all the statements in the token array as the body of a module, which can import and
+    /// export bindings at its top level
+    pub fn parse_module(&mut self) -> ParseResult {
+        // module code is always strict mode code
+        self.strict = true;
+        let mut exprs = try!(self.parse_directives());
         while self.pos < self.tokens.len() {
-            let result = try!(self.parse());
+            let tk = try!(self.get_token(self.pos));
+            let result = match tk.data {
+                TKeyword(KImport) => {
+                    self.pos += 1;
+                    try!(self.parse_import(tk.pos))
+                },
+                TKeyword(KExport) => {
+                    self.pos += 1;
+                    try!(self.parse_export(tk.pos))
+                },
+                _ => try!(self.parse_statement())
+            };
             exprs.push(result);
         }
-        Ok(mk!(self, BlockExpr(exprs)))
+        let module = mk!(self, BlockExpr(exprs), try!(self.get_token(0)).pos);
+        try!(check_scopes(&module, true));
+        Ok(module)
+    }
+    /// Parse all expressions in the token array, replacing each statement that can't be parsed with
+    /// an `ErrorExpr` and carrying on after it, which gives a diagnostic for each of them
+    ///
+    /// The first diagnostic is the error that `parse_all` gives, if there are any
+    pub fn parse_all_recovering(&mut self) -> (Expr, Vec<Diagnostic>) {
+        self.recovering = true;
+        // the directives can't fail to parse, since a statement that can't be parsed is recovered from
+        let mut exprs = self.parse_directives().unwrap();
+        while self.pos < self.tokens.len() {
+            exprs.push(self.recover_statement());
+        }
+        let (start, end) = match (self.tokens.as_slice().head(), self.tokens.last()) {
+            (Some(first), Some(last)) => (first.pos, last.end),
+            _ => (Position::new(1, 1, 0), Position::new(1, 1, 0))
+        };
+        let script = Expr::new(BlockExpr(exprs), start, end);
+        match check_scopes(&script, false) {
+            Ok(()) => (),
+            Err(err) => self.report(&err)
+        }
+        (script, replace(&mut self.diagnostics, Vec::new()))
+    }
+    /// Add a diagnostic for an error found while recovering
+    fn report(&mut self, err:&ParseError) {
+        let end = self.tokens.last().map(|tk| tk.end).unwrap_or(Position::new(1, 1, 0));
+        self.diagnostics.push(Diagnostic::from_parse(err, end));
+    }
+    /// Parse a statement of a script or block, which is replaced with an `ErrorExpr` if it can't be
+    /// parsed while recovering
+    fn parse_block_statement(&mut self) -> ParseResult {
+        if self.recovering {
+            Ok(self.recover_statement())
+        } else {
+            self.parse_statement()
+        }
+    }
+    /// Parse a statement, or report why it can't be parsed and skip past it to give an `ErrorExpr`
+    fn recover_statement(&mut self) -> Expr {
+        let start = self.pos;
+        let state = (self.labels.clone(), self.loop_depth, self.switch_depth, self.no_in);
+        match self.parse_statement() {
+            Ok(expr) => expr,
+            Err(err) => {
+                self.report(&err);
+                let (labels, loop_depth, switch_depth, no_in) = state;
+                self.labels = labels;
+                self.loop_depth = loop_depth;
+                self.switch_depth = switch_depth;
+                self.no_in = no_in;
+                self.synchronise(start);
+                let message = self.diagnostics.last().unwrap().message.clone();
+                Expr::new(ErrorExpr(message), self.tokens[start].pos, self.tokens[self.pos - 1].end)
+            }
+        }
+    }
+    /// Skip past a statement starting at the token `start` that couldn't be parsed, up to the
+    /// first `;`, `}` closing the enclosing block or token on a new line that isn't inside
+    /// brackets and isn't before where the parse failed
+    fn synchronise(&mut self, start:uint) {
+        let failed = max(self.pos, start + 1);
+        let mut depth = 0u;
+        let mut pos = start;
+        while pos < self.tokens.len() {
+            let tk = &self.tokens[pos];
+            if pos > start && depth == 0 {
+                match tk.data {
+                    TPunctuator(PCloseBlock) => break,
+                    _ if pos >= failed && tk.newline_before => break,
+                    _ => ()
+                }
+            }
+            pos += 1;
+            match tk.data {
+                TPunctuator(POpenParen) | TPunctuator(POpenBracket) | TPunctuator(POpenBlock) => depth += 1,
+                TPunctuator(PCloseParen) | TPunctuator(PCloseBracket) | TPunctuator(PCloseBlock) if depth > 0 => depth -= 1,
+                TPunctuator(PSemicolon) if depth == 0 && pos >= failed => break,
+                _ => ()
+            }
+        }
+        self.pos = pos;
     }
     fn parse_struct(&mut self, keyword:Keyword) -> ParseResult {
+        let keyword_pos = try!(self.get_token(self.pos - 1)).pos;
         match keyword {
             KThrow => {
+                // a new line can't come between `throw` and the value it throws
+                match self.tokens.as_slice().get(self.pos) {
+                    Some(tk) if tk.newline_before => return Err(Expected(Vec::new(), tk.clone(), "throw statement")),
+                    _ => ()
+                }
                 let thrown = try!(self.parse());
-                Ok(mk!(self, ThrowExpr(box thrown)))
+                Ok(mk!(self, ThrowExpr(box thrown), keyword_pos))
             },
-            KVar => {
+            KTry => {
+                // the bodies of a try statement are always blocks, never object literals
+                let expr = try!(self.parse_block());
+                let catch = match self.get_token(self.pos) {
+                    Ok(Token {data: TKeyword(KCatch), ..}) => {
+                        self.pos += 1;
+                        try!(self.expect_punc(POpenParen, "catch clause"));
+                        let tk = try!(self.get_token(self.pos));
+                        let name = match tk.data {
+                            TIdentifier(ref name) => name.clone(),
+                            _ => return Err(Expected(vec!(TIdentifier("identifier".into_string())), tk, "catch clause"))
+                        };
+                        self.pos += 1;
+                        try!(self.expect_punc(PCloseParen, "catch clause"));
+                        Some((name, box try!(self.parse_block())))
+                    },
+                    _ => None
+                };
+                let finally = match self.get_token(self.pos) {
+                    Ok(Token {data: TKeyword(KFinally), ..}) => {
+                        self.pos += 1;
+                        Some(box try!(self.parse_block()))
+                    },
+                    _ => None
+                };
+                if catch.is_none() && finally.is_none() {
+                    return Err(Expected(vec!(TKeyword(KCatch), TKeyword(KFinally)), try!(self.get_token(self.pos)), "try statement"));
+                }
+                Ok(mk!(self, TryExpr(box expr, catch, finally), keyword_pos))
+            },
+            KVar | KLet | KConst => {
+                let kind = match keyword {
+                    KVar => VarDecl,
+                    KLet => LetDecl,
+                    _ => ConstDecl
+                };
                 let mut vars = Vec::new();
                 loop {
-                    let name = match self.get_token(self.pos) {
-                        Ok(Token { data: TIdentifier(ref name), ..}) => name.clone(),
-                        Ok(tok) => return Err(Expected(vec!(TIdentifier("identifier".into_string())), tok, "var statement")),
+                    match self.get_token(self.pos) {
+                        Ok(_) => (),
                         Err(AbruptEnd) => break,
                         Err(e) => return Err(e)
+                    }
+                    let name = try!(self.parse_pattern());
+                    let destructures = match name {
+                        NamePattern(_) => false,
+                        _ => true
                     };
-                    self.pos += 1;
                     match self.get_token(self.pos) {
                         Ok(Token {data: TPunctuator(PAssign), ..}) => {
                             self.pos += 1;
@@ -102,6 +310,9 @@ impl Parser {
                                 _ => break
                             }
                         },
+                        // constants and destructuring have to be initialised, unless they're the variable of a for-in loop
+                        Ok(tok) if (kind == ConstDecl || destructures) && tok.data != TKeyword(KIn) =>
+                            return Err(Expected(vec!(TPunctuator(PAssign)), tok, "variable declaration")),
                         Ok(Token {data: TPunctuator(PComma), ..}) => {
                             self.pos += 1;
                             vars.push((name, None));
@@ -112,36 +323,103 @@ impl Parser {
                         }
                     }
                 }
-                Ok(mk!(self, VarDeclExpr(vars)))
+                Ok(mk!(self, VarDeclExpr(kind, vars), keyword_pos))
             },
-            KReturn => Ok(mk!(self, ReturnExpr(Some(box try!(self.parse()).clone())))),
+            KBreak | KContinue => {
+                let label = match self.get_token(self.pos) {
+                    // a label on the next line is a statement of its own
+                    Ok(Token {data: TIdentifier(ref label), newline_before: false, ..}) => {
+                        self.pos += 1;
+                        Some(label.clone())
+                    },
+                    _ => None
+                };
+                match label {
+                    Some(ref label) => match self.labels.iter().rev().find(|&&(ref name, _)| name == label) {
+                        Some(&(_, is_loop)) if is_loop || keyword == KBreak => (),
+                        Some(_) => return Err(UnexpectedKeyword(keyword, keyword_pos)),
+                        None => return Err(UnknownLabel(label.clone(), keyword_pos))
+                    },
+                    None if self.loop_depth > 0 || (keyword == KBreak && self.switch_depth > 0) => (),
+                    None => return Err(UnexpectedKeyword(keyword, keyword_pos))
+                }
+                Ok(mk!(self, if keyword == KBreak {
+                    BreakExpr(label)
+                } else {
+                    ContinueExpr(label)
+                }, keyword_pos))
+            },
+            KReturn => Ok(mk!(self, ReturnExpr(if self.can_end_statement() {
+                None
+            } else {
+                Some(box try!(self.parse()))
+            }), keyword_pos)),
             KNew => {
                 let call = try!(self.parse());
                 match call.def {
-                    CallExpr(ref func, ref args) => Ok(mk!(self, ConstructExpr(func.clone(), args.clone()))),
+                    CallExpr(ref func, ref args) => Ok(mk!(self, ConstructExpr(func.clone(), args.clone()), keyword_pos)),
                     _ => Err(ExpectedExpr("constructor", call))
                 }
             },
-            KTypeOf => Ok(mk!(self, TypeOfExpr(box try!(self.parse())))),
+            KTypeOf => Ok(mk!(self, TypeOfExpr(box try!(self.parse())), keyword_pos)),
+            KDelete => self.unary_op(UnaryDelete),
+            KVoid => self.unary_op(UnaryVoid),
             KIf => {
                 try!(self.expect_punc(POpenParen, "if block"));
                 let cond = try!(self.parse());
                 try!(self.expect_punc(PCloseParen, "if block"));
-                let expr = try!(self.parse());
-                let next = self.get_token(self.pos + 1);
+                let expr = try!(self.parse_statement());
+                let next = self.get_token(self.pos);
                 Ok(mk!(self, IfExpr(box cond, box expr, if next.is_ok() && next.unwrap().data == TKeyword(KElse) {
-                    self.pos += 2;
-                    Some(box try!(self.parse()))
+                    self.pos += 1;
+                    Some(box try!(self.parse_statement()))
                 } else {
                     None
-                })))
+                }), keyword_pos))
             },
             KWhile => {
                 try!(self.expect_punc(POpenParen, "while condition"));
                 let cond = try!(self.parse());
                 try!(self.expect_punc(PCloseParen, "while condition"));
-                let expr = try!(self.parse());
-                Ok(mk!(self, WhileLoopExpr(box cond, box expr)))
+                let expr = try!(self.parse_loop_body());
+                Ok(mk!(self, WhileLoopExpr(box cond, box expr), keyword_pos))
+            },
+            KDo => {
+                let expr = try!(self.parse_loop_body());
+                try!(self.expect(TKeyword(KWhile), "do-while loop"));
+                try!(self.expect_punc(POpenParen, "do-while condition"));
+                let cond = try!(self.parse());
+                try!(self.expect_punc(PCloseParen, "do-while condition"));
+                Ok(mk!(self, DoWhileLoopExpr(box expr, box cond), keyword_pos))
+            },
+            KFor => {
+                try!(self.expect_punc(POpenParen, "for loop"));
+                let init = if try!(self.get_token(self.pos)).data == TPunctuator(PSemicolon) {
+                    self.pos += 1;
+                    None
+                } else {
+                    let no_in = replace(&mut self.no_in, true);
+                    let init = self.parse();
+                    self.no_in = no_in;
+                    let init = try!(init);
+                    if try!(self.get_token(self.pos)).data == TKeyword(KIn) {
+                        match init.def {
+                            LocalExpr(_) | VarDeclExpr(_, _) => (),
+                            _ => return Err(ExpectedExpr("for-in variable", init))
+                        }
+                        self.pos += 1;
+                        let obj = try!(self.parse());
+                        try!(self.expect_punc(PCloseParen, "for-in loop"));
+                        let expr = try!(self.parse_loop_body());
+                        return Ok(mk!(self, ForInLoopExpr(box init, box obj, box expr), keyword_pos));
+                    }
+                    try!(self.end_for_clause(PSemicolon));
+                    Some(box init)
+                };
+                let cond = try!(self.parse_for_clause(PSemicolon));
+                let step = try!(self.parse_for_clause(PCloseParen));
+                let expr = try!(self.parse_loop_body());
+                Ok(mk!(self, ForLoopExpr(init, cond, step, box expr), keyword_pos))
             },
             KSwitch => {
                 try!(self.expect_punc(POpenParen, "switch value"));
@@ -150,7 +428,8 @@ impl Parser {
                 try!(self.expect_punc(POpenBlock, "switch block"));
                 let mut cases = Vec::new();
                 let mut default = None;
-                while self.pos + 1 < self.tokens.len() {
+                self.switch_depth += 1;
+                loop {
                     let tok = try!(self.get_token(self.pos));
                     self.pos += 1;
                     match tok.data {
@@ -162,7 +441,7 @@ impl Parser {
                                 match try!(self.get_token(self.pos)).data {
                                     TKeyword(KCase) | TKeyword(KDefault) => break,
                                     TPunctuator(PCloseBlock) => break,
-                                    _ => block.push(try!(self.parse()))
+                                    _ => block.push(try!(self.parse_statement()))
                                 }
                             }
                             cases.push((cond.unwrap(), block));
@@ -174,22 +453,26 @@ impl Parser {
                                 match try!(self.get_token(self.pos)).data {
                                     TKeyword(KCase) | TKeyword(KDefault) => break,
                                     TPunctuator(PCloseBlock) => break,
-                                    _ => block.push(try!(self.parse()))
+                                    _ => block.push(try!(self.parse_statement()))
                                 }
                             }
-                            default = Some(mk!(self, BlockExpr(block)));
+                            default = Some(mk!(self, BlockExpr(block), tok.pos));
                         },
                         TPunctuator(PCloseBlock) => break,
                         _ => return Err(Expected(vec!(TKeyword(KCase), TKeyword(KDefault), TPunctuator(PCloseBlock)), tok, "switch block"))
                     }
                 }
-                try!(self.expect_punc(PCloseBlock, "switch block"));
+                self.switch_depth -= 1;
                 Ok(mk!(self, SwitchExpr(box value.unwrap(), cases, match default {
                     Some(v) => Some(box v),
                     None => None
-                })))
+                }), keyword_pos))
             },
             KFunction => {
+                let generator = try!(self.get_token(self.pos)).data == TPunctuator(PMul);
+                if generator {
+                    self.pos += 1;
+                }
                 let tk = try!(self.get_token(self.pos));
                 let name = match tk.data {
                     TIdentifier(ref name) => {
@@ -200,24 +483,685 @@ impl Parser {
                     _ => return Err(Expected(vec!(TIdentifier("identifier".into_string())), tk.clone(), "function name"))
                 };
                 try!(self.expect_punc(POpenParen, "function"));
-                let mut args:Vec<String> = Vec::new();
-                let mut tk = try!(self.get_token(self.pos));
-                while tk.data != TPunctuator(PCloseParen) {
-                    match tk.data {
-                        TIdentifier(ref id) => args.push(id.clone()),
-                        _ => return Err(Expected(vec!(TIdentifier("identifier".into_string())), tk.clone(), "function arguments"))
-                    }
+                let args = try!(self.parse_params());
+                let block = try!(self.parse_function_body(generator, false));
+                Ok(mk!(self, if generator {
+                    GeneratorDeclExpr(name, args, box block)
+                } else {
+                    FunctionDeclExpr(name, args, box block)
+                }, keyword_pos))
+            },
+            KClass => {
+                // the whole of a class is strict mode code
+                let strict = replace(&mut self.strict, true);
+                let class = self.parse_class(keyword_pos);
+                self.strict = strict;
+                class
+            },
+            KYield => {
+                // `yield*` delegates to another iterator, and a plain `yield` can leave out its value
+                if self.get_token(self.pos).map(|tk| tk.data == TPunctuator(PMul)).unwrap_or(false) {
                     self.pos += 1;
-                    if try!(self.get_token(self.pos)).data == TPunctuator(PComma) {
-                        self.pos += 1;
-                    }
-                    tk = try!(self.get_token(self.pos));
+                    return Ok(mk!(self, DelegateYieldExpr(box try!(self.parse())), keyword_pos));
                 }
+                let no_value = match self.tokens.as_slice().get(self.pos) {
+                    Some(&Token {data: TPunctuator(PCloseParen), ..}) | Some(&Token {data: TPunctuator(PCloseBracket), ..}) |
+                    Some(&Token {data: TPunctuator(PComma), ..}) | Some(&Token {data: TPunctuator(PColon), ..}) => true,
+                    _ => self.can_end_statement()
+                };
+                Ok(mk!(self, YieldExpr(if no_value {
+                    None
+                } else {
+                    Some(box try!(self.parse()))
+                }), keyword_pos))
+            },
+            KAwait => {
+                let next = try!(self.parse());
+                self.combine_await(keyword_pos, next)
+            },
+            KWith if self.strict => Err(StrictModeViolation("A with statement".into_string(), keyword_pos)),
+            KThis => Ok(mk!(self, ThisExpr)),
+            KSuper => Ok(mk!(self, SuperExpr)),
+            _ => Err(UnexpectedKeyword(keyword, keyword_pos))
+        }
+    }
+    /// Parse an import declaration after its `import`, which starts at `start`
+    fn parse_import(&mut self, start:Position) -> ParseResult {
+        let mut specifiers = Vec::new();
+        // `import "module"` only runs the module
+        match try!(self.get_token(self.pos)).data {
+            TStringLiteral(module) => {
+                self.pos += 1;
+                try!(self.end_statement());
+                return Ok(mk!(self, ImportExpr(specifiers, module), start));
+            },
+            TIdentifier(ref name) => {
                 self.pos += 1;
-                let block = try!(self.parse());
-                Ok(mk!(self, FunctionDeclExpr(name, args, box block)))
+                specifiers.push(ImportDefault(name.clone()));
+                if try!(self.get_token(self.pos)).data != TPunctuator(PComma) {
+                    return self.end_import(specifiers, start);
+                }
+                self.pos += 1;
+            },
+            _ => ()
+        }
+        let tk = try!(self.get_token(self.pos));
+        self.pos += 1;
+        match tk.data {
+            TPunctuator(PMul) => {
+                try!(self.expect_word("as", "namespace import"));
+                specifiers.push(ImportNamespace(try!(self.parse_binding_name("namespace import"))));
+            },
+            TPunctuator(POpenBlock) => for (name, local) in try!(self.parse_export_names("import specifiers")).move_iter() {
+                specifiers.push(ImportNamed(name, local));
             },
-            _ => Err(UnexpectedKeyword(keyword))
+            _ => return Err(Expected(vec!(TPunctuator(PMul), TPunctuator(POpenBlock)), tk, "import declaration"))
+        }
+        self.end_import(specifiers, start)
+    }
+    /// Parse the `from` clause ending an import declaration
+    fn end_import(&mut self, specifiers:Vec<ImportSpecifier>, start:Position) -> ParseResult {
+        try!(self.expect_word("from", "import declaration"));
+        let module = try!(self.parse_specifier("import declaration"));
+        try!(self.end_statement());
+        Ok(mk!(self, ImportExpr(specifiers, module), start))
+    }
+    /// Parse an export declaration after its `export`, which starts at `start`
+    fn parse_export(&mut self, start:Position) -> ParseResult {
+        let tk = try!(self.get_token(self.pos));
+        let export = match tk.data {
+            TPunctuator(PMul) => {
+                self.pos += 1;
+                try!(self.expect_word("from", "export declaration"));
+                let module = try!(self.parse_specifier("export declaration"));
+                try!(self.end_statement());
+                ExportAll(module)
+            },
+            TPunctuator(POpenBlock) => {
+                self.pos += 1;
+                let names = try!(self.parse_export_names("export specifiers"));
+                let module = if self.is_word("from") {
+                    self.pos += 1;
+                    Some(try!(self.parse_specifier("export declaration")))
+                } else {
+                    None
+                };
+                try!(self.end_statement());
+                ExportNames(names, module)
+            },
+            TKeyword(KDefault) => {
+                self.pos += 1;
+                // a function or class declaration is exported as a declaration, and anything else as an expression
+                let is_declaration = match try!(self.get_token(self.pos)).data {
+                    TKeyword(KFunction) | TKeyword(KClass) => true,
+                    TIdentifier(ref word) => word.as_slice() == "async" && self.is_async_function(self.pos + 1),
+                    _ => false
+                };
+                if is_declaration {
+                    ExportDefault(box try!(self.parse_statement()))
+                } else {
+                    let expr = try!(self.parse());
+                    try!(self.end_statement());
+                    ExportDefault(box expr)
+                }
+            },
+            TKeyword(KVar) | TKeyword(KLet) | TKeyword(KConst) | TKeyword(KFunction) | TKeyword(KClass) =>
+                ExportDeclaration(box try!(self.parse_statement())),
+            TIdentifier(ref word) if word.as_slice() == "async" && self.is_async_function(self.pos + 1) =>
+                ExportDeclaration(box try!(self.parse_statement())),
+            _ => return Err(Expected(vec!(TPunctuator(PMul), TPunctuator(POpenBlock), TKeyword(KDefault)), tk, "export declaration"))
+        };
+        Ok(mk!(self, ExportExpr(export), start))
+    }
+    /// Parse the names between the braces of an import or export declaration after the open brace,
+    /// up to and including the close brace, giving each name with the name it's bound or exported as
+    fn parse_export_names(&mut self, routine:&'static str) -> Result<Vec<(String, String)>, ParseError> {
+        let mut names = Vec::new();
+        loop {
+            if try!(self.get_token(self.pos)).data == TPunctuator(PCloseBlock) {
+                self.pos += 1;
+                return Ok(names);
+            }
+            let name = try!(self.parse_binding_name(routine));
+            let alias = if self.is_word("as") {
+                self.pos += 1;
+                try!(self.parse_binding_name(routine))
+            } else {
+                name.clone()
+            };
+            names.push((name, alias));
+            let tk = try!(self.get_token(self.pos));
+            self.pos += 1;
+            match tk.data {
+                TPunctuator(PComma) => (),
+                TPunctuator(PCloseBlock) => return Ok(names),
+                _ => return Err(Expected(vec!(TPunctuator(PComma), TPunctuator(PCloseBlock)), tk, routine))
+            }
+        }
+    }
+    /// Parse a name in an import or export declaration, which can be a keyword such as `default`
+    fn parse_binding_name(&mut self, routine:&'static str) -> Result<String, ParseError> {
+        let tk = try!(self.get_token(self.pos));
+        self.pos += 1;
+        match tk.data {
+            TIdentifier(ref name) => Ok(name.clone()),
+            TKeyword(ref keyword) => Ok(keyword.to_string()),
+            _ => Err(Expected(vec!(TIdentifier("identifier".into_string())), tk, routine))
+        }
+    }
+    /// Parse the string naming the module an import or export declaration takes bindings from
+    fn parse_specifier(&mut self, routine:&'static str) -> Result<String, ParseError> {
+        let tk = try!(self.get_token(self.pos));
+        self.pos += 1;
+        match tk.data {
+            TStringLiteral(ref module) => Ok(module.clone()),
+            _ => Err(Expected(vec!(TStringLiteral("module specifier".into_string())), tk, routine))
+        }
+    }
+    /// Returns true if the next token is the identifier `word`
+    fn is_word(&self, word:&str) -> bool {
+        match self.tokens.as_slice().get(self.pos) {
+            Some(&Token {data: TIdentifier(ref name), ..}) => name.as_slice() == word,
+            _ => false
+        }
+    }
+    /// Returns an error if the next token is not the identifier `word`, which is used for words
+    /// that are only keywords in some places such as `from`
+    fn expect_word(&mut self, word:&'static str, routine:&'static str) -> Result<(), ParseError> {
+        self.expect(TIdentifier(word.into_string()), routine)
+    }
+    /// Parse a class declaration or expression after its `class`, which starts at `start`
+    fn parse_class(&mut self, start:Position) -> ParseResult {
+        let name = match try!(self.get_token(self.pos)).data {
+            TIdentifier(ref name) => {
+                self.pos += 1;
+                Some(name.clone())
+            },
+            _ => None
+        };
+        let parent = if try!(self.get_token(self.pos)).data == TKeyword(KExtends) {
+            self.pos += 1;
+            Some(box try!(self.parse()))
+        } else {
+            None
+        };
+        try!(self.expect_punc(POpenBlock, "class"));
+        let mut constructor = None;
+        let mut members = Vec::new();
+        loop {
+            let tk = try!(self.get_token(self.pos));
+            match tk.data {
+                TPunctuator(PCloseBlock) => {
+                    self.pos += 1;
+                    break;
+                },
+                TPunctuator(PSemicolon) => {
+                    self.pos += 1;
+                    continue;
+                },
+                _ => ()
+            }
+            let member = try!(self.parse_class_member());
+            if !member.is_static && member.kind == PlainMethod && member.name.as_slice() == "constructor" {
+                if constructor.is_some() {
+                    return Err(Redeclaration(member.name, tk.pos));
+                }
+                constructor = Some(member.func);
+            } else {
+                members.push(member);
+            }
+        }
+        let constructor = match constructor {
+            Some(constructor) => constructor,
+            None => try!(self.default_constructor(parent.is_some()))
+        };
+        Ok(mk!(self, ClassDeclExpr(name, parent, box constructor, members), start))
+    }
+    /// Parse a method, getter or setter in a class body
+    fn parse_class_member(&mut self) -> Result<ClassMember, ParseError> {
+        let first = try!(self.get_token(self.pos));
+        let mut is_static = false;
+        let mut kind = PlainMethod;
+        // `static`, `get` and `set` are only modifiers when they aren't the name of the method
+        if first.data == TIdentifier("static".into_string()) && try!(self.get_token(self.pos + 1)).data != TPunctuator(POpenParen) {
+            is_static = true;
+            self.pos += 1;
+        }
+        // `async` is only a modifier when the name of the method follows it on the same line
+        let async = match (try!(self.get_token(self.pos)).data, try!(self.get_token(self.pos + 1))) {
+            (TIdentifier(ref word), ref next) => word.as_slice() == "async" && !next.newline_before && next.data != TPunctuator(POpenParen),
+            _ => false
+        };
+        if async {
+            self.pos += 1;
+        }
+        let generator = !async && try!(self.get_token(self.pos)).data == TPunctuator(PMul);
+        if generator {
+            self.pos += 1;
+        }
+        match try!(self.get_token(self.pos)).data {
+            TIdentifier(ref word) if try!(self.get_token(self.pos + 1)).data != TPunctuator(POpenParen) => {
+                kind = match word.as_slice() {
+                    "get" => GetMethod,
+                    "set" => SetMethod,
+                    _ => PlainMethod
+                };
+                if kind != PlainMethod {
+                    self.pos += 1;
+                }
+            },
+            _ => ()
+        }
+        let tk = try!(self.get_token(self.pos));
+        self.pos += 1;
+        let name = match tk.data {
+            TIdentifier(ref name) | TStringLiteral(ref name) => name.clone(),
+            TKeyword(ref keyword) => keyword.to_string(),
+            _ => return Err(Expected(vec!(TIdentifier("identifier".into_string())), tk.clone(), "class member"))
+        };
+        Ok(ClassMember {
+            name: name,
+            is_static: is_static,
+            kind: kind,
+            func: try!(self.parse_method(generator, async, first.pos, "class member"))
+        })
+    }
+    /// Parse the parameters and body of a method after its name, giving a function that starts at `start`
+    fn parse_method(&mut self, generator:bool, async:bool, start:Position, routine:&'static str) -> ParseResult {
+        try!(self.expect_punc(POpenParen, routine));
+        let params = try!(self.parse_params());
+        let body = try!(self.parse_function_body(generator, async));
+        Ok(mk!(self, if generator {
+            GeneratorDeclExpr(None, params, box body)
+        } else if async {
+            AsyncFunctionDeclExpr(None, params, box body)
+        } else {
+            FunctionDeclExpr(None, params, box body)
+        }, start))
+    }
+    /// Returns true if the braces opened just before `start` hold an object literal rather than a
+    /// block, which is when they're empty or their first property can't start a statement
+    fn is_object_literal(&self, start:uint) -> bool {
+        let tokens = self.tokens.slice_from(start);
+        let next = tokens.get(1).map(|tk| tk.data.clone()).unwrap_or(TEOF);
+        match tokens.get(0).map(|tk| tk.data.clone()).unwrap_or(TEOF) {
+            TPunctuator(PCloseBlock) | TPunctuator(PSpread) | TPunctuator(PMul) | TPunctuator(POpenBracket) => true,
+            TIdentifier(ref word) => match next {
+                TPunctuator(PColon) | TPunctuator(POpenParen) | TPunctuator(PComma) | TPunctuator(PCloseBlock) => true,
+                // `get`, `set` and `async` are only modifiers when a key follows them
+                TIdentifier(_) | TKeyword(_) | TStringLiteral(_) | TNumericLiteral(_) | TPunctuator(POpenBracket) | TPunctuator(PMul) =>
+                    word.as_slice() == "get" || word.as_slice() == "set" || word.as_slice() == "async",
+                _ => false
+            },
+            TStringLiteral(_) | TNumericLiteral(_) => next == TPunctuator(PColon) || next == TPunctuator(POpenParen),
+            TKeyword(_) => next == TPunctuator(PColon),
+            _ => false
+        }
+    }
+    /// Returns true if the token at `pos` ends the key of a property that isn't a method, getter or
+    /// setter, which means the word before it is the key rather than a modifier
+    fn ends_key(&self, pos:uint) -> bool {
+        match self.tokens.as_slice().get(pos) {
+            Some(&Token {data: TPunctuator(PColon), ..}) | Some(&Token {data: TPunctuator(POpenParen), ..}) |
+            Some(&Token {data: TPunctuator(PComma), ..}) | Some(&Token {data: TPunctuator(PCloseBlock), ..}) => true,
+            _ => false
+        }
+    }
+    /// Parse the properties of an object literal after its open brace, up to and including the close brace
+    fn parse_object(&mut self) -> Result<Vec<PropertyDef>, ParseError> {
+        let mut props = Vec::new();
+        loop {
+            if try!(self.get_token(self.pos)).data == TPunctuator(PCloseBlock) {
+                self.pos += 1;
+                return Ok(props);
+            }
+            props.push(try!(self.parse_property()));
+            let tk = try!(self.get_token(self.pos));
+            self.pos += 1;
+            match tk.data {
+                TPunctuator(PComma) => (),
+                TPunctuator(PCloseBlock) => return Ok(props),
+                _ => return Err(Expected(vec!(TPunctuator(PComma), TPunctuator(PCloseBlock)), tk, "object literal"))
+            }
+        }
+    }
+    /// Parse a property, method, getter, setter or spread in an object literal
+    fn parse_property(&mut self) -> Result<PropertyDef, ParseError> {
+        let first = try!(self.get_token(self.pos));
+        if first.data == TPunctuator(PSpread) {
+            self.pos += 1;
+            return Ok(SpreadProperty(try!(self.parse())));
+        }
+        // `async` is only a modifier when the key of the method follows it on the same line
+        let async = match (first.data.clone(), try!(self.get_token(self.pos + 1))) {
+            (TIdentifier(ref word), ref next) => word.as_slice() == "async" && !next.newline_before && !self.ends_key(self.pos + 1),
+            _ => false
+        };
+        if async {
+            self.pos += 1;
+        }
+        let generator = !async && try!(self.get_token(self.pos)).data == TPunctuator(PMul);
+        if generator {
+            self.pos += 1;
+        }
+        let mut kind = PlainMethod;
+        match try!(self.get_token(self.pos)).data {
+            TIdentifier(ref word) if !async && !generator && !self.ends_key(self.pos + 1) => {
+                kind = match word.as_slice() {
+                    "get" => GetMethod,
+                    "set" => SetMethod,
+                    _ => PlainMethod
+                };
+                if kind != PlainMethod {
+                    self.pos += 1;
+                }
+            },
+            _ => ()
+        }
+        let tk = try!(self.get_token(self.pos));
+        self.pos += 1;
+        try!(self.check_octal(&tk));
+        let key = match tk.data {
+            TIdentifier(ref name) | TStringLiteral(ref name) => NamedKey(name.clone()),
+            TKeyword(ref keyword) => NamedKey(keyword.to_string()),
+            // numeric keys are the string the number converts to, so `1.0` and `1` are the same key
+            TNumericLiteral(num) => NamedKey(f64::to_str_digits(num, 15)),
+            TPunctuator(POpenBracket) => {
+                let key = try!(self.parse());
+                try!(self.expect_punc(PCloseBracket, "computed property key"));
+                ComputedKey(key)
+            },
+            _ => return Err(Expected(vec!(TIdentifier("identifier".into_string()), TStringLiteral("string".into_string()), TPunctuator(POpenBracket)), tk.clone(), "object literal"))
+        };
+        if async || generator || kind != PlainMethod || try!(self.get_token(self.pos)).data == TPunctuator(POpenParen) {
+            let func = try!(self.parse_method(generator, async, first.pos, "method"));
+            return Ok(MethodProperty(key, kind, func));
+        }
+        match (tk.data, try!(self.get_token(self.pos)).data) {
+            (_, TPunctuator(PColon)) => {
+                self.pos += 1;
+                Ok(ValueProperty(key, try!(self.parse())))
+            },
+            // a shorthand property takes its value from the variable with the same name
+            (TIdentifier(ref name), _) => Ok(ValueProperty(key, mk!(self, LocalExpr(name.clone()), tk.pos))),
+            (_, _) => Err(Expected(vec!(TPunctuator(PColon)), try!(self.get_token(self.pos)), "object literal"))
+        }
+    }
+    /// Make the constructor of a class that doesn't have one, which passes its arguments on to
+    /// the parent constructor if the class has a parent
+    fn default_constructor(&self, derived:bool) -> ParseResult {
+        let mut params = Params {args: Vec::new(), rest: None};
+        let mut body = Vec::new();
+        if derived {
+            params.rest = Some(box NamePattern("args".into_string()));
+            let args = mk!(self, LocalExpr("args".into_string()));
+            let parent = mk!(self, SuperExpr);
+            body.push(mk!(self, CallExpr(box parent, vec!(SpreadElement(args)))));
+        }
+        let body = mk!(self, BlockExpr(body));
+        Ok(mk!(self, FunctionDeclExpr(None, params, box body)))
+    }
+    /// Parse an optional clause of a for loop header, which is terminated by `end`
+    fn parse_for_clause(&mut self, end:Punctuator) -> Result<Option<Box<Expr>>, ParseError> {
+        if try!(self.get_token(self.pos)).data == TPunctuator(end) {
+            self.pos += 1;
+            return Ok(None);
+        }
+        let expr = try!(self.parse());
+        try!(self.end_for_clause(end));
+        Ok(Some(box expr))
+    }
+    /// Returns an error if a clause of a for loop header was not terminated by `end`
+    fn end_for_clause(&mut self, end:Punctuator) -> Result<(), ParseError> {
+        self.expect_punc(end, "for loop")
+    }
+    /// Returns true if the statement being parsed can end before the next token, which it can if
+    /// that token is `;` or `}`, the script ends, or there's a new line between them
+    fn can_end_statement(&self) -> bool {
+        match self.tokens.as_slice().get(self.pos) {
+            Some(&Token {data: TPunctuator(PSemicolon), ..}) | Some(&Token {data: TPunctuator(PCloseBlock), ..}) | None => true,
+            Some(tk) => tk.newline_before
+        }
+    }
+    /// Consume the semicolon ending a statement, or make sure one can be inserted if there isn't one
+    fn end_statement(&mut self) -> Result<(), ParseError> {
+        if !self.can_end_statement() {
+            return Err(Expected(vec!(TPunctuator(PSemicolon)), try!(self.get_token(self.pos)), "statement"));
+        }
+        if self.get_token(self.pos).map(|tk| tk.data == TPunctuator(PSemicolon)).unwrap_or(false) {
+            self.pos += 1;
+        }
+        Ok(())
+    }
+    /// Parse a statement, which may be labelled
+    fn parse_statement(&mut self) -> ParseResult {
+        let tk = try!(self.get_token(self.pos));
+        match tk.data {
+            // statements that end with a block or another statement don't take a semicolon, and can't
+            // carry on into an expression
+            TKeyword(keyword @ KIf) | TKeyword(keyword @ KWhile) | TKeyword(keyword @ KFor) | TKeyword(keyword @ KSwitch) |
+            TKeyword(keyword @ KTry) | TKeyword(keyword @ KFunction) | TKeyword(keyword @ KClass) => {
+                self.pos += 1;
+                return self.parse_struct(keyword);
+            },
+            TKeyword(keyword @ KDo) => {
+                self.pos += 1;
+                let expr = try!(self.parse_struct(keyword));
+                // a semicolon is inserted after a do-while loop even without a new line
+                if self.get_token(self.pos).map(|tk| tk.data == TPunctuator(PSemicolon)).unwrap_or(false) {
+                    self.pos += 1;
+                }
+                return Ok(expr);
+            },
+            // `let` is a name in sloppy mode code unless a declaration follows it
+            TKeyword(KLet) if !self.is_let_declaration(self.pos + 1) => (),
+            TKeyword(keyword @ KVar) | TKeyword(keyword @ KLet) | TKeyword(keyword @ KConst) | TKeyword(keyword @ KReturn) |
+            TKeyword(keyword @ KThrow) | TKeyword(keyword @ KBreak) | TKeyword(keyword @ KContinue) => {
+                self.pos += 1;
+                let expr = try!(self.parse_struct(keyword));
+                try!(self.end_statement());
+                return Ok(expr);
+            },
+            TPunctuator(PSemicolon) => {
+                self.pos += 1;
+                return Ok(mk!(self, ConstExpr(CUndefined)));
+            },
+            // a statement starting with `{` is always a block, even if it's empty
+            TPunctuator(POpenBlock) => return self.parse_block(),
+            // an async function declaration doesn't take a semicolon either
+            TIdentifier(ref word) if word.as_slice() == "async" && self.is_async_function(self.pos + 1) => {
+                self.pos += 1;
+                return self.parse_async(tk.pos);
+            },
+            _ => ()
+        }
+        let label = match (self.get_token(self.pos), self.get_token(self.pos + 1)) {
+            (Ok(Token {data: TIdentifier(ref name), ..}), Ok(Token {data: TPunctuator(PColon), ..})) => name.clone(),
+            _ => {
+                let expr = try!(self.parse());
+                try!(self.end_statement());
+                return Ok(expr);
+            }
+        };
+        let first = try!(self.get_token(self.pos));
+        self.pos += 2;
+        // a label on another label labels the statement after both of them
+        let mut statement = self.pos;
+        loop {
+            match (self.get_token(statement), self.get_token(statement + 1)) {
+                (Ok(Token {data: TIdentifier(_), ..}), Ok(Token {data: TPunctuator(PColon), ..})) => statement += 2,
+                _ => break
+            }
+        }
+        let is_loop = match try!(self.get_token(statement)).data {
+            TKeyword(KFor) | TKeyword(KWhile) | TKeyword(KDo) => true,
+            _ => false
+        };
+        self.labels.push((label.clone(), is_loop));
+        let expr = self.parse_statement();
+        self.labels.pop();
+        Ok(mk!(self, LabelledExpr(label, box try!(expr)), first.pos))
+    }
+    /// Parse a block of statements between braces
+    fn parse_block(&mut self) -> ParseResult {
+        let first = try!(self.get_token(self.pos));
+        try!(self.expect_punc(POpenBlock, "block"));
+        let mut exprs = Vec::new();
+        while try!(self.get_token(self.pos)).data != TPunctuator(PCloseBlock) {
+            exprs.push(try!(self.parse_block_statement()));
+        }
+        self.pos += 1;
+        Ok(mk!(self, BlockExpr(exprs), first.pos))
+    }
+    /// Parse the braces around the body of a function, which starts with its directives
+    fn parse_body(&mut self) -> ParseResult {
+        let first = try!(self.get_token(self.pos));
+        try!(self.expect_punc(POpenBlock, "function body"));
+        let mut exprs = try!(self.parse_directives());
+        while try!(self.get_token(self.pos)).data != TPunctuator(PCloseBlock) {
+            exprs.push(try!(self.parse_block_statement()));
+        }
+        self.pos += 1;
+        Ok(mk!(self, BlockExpr(exprs), first.pos))
+    }
+    /// Parse the directive prologue at the start of a script or function body, which is the
+    /// strings that are whole statements on their own before any other statement, and switch on
+    /// strict mode if one of them is `"use strict"`
+    ///
+    /// This also gives the statement after the directives if it starts with a string
+    fn parse_directives(&mut self) -> Result<Vec<Expr>, ParseError> {
+        let mut exprs = Vec::new();
+        loop {
+            let first = match self.tokens.as_slice().get(self.pos) {
+                Some(tk) => tk.clone(),
+                None => return Ok(exprs)
+            };
+            match first.data {
+                TStringLiteral(_) => (),
+                _ => return Ok(exprs)
+            }
+            let statement = try!(self.parse_block_statement());
+            // a string that carries on into an expression, such as `"a" + b`, ends the directives
+            let directive = match statement.def {
+                ConstExpr(CString(ref text)) if statement.start == first.pos && statement.end == first.end => Some(text.clone()),
+                _ => None
+            };
+            match directive {
+                Some(directive) => {
+                    if directive.as_slice() == "use strict" {
+                        self.strict = true;
+                    }
+                    exprs.push(Expr::new(DirectiveExpr(directive), statement.start, statement.end));
+                },
+                None => {
+                    exprs.push(statement);
+                    return Ok(exprs);
+                }
+            }
+        }
+    }
+    /// Returns an error if a number written with a leading zero is in strict mode code
+    fn check_octal(&self, tk:&Token) -> Result<(), ParseError> {
+        if tk.legacy_octal && self.strict {
+            Err(StrictModeViolation("An octal literal".into_string(), tk.pos))
+        } else {
+            Ok(())
+        }
+    }
+    /// Parse the body of a loop
+    fn parse_loop_body(&mut self) -> ParseResult {
+        self.loop_depth += 1;
+        let expr = self.parse_statement();
+        self.loop_depth -= 1;
+        expr
+    }
+    /// Parse the body of a function, which can't break out of the statements enclosing it and can
+    /// only yield if it's the body of a generator or await if it's the body of an async function
+    fn parse_function_body(&mut self, generator:bool, async:bool) -> ParseResult {
+        let labels = replace(&mut self.labels, Vec::new());
+        let loop_depth = replace(&mut self.loop_depth, 0);
+        let switch_depth = replace(&mut self.switch_depth, 0);
+        let no_in = replace(&mut self.no_in, false);
+        let generator = replace(&mut self.generator, generator);
+        let async = replace(&mut self.async, async);
+        // a function inside strict mode code is strict too, even without its own directive
+        let strict = self.strict;
+        // a body in braces is always a block, even if it's empty
+        let expr = match self.get_token(self.pos) {
+            Ok(Token {data: TPunctuator(POpenBlock), ..}) => self.parse_body(),
+            _ => self.parse()
+        };
+        self.strict = strict;
+        self.labels = labels;
+        self.loop_depth = loop_depth;
+        self.switch_depth = switch_depth;
+        self.no_in = no_in;
+        self.generator = generator;
+        self.async = async;
+        expr
+    }
+    /// Returns true if the token at `pos`, which follows `async`, starts an async function on the
+    /// same line, which it does if it's `function`
+    fn is_async_function(&self, pos:uint) -> bool {
+        match self.tokens.as_slice().get(pos) {
+            Some(&Token {data: TKeyword(KFunction), newline_before: false, ..}) => true,
+            _ => false
+        }
+    }
+    /// Returns true if the token at `pos`, which follows `async`, starts an async function or an
+    /// async arrow function on the same line
+    fn is_async_start(&self, pos:uint) -> bool {
+        match self.tokens.as_slice().get(pos) {
+            Some(&Token {data: TPunctuator(POpenParen), newline_before: false, ..}) => self.is_arrow_params(pos + 1),
+            Some(&Token {data: TIdentifier(_), newline_before: false, ..}) => match self.tokens.as_slice().get(pos + 1) {
+                Some(&Token {data: TPunctuator(PArrow), newline_before: false, ..}) => true,
+                _ => false
+            },
+            _ => self.is_async_function(pos)
+        }
+    }
+    /// Returns true if the `let` before `pos` starts a declaration, which it always does in strict
+    /// mode code, and otherwise only does if a name or a pattern follows it
+    fn is_let_declaration(&self, pos:uint) -> bool {
+        self.strict || match self.tokens.as_slice().get(pos) {
+            Some(&Token {data: TIdentifier(_), ..}) | Some(&Token {data: TPunctuator(POpenBracket), ..}) |
+            Some(&Token {data: TPunctuator(POpenBlock), ..}) | Some(&Token {data: TKeyword(KYield), ..}) |
+            Some(&Token {data: TKeyword(KAwait), ..}) | Some(&Token {data: TKeyword(KLet), ..}) => true,
+            _ => false
+        }
+    }
+    /// Parse an async function or async arrow function after its `async`, which starts at `start`
+    fn parse_async(&mut self, start:Position) -> ParseResult {
+        let tk = try!(self.get_token(self.pos));
+        self.pos += 1;
+        match tk.data {
+            TKeyword(KFunction) => {
+                let tk = try!(self.get_token(self.pos));
+                let name = match tk.data {
+                    TIdentifier(ref name) => {
+                        self.pos += 1;
+                        Some(name.clone())
+                    },
+                    TPunctuator(POpenParen) => None,
+                    _ => return Err(Expected(vec!(TIdentifier("identifier".into_string())), tk.clone(), "async function name"))
+                };
+                try!(self.expect_punc(POpenParen, "async function"));
+                let args = try!(self.parse_params());
+                let block = try!(self.parse_function_body(false, true));
+                Ok(mk!(self, AsyncFunctionDeclExpr(name, args, box block), start))
+            },
+            TPunctuator(POpenParen) => {
+                let args = try!(self.parse_params());
+                try!(self.expect_punc(PArrow, "async arrow function"));
+                let expr = try!(self.parse_function_body(false, true));
+                Ok(mk!(self, AsyncArrowFunctionDeclExpr(args, box expr), start))
+            },
+            TIdentifier(ref name) => {
+                try!(self.expect_punc(PArrow, "async arrow function"));
+                let expr = try!(self.parse_function_body(false, true));
+                Ok(mk!(self, AsyncArrowFunctionDeclExpr(Params::from_names(vec!(name.clone())), box expr), start))
+            },
+            _ => Err(Expected(vec!(TKeyword(KFunction), TPunctuator(POpenParen)), tk, "async function"))
         }
     }
     /// Parse a single expression
@@ -227,75 +1171,67 @@ impl Parser {
         }
         let token = try!(self.get_token(self.pos));
         self.pos += 1;
+        try!(self.check_octal(&token));
         let expr : Expr = match token.data {
-            TPunctuator(PSemicolon) | TComment(_) if self.pos < self.tokens.len() => try!(self.parse()),
-            TPunctuator(PSemicolon) | TComment(_) => mk!(self, ConstExpr(CUndefined)),
+            TPunctuator(PSemicolon) if self.pos < self.tokens.len() => try!(self.parse()),
+            TPunctuator(PSemicolon) => mk!(self, ConstExpr(CUndefined)),
+            // integers are kept as integers as long as they fit in one
+            TNumericLiteral(num) if num % 1.0 == 0.0 && num >= i32::MIN as f64 && num <= i32::MAX as f64 =>
+                mk!(self, ConstExpr(CInt(num as i32))),
             TNumericLiteral(num) =>
                 mk!(self, ConstExpr(CNum(num))),
+            TBigIntLiteral(digits) =>
+                mk!(self, ConstExpr(CBigInt(digits))),
             TNullLiteral =>
                 mk!(self, ConstExpr(CNull)),
             TStringLiteral(text) =>
                 mk!(self, ConstExpr(CString(text))),
+            TRegularExpression(body, flags) =>
+                mk!(self, ConstExpr(CRegExp(body, flags))),
+            TNoSubstitutionTemplate(cooked, raw) =>
+                mk!(self, TemplateExpr(None, vec!((cooked, raw)), Vec::new())),
+            TTemplateHead(cooked, raw) => {
+                let (strings, exprs) = try!(self.parse_template(cooked, raw));
+                mk!(self, TemplateExpr(None, strings, exprs), token.pos)
+            },
             TBooleanLiteral(val) =>
                 mk!(self, ConstExpr(CBool(val))),
             TIdentifier(ref s) if s.as_slice() == "undefined" =>
                 mk!(self, ConstExpr(CUndefined)),
+            // `async` is only a modifier when a function follows it on the same line
+            TIdentifier(ref s) if s.as_slice() == "async" && self.is_async_start(self.pos) =>
+                try!(self.parse_async(token.pos)),
             TIdentifier(s) =>
                 mk!(self, LocalExpr(s)),
+            // `yield` is only a keyword inside generators
+            TKeyword(KYield) if !self.generator =>
+                mk!(self, LocalExpr("yield".into_string())),
+            // `await` is only a keyword inside async functions
+            TKeyword(KAwait) if !self.async =>
+                mk!(self, LocalExpr("await".into_string())),
+            // `let` is only a keyword in strict mode code or when it starts a declaration
+            TKeyword(KLet) if !self.is_let_declaration(self.pos) =>
+                mk!(self, LocalExpr("let".into_string())),
             TKeyword(keyword) =>
                 try!(self.parse_struct(keyword)),
+            TPunctuator(POpenParen) if self.is_arrow_params(self.pos) => {
+                let args = try!(self.parse_params());
+                try!(self.expect_punc(PArrow, "arrow function"));
+                let expr = try!(self.parse_function_body(false, false));
+                mk!(self, ArrowFunctionDeclExpr(args, box expr), token.pos)
+            },
             TPunctuator(POpenParen) => {
-                match try!(self.get_token(self.pos)).data {
-                    TPunctuator(PCloseParen) if try!(self.get_token(self.pos + 1)).data == TPunctuator(PArrow) => {
-                        self.pos += 2;
-                        let expr = try!(self.parse());
-                        mk!(self, ArrowFunctionDeclExpr(Vec::new(), box expr), token)
-                    },
-                    _ => {
-                        let next = try!(self.parse());
-                        let next_tok = try!(self.get_token(self.pos));
-                        self.pos += 1;
-                        match next_tok.data {
-                            TPunctuator(PCloseParen) => next,
-                            TPunctuator(PComma) => { // at this point it's probably gonna be an arrow function
-                                let mut args = vec!(match next.def {
-                                    LocalExpr(name) => name,
-                                    _ => "".into_string()
-                                }, match try!(self.get_token(self.pos)).data {
-                                    TIdentifier(ref id) => id.clone(),
-                                    _ => "".into_string()
-                                });
-                                let mut expect_ident = true;
-                                loop {
-                                    self.pos += 1;
-                                    let curr_tk = try!(self.get_token(self.pos));
-                                    match curr_tk.data {
-                                        TIdentifier(ref id) if expect_ident => {
-                                            args.push(id.clone());
-                                            expect_ident = false;
-                                        },
-                                        TPunctuator(PComma) => {
-                                            expect_ident = true;
-                                        },
-                                        TPunctuator(PCloseParen) => {
-                                            self.pos += 1;
-                                            break;
-                                        },
-                                        _ if expect_ident => return Err(Expected(vec!(TIdentifier("identifier".into_string())), curr_tk, "arrow function")),
-                                        _ => return Err(Expected(vec!(TPunctuator(PComma), TPunctuator(PCloseParen)), curr_tk, "arrow function"))
-                                    }
-                                }
-                                try!(self.expect(TPunctuator(PArrow), "arrow function"));
-                                let expr = try!(self.parse());
-                                mk!(self, ArrowFunctionDeclExpr(args, box expr), token)
-                            }
-                            _ => return Err(Expected(vec!(TPunctuator(PCloseParen)), next_tok, "brackets"))
-                        }
-                    }
-                }
+                let no_in = replace(&mut self.no_in, false);
+                let next = self.parse();
+                self.no_in = no_in;
+                let next = try!(next);
+                try!(self.expect_punc(PCloseParen, "brackets"));
+                // the brackets are part of the expression's span, which tells it apart from an
+                // operation without them when combining operators
+                mk!(self, next.def, token.pos)
             },
             TPunctuator(POpenBracket) => {
-                let mut array : Vec<Expr> = Vec::new();
+                let mut array : Vec<Element> = Vec::new();
                 let mut expect_comma_or_end = try!(self.get_token(self.pos)).data == TPunctuator(PCloseBracket);
                 loop {
                     let token = try!(self.get_token(self.pos));
@@ -305,63 +1241,39 @@ impl Parser {
                     } else if token.data == TPunctuator(PComma) && expect_comma_or_end {
                         expect_comma_or_end = false;
                     } else if token.data == TPunctuator(PComma) && !expect_comma_or_end {
-                        array.push(mk!(self, ConstExpr(CNull)));
+                        array.push(ExprElement(mk!(self, ConstExpr(CNull))));
                         expect_comma_or_end = false;
                     } else if expect_comma_or_end {
                         return Err(Expected(vec!(TPunctuator(PComma), TPunctuator(PCloseBracket)), token.clone(), "array declaration"));
                     } else {
-                        let parsed = try!(self.parse());
+                        let parsed = try!(self.parse_element());
                         self.pos -= 1;
                         array.push(parsed);
                         expect_comma_or_end = true;
                     }
                     self.pos += 1;
                 }
-                mk!(self, ArrayDeclExpr(array), token)
-            },
-            TPunctuator(POpenBlock) if try!(self.get_token(self.pos)).data == TPunctuator(PCloseBlock) => {
-                self.pos += 1;
-                mk!(self, ObjectDeclExpr(box TreeMap::new()), token)
+                mk!(self, ArrayDeclExpr(array), token.pos)
             },
-            TPunctuator(POpenBlock) if try!(self.get_token(self.pos + 1)).data == TPunctuator(PColon) => {
-                let mut map = box TreeMap::new();
-                while try!(self.get_token(self.pos - 1)).data == TPunctuator(PComma) || map.len() == 0 {
-                    let tk = try!(self.get_token(self.pos));
-                    let name = match tk.data {
-                        TIdentifier(ref id) => id.clone(),
-                        TStringLiteral(ref str) => str.clone(),
-                        _ => return Err(Expected(vec!(TIdentifier("identifier".into_string()), TStringLiteral("string".into_string())), tk, "object declaration"))
-                    };
-                    self.pos += 1;
-                    try!(self.expect(TPunctuator(PColon), "object declaration"));
-                    let value = try!(self.parse());
-                    map.insert(name, value);
-                    self.pos += 1;
-                }
-                mk!(self, ObjectDeclExpr(map), token)
+            TPunctuator(POpenBlock) if self.is_object_literal(self.pos) => {
+                let props = try!(self.parse_object());
+                mk!(self, ObjectDeclExpr(props), token.pos)
             },
             TPunctuator(POpenBlock) => {
-                let mut exprs = Vec::new();
-                loop {
-                    if try!(self.get_token(self.pos)).data == TPunctuator(PCloseBlock) {
-                        break;
-                    } else {
-                        exprs.push(try!(self.parse()));
-                    }
-                }
-                self.pos += 1;
-                mk!(self, BlockExpr(exprs), token)
+                self.pos -= 1;
+                // a block is a statement, so nothing after it can carry it on as an expression
+                return self.parse_block();
             },
             TPunctuator(PSub) =>
-                mk!(self, UnaryOpExpr(UnaryMinus, box try!(self.parse()))),
+                try!(self.unary_op(UnaryMinus)),
             TPunctuator(PAdd) =>
-                mk!(self, UnaryOpExpr(UnaryPlus, box try!(self.parse()))),
+                try!(self.unary_op(UnaryPlus)),
             TPunctuator(PNot) =>
-                mk!(self, UnaryOpExpr(UnaryNot, box try!(self.parse()))),
+                try!(self.unary_op(UnaryNot)),
             TPunctuator(PInc) =>
-                mk!(self, UnaryOpExpr(UnaryIncrementPre, box try!(self.parse()))),
+                try!(self.unary_op(UnaryIncrementPre)),
             TPunctuator(PDec) =>
-                mk!(self, UnaryOpExpr(UnaryDecrementPre, box try!(self.parse()))),
+                try!(self.unary_op(UnaryDecrementPre)),
             _ => return Err(Expected(Vec::new(), token.clone(), "script"))
         };
         if self.pos >= self.tokens.len() {
@@ -378,6 +1290,7 @@ impl Parser {
         }
     }
     fn parse_next(&mut self, expr:Expr) -> ParseResult {
+        let start = expr.start;
         let next = try!(self.get_token(self.pos));
         let mut carry_on = true;
         let mut result = expr.clone();
@@ -386,7 +1299,7 @@ impl Parser {
                 self.pos += 1;
                 let tk = try!(self.get_token(self.pos));
                 match tk.data {
-                    TIdentifier(ref s) => result = mk!(self, GetConstFieldExpr(box expr, s.to_string())),
+                    TIdentifier(ref s) => result = mk!(self, GetConstFieldExpr(box expr, s.to_string()), start),
                     _ => return Err(Expected(vec!(TIdentifier("identifier".into_string())), tk, "field access"))
                 }
                 self.pos += 1;
@@ -405,44 +1318,51 @@ impl Parser {
                     } else if expect_comma_or_end {
                         return Err(Expected(vec!(TPunctuator(PComma), TPunctuator(PCloseParen)), token, "function call arguments"));
                     } else {
-                        let parsed = try!(self.parse());
+                        let parsed = try!(self.parse_element());
                         self.pos -= 1;
                         args.push(parsed);
                         expect_comma_or_end = true;
                     }
                 }
-                result = mk!(self, CallExpr(box expr, args));
+                result = mk!(self, CallExpr(box expr, args), start);
             },
             TPunctuator(PQuestion) => {
                 self.pos += 1;
                 let if_e = try!(self.parse());
-                try!(self.expect(TPunctuator(PColon), "if expression"));
+                try!(self.expect_punc(PColon, "conditional expression"));
+                // parsing the else branch with `parse` makes `a ? b : c ? d : e` group to the right
                 let else_e = try!(self.parse());
-                result = mk!(self, IfExpr(box expr, box if_e, Some(box else_e)));
+                result = mk!(self, ConditionalExpr(box expr, box if_e, box else_e), start);
+            },
+            TNoSubstitutionTemplate(ref cooked, ref raw) => {
+                self.pos += 1;
+                result = mk!(self, TemplateExpr(Some(box expr), vec!((cooked.clone(), raw.clone())), Vec::new()), start);
+            },
+            TTemplateHead(ref cooked, ref raw) => {
+                self.pos += 1;
+                let (strings, exprs) = try!(self.parse_template(cooked.clone(), raw.clone()));
+                result = mk!(self, TemplateExpr(Some(box expr), strings, exprs), start);
             },
             TPunctuator(POpenBracket) => {
                 self.pos += 1;
                 let index = try!(self.parse());
                 try!(self.expect(TPunctuator(PCloseBracket), "array index"));
-                result = mk!(self, GetFieldExpr(box expr, box index));
-            },
-            TPunctuator(PSemicolon) | TComment(_) => {
-                self.pos += 1;
+                result = mk!(self, GetFieldExpr(box expr, box index), start);
             },
             TPunctuator(PAssign) => {
                 self.pos += 1;
+                let target = try!(self.to_pattern(expr));
                 let next = try!(self.parse());
-                result = mk!(self, AssignExpr(box expr, box next));
+                result = mk!(self, AssignExpr(box target, box next), start);
             },
             TPunctuator(PArrow) => {
                 self.pos += 1;
-                let mut args = Vec::with_capacity(1);
-                match result.def {
-                    LocalExpr(name) => args.push(name),
+                let args = match result.def {
+                    LocalExpr(name) => Params::from_names(vec!(name)),
                     _ => return Err(ExpectedExpr("identifier", result))
-                }
-                let next = try!(self.parse());
-                result = mk!(self, ArrowFunctionDeclExpr(args, box next));
+                };
+                let next = try!(self.parse_function_body(false, false));
+                result = mk!(self, ArrowFunctionDeclExpr(args, box next), start);
             },
             TPunctuator(PAdd) =>
                 result = try!(self.binop(BinNum(OpAdd), expr)),
@@ -454,6 +1374,8 @@ impl Parser {
                 result = try!(self.binop(BinNum(OpDiv), expr)),
             TPunctuator(PMod) =>
                 result = try!(self.binop(BinNum(OpMod), expr)),
+            TPunctuator(PExp) =>
+                result = try!(self.binop(BinNum(OpExp), expr)),
             TPunctuator(PBoolAnd) =>
                 result = try!(self.binop(BinLog(LogAnd), expr)),
             TPunctuator(PBoolOr) =>
@@ -468,6 +1390,32 @@ impl Parser {
                 result = try!(self.binop(BinBit(BitShl), expr)),
             TPunctuator(PRightSh) =>
                 result = try!(self.binop(BinBit(BitShr), expr)),
+            TPunctuator(PURightSh) =>
+                result = try!(self.binop(BinBit(BitUShr), expr)),
+            TPunctuator(PAssignAdd) =>
+                result = try!(self.assign_op(BinNum(OpAdd), expr)),
+            TPunctuator(PAssignSub) =>
+                result = try!(self.assign_op(BinNum(OpSub), expr)),
+            TPunctuator(PAssignMul) =>
+                result = try!(self.assign_op(BinNum(OpMul), expr)),
+            TPunctuator(PAssignDiv) =>
+                result = try!(self.assign_op(BinNum(OpDiv), expr)),
+            TPunctuator(PAssignMod) =>
+                result = try!(self.assign_op(BinNum(OpMod), expr)),
+            TPunctuator(PAssignExp) =>
+                result = try!(self.assign_op(BinNum(OpExp), expr)),
+            TPunctuator(PAssignLeftSh) =>
+                result = try!(self.assign_op(BinBit(BitShl), expr)),
+            TPunctuator(PAssignRightSh) =>
+                result = try!(self.assign_op(BinBit(BitShr), expr)),
+            TPunctuator(PAssignURightSh) =>
+                result = try!(self.assign_op(BinBit(BitUShr), expr)),
+            TPunctuator(PAssignAnd) =>
+                result = try!(self.assign_op(BinBit(BitAnd), expr)),
+            TPunctuator(PAssignOr) =>
+                result = try!(self.assign_op(BinBit(BitOr), expr)),
+            TPunctuator(PAssignXor) =>
+                result = try!(self.assign_op(BinBit(BitXor), expr)),
             TPunctuator(PEq) =>
                 result = try!(self.binop(BinComp(CompEqual), expr)),
             TPunctuator(PNotEq) =>
@@ -484,10 +1432,19 @@ impl Parser {
                 result = try!(self.binop(BinComp(CompGreaterThan), expr)),
             TPunctuator(PGreaterThanOrEq) =>
                 result = try!(self.binop(BinComp(CompGreaterThanOrEqual), expr)),
-            TPunctuator(PInc) =>
-                result = mk!(self, UnaryOpExpr(UnaryIncrementPost, box try!(self.parse()))),
-            TPunctuator(PDec) =>
-                result = mk!(self, UnaryOpExpr(UnaryDecrementPost, box try!(self.parse()))),
+            TKeyword(KIn) if !self.no_in =>
+                result = try!(self.binop(BinComp(CompIn), expr)),
+            TKeyword(KInstanceOf) =>
+                result = try!(self.binop(BinComp(CompInstanceOf), expr)),
+            // a postfix operator has to be on the same line as its operand
+            TPunctuator(PInc) if !next.newline_before => {
+                self.pos += 1;
+                result = try!(self.update_op(UnaryIncrementPost, expr, start, next.end));
+            },
+            TPunctuator(PDec) if !next.newline_before => {
+                self.pos += 1;
+                result = try!(self.update_op(UnaryDecrementPost, expr, start, next.end));
+            },
             _ => carry_on = false
         };
         if carry_on && self.pos < self.tokens.len() {
@@ -496,22 +1453,298 @@ impl Parser {
             Ok(result)
         }
     }
+    /// Returns true if the brackets opened just before `start` hold the parameters of an arrow
+    /// function, which is when the token after the matching close bracket is `=>`
+    fn is_arrow_params(&self, start:uint) -> bool {
+        let mut depth = 1u;
+        let tokens = self.tokens.slice_from(start);
+        for (i, tk) in tokens.iter().enumerate() {
+            match tk.data {
+                TPunctuator(POpenParen) | TPunctuator(POpenBracket) | TPunctuator(POpenBlock) => depth += 1,
+                TPunctuator(PCloseParen) | TPunctuator(PCloseBracket) | TPunctuator(PCloseBlock) => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return match tokens.get(i + 1) {
+                            Some(&Token {data: TPunctuator(PArrow), ..}) => true,
+                            _ => false
+                        };
+                    }
+                },
+                _ => ()
+            }
+        }
+        false
+    }
+    /// Parse the parameters of a function after its open bracket, up to and including the close bracket
+    fn parse_params(&mut self) -> Result<Params, ParseError> {
+        let mut params = Params {args: Vec::new(), rest: None};
+        if try!(self.get_token(self.pos)).data == TPunctuator(PCloseParen) {
+            self.pos += 1;
+            return Ok(params);
+        }
+        loop {
+            if try!(self.get_token(self.pos)).data == TPunctuator(PSpread) {
+                self.pos += 1;
+                params.rest = try!(self.parse_rest(PCloseParen, "function parameters"));
+                return Ok(params);
+            }
+            let pattern = try!(self.parse_pattern());
+            let default = try!(self.parse_default());
+            params.args.push((pattern, default));
+            let tk = try!(self.get_token(self.pos));
+            self.pos += 1;
+            match tk.data {
+                TPunctuator(PComma) => (),
+                TPunctuator(PCloseParen) => return Ok(params),
+                _ => return Err(Expected(vec!(TPunctuator(PComma), TPunctuator(PCloseParen)), tk, "function parameters"))
+            }
+        }
+    }
+    /// Parse an argument of a call or an item of an array literal, which can be spread
+    fn parse_element(&mut self) -> Result<Element, ParseError> {
+        if try!(self.get_token(self.pos)).data == TPunctuator(PSpread) {
+            self.pos += 1;
+            Ok(SpreadElement(try!(self.parse())))
+        } else {
+            Ok(ExprElement(try!(self.parse())))
+        }
+    }
+    /// Parse a default value if there is one, for a part of a pattern
+    fn parse_default(&mut self) -> Result<Option<Expr>, ParseError> {
+        if try!(self.get_token(self.pos)).data == TPunctuator(PAssign) {
+            self.pos += 1;
+            Ok(Some(try!(self.parse())))
+        } else {
+            Ok(None)
+        }
+    }
+    /// Parse the rest element of a pattern after its `...`, which has to be the last element
+    fn parse_rest(&mut self, end:Punctuator, routine:&'static str) -> Result<Option<Box<Pattern>>, ParseError> {
+        let rest = try!(self.parse_pattern());
+        try!(self.expect_punc(end, routine));
+        Ok(Some(box rest))
+    }
+    /// Parse a pattern that a value is destructured into
+    fn parse_pattern(&mut self) -> Result<Pattern, ParseError> {
+        let tk = try!(self.get_token(self.pos));
+        self.pos += 1;
+        match tk.data {
+            TIdentifier(ref name) => Ok(NamePattern(name.clone())),
+            // `yield` and `await` are only keywords inside generators and async functions
+            TKeyword(KYield) if !self.generator => Ok(NamePattern("yield".into_string())),
+            TKeyword(KAwait) if !self.async => Ok(NamePattern("await".into_string())),
+            TKeyword(KLet) if !self.strict => Ok(NamePattern("let".into_string())),
+            TPunctuator(POpenBracket) => {
+                let mut items = Vec::new();
+                let mut rest = None;
+                loop {
+                    let tk = try!(self.get_token(self.pos));
+                    match tk.data {
+                        TPunctuator(PCloseBracket) => {
+                            self.pos += 1;
+                            break;
+                        },
+                        TPunctuator(PComma) => {
+                            self.pos += 1;
+                            items.push(None);
+                            continue;
+                        },
+                        TPunctuator(PSpread) => {
+                            self.pos += 1;
+                            rest = try!(self.parse_rest(PCloseBracket, "array pattern"));
+                            break;
+                        },
+                        _ => ()
+                    }
+                    let pattern = try!(self.parse_pattern());
+                    let default = try!(self.parse_default());
+                    items.push(Some((pattern, default)));
+                    let tk = try!(self.get_token(self.pos));
+                    self.pos += 1;
+                    match tk.data {
+                        TPunctuator(PComma) => (),
+                        TPunctuator(PCloseBracket) => break,
+                        _ => return Err(Expected(vec!(TPunctuator(PComma), TPunctuator(PCloseBracket)), tk, "array pattern"))
+                    }
+                }
+                Ok(ArrayPattern(items, rest))
+            },
+            TPunctuator(POpenBlock) => {
+                let mut fields = Vec::new();
+                let mut rest = None;
+                loop {
+                    let tk = try!(self.get_token(self.pos));
+                    self.pos += 1;
+                    let (key, shorthand) = match tk.data {
+                        TPunctuator(PCloseBlock) => break,
+                        TPunctuator(PSpread) => {
+                            rest = try!(self.parse_rest(PCloseBlock, "object pattern"));
+                            break;
+                        },
+                        TIdentifier(ref key) => (key.clone(), true),
+                        TStringLiteral(ref key) => (key.clone(), false),
+                        _ => return Err(Expected(vec!(TIdentifier("identifier".into_string()), TStringLiteral("string".into_string()), TPunctuator(PCloseBlock)), tk.clone(), "object pattern"))
+                    };
+                    let pattern = if try!(self.get_token(self.pos)).data == TPunctuator(PColon) {
+                        self.pos += 1;
+                        try!(self.parse_pattern())
+                    } else if shorthand {
+                        NamePattern(key.clone())
+                    } else {
+                        return Err(Expected(vec!(TPunctuator(PColon)), try!(self.get_token(self.pos)), "object pattern"));
+                    };
+                    let default = try!(self.parse_default());
+                    fields.push((key, pattern, default));
+                    let tk = try!(self.get_token(self.pos));
+                    self.pos += 1;
+                    match tk.data {
+                        TPunctuator(PComma) => (),
+                        TPunctuator(PCloseBlock) => break,
+                        _ => return Err(Expected(vec!(TPunctuator(PComma), TPunctuator(PCloseBlock)), tk, "object pattern"))
+                    }
+                }
+                Ok(ObjectPattern(fields, rest))
+            },
+            _ => Err(Expected(vec!(TIdentifier("identifier".into_string()), TPunctuator(POpenBracket), TPunctuator(POpenBlock)), tk.clone(), "pattern"))
+        }
+    }
+    /// Turn an expression that is being assigned to into the pattern it is written like
+    fn to_pattern(&self, expr:Expr) -> Result<Pattern, ParseError> {
+        match expr.def {
+            LocalExpr(name) => Ok(NamePattern(name)),
+            GetConstFieldExpr(_, _) | GetFieldExpr(_, _) => Ok(ExprPattern(box expr)),
+            ArrayDeclExpr(items) => {
+                let mut patterns = Vec::with_capacity(items.len());
+                let mut rest = None;
+                let count = items.len();
+                for (i, item) in items.move_iter().enumerate() {
+                    match item {
+                        // holes are parsed as nulls
+                        ExprElement(Expr {def: ConstExpr(CNull), ..}) => patterns.push(None),
+                        ExprElement(item) => patterns.push(Some(try!(self.to_default_pattern(item)))),
+                        SpreadElement(item) if i == count - 1 => rest = Some(box try!(self.to_pattern(item))),
+                        SpreadElement(item) => return Err(ExpectedExpr("assignment target", item))
+                    }
+                }
+                Ok(ArrayPattern(patterns, rest))
+            },
+            ObjectDeclExpr(props) => {
+                let mut patterns = Vec::with_capacity(props.len());
+                let mut rest = None;
+                let count = props.len();
+                for (i, prop) in props.move_iter().enumerate() {
+                    match prop {
+                        ValueProperty(NamedKey(key), value) => {
+                            let (pattern, default) = try!(self.to_default_pattern(value));
+                            patterns.push((key, pattern, default));
+                        },
+                        SpreadProperty(value) if i == count - 1 => rest = Some(box try!(self.to_pattern(value))),
+                        // computed keys, methods and spreads before the end have no pattern they're written like
+                        ValueProperty(_, value) | MethodProperty(_, _, value) | SpreadProperty(value) =>
+                            return Err(ExpectedExpr("assignment target", value))
+                    }
+                }
+                Ok(ObjectPattern(patterns, rest))
+            },
+            _ => Err(ExpectedExpr("assignment target", expr))
+        }
+    }
+    /// Turn an expression inside an assignment pattern into a pattern and its default value
+    fn to_default_pattern(&self, expr:Expr) -> Result<(Pattern, Option<Expr>), ParseError> {
+        match expr.def {
+            AssignExpr(box target, box default) => Ok((target, Some(default))),
+            _ => Ok((try!(self.to_pattern(expr)), None))
+        }
+    }
+    /// Parse the substitutions and strings of a template literal after its head
+    fn parse_template(&mut self, cooked:String, raw:String) -> Result<(Vec<(String, String)>, Vec<Expr>), ParseError> {
+        let mut strings = vec!((cooked, raw));
+        let mut exprs = Vec::new();
+        loop {
+            exprs.push(try!(self.parse()));
+            let tk = try!(self.get_token(self.pos));
+            self.pos += 1;
+            match tk.data {
+                TTemplateMiddle(cooked, raw) => strings.push((cooked, raw)),
+                TTemplateTail(cooked, raw) => {
+                    strings.push((cooked, raw));
+                    return Ok((strings, exprs));
+                },
+                _ => return Err(Expected(vec!(TTemplateTail("".into_string(), "".into_string())), tk, "template literal"))
+            }
+        }
+    }
     fn binop(&mut self, op:BinOp, orig:Expr) -> Result<Expr, ParseError> {
-        let (precedence, assoc) = op.get_precedence_and_assoc();
         self.pos += 1;
         let next = try!(self.parse());
+        self.combine_binop(op, orig, next)
+    }
+    /// Parse the value of a compound assignment such as `a += b`
+    fn assign_op(&mut self, op:BinOp, target:Expr) -> ParseResult {
+        self.pos += 1;
+        let pattern = match target.def {
+            LocalExpr(_) | GetConstFieldExpr(_, _) | GetFieldExpr(_, _) => try!(self.to_pattern(target.clone())),
+            _ => return Err(ExpectedExpr("assignment target", target))
+        };
+        let start = target.start;
+        let value = try!(self.parse());
+        Ok(mk!(self, AssignOpExpr(op, box pattern, box value), start))
+    }
+    /// Combine the left operand of a binary operator with the expression parsed after it,
+    /// regrouping them if that expression binds more loosely than the operator
+    ///
+    /// An expression in brackets starts before its first operand, so it is never regrouped
+    fn combine_binop(&self, op:BinOp, orig:Expr, next:Expr) -> Result<Expr, ParseError> {
+        let (precedence, assoc) = op.get_precedence_and_assoc();
+        let (start, end) = (orig.start, next.end);
         Ok(match next.def {
-            BinOpExpr(ref op2, ref a, ref b) => {
-                let other_precedence = op2.get_precedence();
-                if precedence < other_precedence || (precedence == other_precedence && !assoc) {
-                    mk!(self, BinOpExpr(*op2, b.clone(), box mk!(self, BinOpExpr(op.clone(), box orig, a.clone()))))
-                } else {
-                    mk!(self, BinOpExpr(op, box orig, box next.clone()))
-                }
-            },
-            _ => mk!(self, BinOpExpr(op, box orig, box next))
+            // a conditional binds more loosely than any binary operator, so the operator belongs to its condition
+            ConditionalExpr(box ref cond, ref if_e, ref else_e) if cond.start == next.start =>
+                Expr::new(ConditionalExpr(box try!(self.combine_binop(op, orig, cond.clone())), if_e.clone(), else_e.clone()), start, end),
+            BinOpExpr(op2, box ref a, ref b) if a.start == next.start && (precedence < op2.get_precedence() || (precedence == op2.get_precedence() && !assoc)) =>
+                Expr::new(BinOpExpr(op2, box try!(self.combine_binop(op, orig, a.clone())), b.clone()), start, end),
+            _ => Expr::new(BinOpExpr(op, box orig, box next), start, end)
         })
     }
+    /// Parse the operand of a prefix operator, which binds more tightly than any binary or conditional
+    /// operator after the operand
+    fn unary_op(&mut self, op:UnaryOp) -> ParseResult {
+        let start = try!(self.get_token(self.pos - 1)).pos;
+        let next = try!(self.parse());
+        self.combine_unary_op(op, start, next)
+    }
+    /// Apply a prefix operator starting at `start` to the expression parsed after it
+    fn combine_unary_op(&self, op:UnaryOp, start:Position, next:Expr) -> ParseResult {
+        let end = next.end;
+        Ok(match next.def {
+            BinOpExpr(op2, box ref a, ref b) if a.start == next.start =>
+                Expr::new(BinOpExpr(op2, box try!(self.combine_unary_op(op, start, a.clone())), b.clone()), start, end),
+            ConditionalExpr(box ref cond, ref if_e, ref else_e) if cond.start == next.start =>
+                Expr::new(ConditionalExpr(box try!(self.combine_unary_op(op, start, cond.clone())), if_e.clone(), else_e.clone()), start, end),
+            _ if op == UnaryIncrementPre || op == UnaryDecrementPre => try!(self.update_op(op, next, start, end)),
+            _ => Expr::new(UnaryOpExpr(op, box next), start, end)
+        })
+    }
+    /// Apply `await` starting at `start` to the expression parsed after it, which binds as
+    /// tightly as a prefix operator
+    fn combine_await(&self, start:Position, next:Expr) -> ParseResult {
+        let end = next.end;
+        Ok(match next.def {
+            BinOpExpr(op, box ref a, ref b) if a.start == next.start =>
+                Expr::new(BinOpExpr(op, box try!(self.combine_await(start, a.clone())), b.clone()), start, end),
+            ConditionalExpr(box ref cond, ref if_e, ref else_e) if cond.start == next.start =>
+                Expr::new(ConditionalExpr(box try!(self.combine_await(start, cond.clone())), if_e.clone(), else_e.clone()), start, end),
+            _ => Expr::new(AwaitExpr(box next), start, end)
+        })
+    }
+    /// Make an increment or decrement of `target` spanning from `start` to `end`, where the target
+    /// has to be a local or field
+    fn update_op(&self, op:UnaryOp, target:Expr, start:Position, end:Position) -> ParseResult {
+        match target.def {
+            LocalExpr(_) | GetConstFieldExpr(_, _) | GetFieldExpr(_, _) => Ok(Expr::new(UnaryOpExpr(op, box target), start, end)),
+            _ => Err(ExpectedExpr("increment or decrement target", target))
+        }
+    }
     /// Returns an error if the next symbol is not `tk`
     fn expect(&mut self, tk:TokenData, routine:&'static str) -> Result<(), ParseError> {
         self.pos += 1;
@@ -527,4 +1760,69 @@ impl Parser {
     fn expect_punc(&mut self, p:Punctuator, routine:&'static str) -> Result<(), ParseError> {
         self.expect(TPunctuator(p), routine)
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use syntax::ast::expr::*;
+    use syntax::ast::pos::Position;
+    use syntax::error::{parse_str, parse_str_recovering, ParseFailure};
+    /// Get the positions an expression starts and ends at
+    fn span(expr:&Expr) -> (Position, Position) {
+        (expr.start, expr.end)
+    }
+    /// Returns true if an expression replaces a statement that couldn't be parsed
+    fn is_error(expr:&Expr) -> bool {
+        match expr.def {
+            ErrorExpr(_) => true,
+            _ => false
+        }
+    }
+    #[test]
+    fn nested_spans() {
+        let script = parse_str("/* one\ntwo */ foo(`a\nb`, 1 + 2);").unwrap();
+        assert!(span(&script) == (Position::new(2, 8, 14), Position::new(3, 12, 32)));
+        let call = match script.def {
+            BlockExpr(ref exprs) => exprs[0].clone(),
+            _ => fail!("A script should parse to a block")
+        };
+        assert!(span(&call) == (Position::new(2, 8, 14), Position::new(3, 11, 31)));
+        let args = match call.def {
+            CallExpr(_, ref args) => args.clone(),
+            _ => fail!("`foo(...)` should parse to a call")
+        };
+        match args.as_slice() {
+            [ExprElement(ref template), ExprElement(ref sum)] => {
+                assert!(span(template) == (Position::new(2, 12, 18), Position::new(3, 3, 23)));
+                // the token after the template starts on the template's last line
+                assert!(span(sum) == (Position::new(3, 5, 25), Position::new(3, 10, 30)));
+            },
+            _ => fail!("The call should have two arguments")
+        }
+    }
+    #[test]
+    fn recover_from_two_errors() {
+        let source = "a b;\nvar ok = 1;\nc d;\nok;";
+        let (script, diagnostics) = parse_str_recovering(source);
+        assert_eq!(diagnostics.len(), 2);
+        assert!((diagnostics[0].start, diagnostics[0].end) == (Position::new(1, 3, 2), Position::new(1, 4, 3)));
+        assert!((diagnostics[1].start, diagnostics[1].end) == (Position::new(3, 3, 19), Position::new(3, 4, 20)));
+        match parse_str(source) {
+            Err(ParseFailure(ref err, pos)) => {
+                assert_eq!(err.message(), diagnostics[0].message);
+                assert!(pos == diagnostics[0].start);
+            },
+            _ => fail!("The script should fail to parse")
+        }
+        // each error replaces the statement it's in, and the statement after it is still parsed
+        let statements = match script.def {
+            BlockExpr(ref exprs) => exprs.clone(),
+            _ => fail!("A script should parse to a block")
+        };
+        assert_eq!(statements.len(), 4);
+        assert!(is_error(&statements[0]) && is_error(&statements[2]));
+        assert!(!is_error(&statements[1]));
+        assert!(span(&statements[0]) == (Position::new(1, 1, 0), Position::new(1, 5, 4)));
+        assert!(span(&statements[2]) == (Position::new(3, 1, 17), Position::new(3, 5, 21)));
+        assert!(statements[3].def == LocalExpr("ok".into_string()));
+    }
+}
@@ -0,0 +1,696 @@
+use std::char::from_u32;
+use std::num::from_str_radix;
+
+#[deriving(Clone, PartialEq)]
+/// An item in a character class such as `[a-z\d]`
+enum ClassItem {
+    /// A range of characters, which is a single character when both ends are equal
+    ClassRange(char, char),
+    /// `\d` or `\D` when negated
+    ClassDigit(bool),
+    /// `\w` or `\W` when negated
+    ClassWord(bool),
+    /// `\s` or `\S` when negated
+    ClassSpace(bool)
+}
+#[deriving(Clone, PartialEq)]
+/// A parsed regular expression
+enum Node {
+    /// Match nothing, which always succeeds
+    NEmpty,
+    /// Match a single character
+    NChar(char),
+    /// `.` - Match any character except line terminators unless the `s` flag is set
+    NAny,
+    /// Match any character in (or not in, when negated) a character class
+    NClass(Vec<ClassItem>, bool),
+    /// `^` - Match the start of the input, or a line with the `m` flag
+    NStart,
+    /// `$` - Match the end of the input, or a line with the `m` flag
+    NEnd,
+    /// `\b` or `\B` when negated - Match a word boundary
+    NWordBoundary(bool),
+    /// Match each node in turn
+    NConcat(Vec<Node>),
+    /// Match the first of the nodes that matches
+    NAlt(Vec<Node>),
+    /// A group, which captures into the given group index if it is capturing
+    NGroup(Box<Node>, Option<uint>),
+    /// Match a node between a minimum and optional maximum number of times, greedily or lazily
+    NRepeat(Box<Node>, uint, Option<uint>, bool),
+    /// Match the same text as a previous group
+    NBackRef(uint),
+    /// Match the same text as a previous named group, resolved once all groups are known
+    NNamedBackRef(String),
+    /// `(?=...)` or `(?!...)` when negated - Check if a node matches without consuming it
+    NLookAhead(Box<Node>, bool)
+}
+#[deriving(Clone, PartialEq)]
+/// An instruction for the backtracking matcher
+enum Inst {
+    IChar(char),
+    IAny,
+    IClass(Vec<ClassItem>, bool),
+    IStart,
+    IEnd,
+    IWordBoundary(bool),
+    /// Try the first branch, backtracking to the second if it fails
+    ISplit(uint, uint),
+    IJump(uint),
+    /// Save the position into a slot
+    ISave(uint),
+    IBackRef(uint),
+    /// Run the following program up to its `IMatch` as a lookahead, then continue at the index
+    ILookAhead(bool, uint),
+    /// Save the position into a slot, so that `ICheckProgress` can stop empty loops
+    IMark(uint),
+    /// Fail if the position hasn't moved since the slot was marked
+    ICheckProgress(uint),
+    IMatch
+}
+/// The flags a regular expression was created with
+#[deriving(Clone, PartialEq)]
+pub struct RegExpFlags {
+    /// `g` - Find every match rather than just the first
+    pub global: bool,
+    /// `i` - Match letters regardless of case
+    pub ignore_case: bool,
+    /// `m` - Make `^` and `$` match at line terminators
+    pub multiline: bool,
+    /// `s` - Make `.` match line terminators
+    pub dot_all: bool,
+    /// `u` - Treat the pattern as a sequence of code points
+    pub unicode: bool,
+    /// `y` - Only match at `lastIndex`
+    pub sticky: bool
+}
+impl RegExpFlags {
+    /// Parse flags from a string such as `"gi"`
+    pub fn parse(flags:&str) -> Result<RegExpFlags, String> {
+        let mut parsed = RegExpFlags {
+            global: false,
+            ignore_case: false,
+            multiline: false,
+            dot_all: false,
+            unicode: false,
+            sticky: false
+        };
+        for ch in flags.chars() {
+            let flag = match ch {
+                'g' => &mut parsed.global,
+                'i' => &mut parsed.ignore_case,
+                'm' => &mut parsed.multiline,
+                's' => &mut parsed.dot_all,
+                'u' => &mut parsed.unicode,
+                'y' => &mut parsed.sticky,
+                _ => return Err(format!("Invalid regular expression flag `{}`", ch))
+            };
+            if *flag {
+                return Err(format!("Duplicate regular expression flag `{}`", ch));
+            }
+            *flag = true;
+        }
+        Ok(parsed)
+    }
+}
+/// A successful match of a regular expression
+pub struct Match {
+    /// The start and end of each group, where the whole match is group 0
+    pub groups: Vec<Option<(uint, uint)>>
+}
+impl Match {
+    /// The index the match starts at
+    pub fn start(&self) -> uint {
+        self.groups[0].unwrap().val0()
+    }
+    /// The index the match ends at
+    pub fn end(&self) -> uint {
+        self.groups[0].unwrap().val1()
+    }
+    /// Get the text of a group, if it participated in the match
+    pub fn group(&self, input:&[char], index:uint) -> Option<String> {
+        match self.groups[index] {
+            Some((start, end)) => Some(String::from_chars(input.slice(start, end))),
+            None => None
+        }
+    }
+}
+#[deriving(Clone)]
+/// A compiled regular expression
+pub struct RegExp {
+    /// The source text of the pattern
+    pub source: String,
+    /// The flags of the pattern
+    pub flags: RegExpFlags,
+    /// The names of the named groups and their group indices
+    pub names: Vec<(String, uint)>,
+    /// How many capturing groups there are, including the whole match
+    pub group_count: uint,
+    insts: Vec<Inst>,
+    slot_count: uint
+}
+impl RegExp {
+    /// Parse and compile a regular expression
+    pub fn new(source:&str, flags:&str) -> Result<RegExp, String> {
+        let flags = try!(RegExpFlags::parse(flags));
+        let mut parser = PatternParser {
+            chars: source.chars().collect(),
+            pos: 0,
+            group_count: 1,
+            names: Vec::new()
+        };
+        let node = try!(parser.parse_alt());
+        if parser.pos < parser.chars.len() {
+            return Err(format!("Unmatched `{}` in regular expression", parser.chars[parser.pos]));
+        }
+        let node = try!(parser.resolve_names(node));
+        let mut compiler = PatternCompiler {
+            insts: Vec::new(),
+            mark_base: parser.group_count * 2,
+            mark_count: 0
+        };
+        compiler.insts.push(ISave(0));
+        compiler.emit(&node);
+        compiler.insts.push(ISave(1));
+        compiler.insts.push(IMatch);
+        let group_count = parser.group_count;
+        Ok(RegExp {
+            source: source.into_string(),
+            flags: flags,
+            names: parser.names,
+            group_count: group_count,
+            insts: compiler.insts,
+            slot_count: group_count * 2 + compiler.mark_count
+        })
+    }
+    /// Find the first match starting at or after `start`, or only at `start` when sticky
+    pub fn match_from(&self, input:&[char], start:uint) -> Option<Match> {
+        let mut pos = start;
+        while pos <= input.len() {
+            let slots = Vec::from_elem(self.slot_count, None);
+            match self.run(input, 0, pos, slots) {
+                Some(slots) => return Some(Match {
+                    groups: range(0, self.group_count).map(|i| match (slots[i * 2], slots[i * 2 + 1]) {
+                        (Some(start), Some(end)) => Some((start, end)),
+                        _ => None
+                    }).collect()
+                }),
+                None if self.flags.sticky => return None,
+                None => pos += 1
+            }
+        }
+        None
+    }
+    /// Run the program from `pc` at `pos`, returning the slots if it reaches a match
+    fn run(&self, input:&[char], pc:uint, pos:uint, slots:Vec<Option<uint>>) -> Option<Vec<Option<uint>>> {
+        let mut stack = vec!((pc, pos, slots));
+        loop {
+            let (mut pc, mut pos, mut slots) = match stack.pop() {
+                Some(thread) => thread,
+                None => return None
+            };
+            loop {
+                let matched = match self.insts[pc] {
+                    IChar(ch) =>
+                        pos < input.len() && self.chars_eq(input[pos], ch) && { pos += 1; true },
+                    IAny =>
+                        pos < input.len() && (self.flags.dot_all || !is_line_terminator(input[pos])) && { pos += 1; true },
+                    IClass(ref items, negated) =>
+                        pos < input.len() && self.class_matches(items.as_slice(), input[pos]) != negated && { pos += 1; true },
+                    IStart =>
+                        pos == 0 || (self.flags.multiline && is_line_terminator(input[pos - 1])),
+                    IEnd =>
+                        pos == input.len() || (self.flags.multiline && is_line_terminator(input[pos])),
+                    IWordBoundary(negated) => {
+                        let before = pos > 0 && is_word_char(input[pos - 1]);
+                        let after = pos < input.len() && is_word_char(input[pos]);
+                        (before != after) != negated
+                    },
+                    ISplit(first, second) => {
+                        stack.push((second, pos, slots.clone()));
+                        pc = first;
+                        continue;
+                    },
+                    IJump(to) => {
+                        pc = to;
+                        continue;
+                    },
+                    ISave(slot) | IMark(slot) => {
+                        *slots.get_mut(slot) = Some(pos);
+                        true
+                    },
+                    ICheckProgress(slot) =>
+                        slots[slot] != Some(pos),
+                    IBackRef(group) => match (slots[group * 2], slots[group * 2 + 1]) {
+                        (Some(start), Some(end)) => {
+                            let len = end - start;
+                            let matches = pos + len <= input.len() && range(0, len).all(|i| self.chars_eq(input[pos + i], input[start + i]));
+                            matches && { pos += len; true }
+                        },
+                        // a group that hasn't participated matches the empty string
+                        _ => true
+                    },
+                    ILookAhead(negated, next) => {
+                        match self.run(input, pc + 1, pos, slots.clone()) {
+                            Some(look_slots) if !negated => {
+                                slots = look_slots;
+                                pc = next;
+                                continue;
+                            },
+                            None if negated => {
+                                pc = next;
+                                continue;
+                            },
+                            _ => false
+                        }
+                    },
+                    IMatch => return Some(slots)
+                };
+                if !matched {
+                    break;
+                }
+                pc += 1;
+            }
+        }
+    }
+    fn chars_eq(&self, a:char, b:char) -> bool {
+        a == b || (self.flags.ignore_case && a.to_lowercase() == b.to_lowercase())
+    }
+    fn class_matches(&self, items:&[ClassItem], ch:char) -> bool {
+        items.iter().any(|item| match *item {
+            ClassRange(start, end) => (ch >= start && ch <= end) || (self.flags.ignore_case && {
+                let lower = ch.to_lowercase();
+                let upper = ch.to_uppercase();
+                (lower >= start && lower <= end) || (upper >= start && upper <= end)
+            }),
+            ClassDigit(negated) => ch.is_digit_radix(10) != negated,
+            ClassWord(negated) => is_word_char(ch) != negated,
+            ClassSpace(negated) => (ch.is_whitespace() || ch == '\ufeff') != negated
+        })
+    }
+}
+fn is_line_terminator(ch:char) -> bool {
+    ch == '\n' || ch == '\r' || ch == '\u2028' || ch == '\u2029'
+}
+fn is_word_char(ch:char) -> bool {
+    (ch >= 'a' && ch <= 'z') || (ch >= 'A' && ch <= 'Z') || (ch >= '0' && ch <= '9') || ch == '_'
+}
+/// Parses the source of a regular expression into nodes
+struct PatternParser {
+    chars: Vec<char>,
+    pos: uint,
+    group_count: uint,
+    names: Vec<(String, uint)>
+}
+impl PatternParser {
+    fn peek(&self) -> Option<char> {
+        if self.pos < self.chars.len() {
+            Some(self.chars[self.pos])
+        } else {
+            None
+        }
+    }
+    fn next(&mut self) -> Result<char, String> {
+        match self.peek() {
+            Some(ch) => {
+                self.pos += 1;
+                Ok(ch)
+            },
+            None => Err("Unexpected end of regular expression".into_string())
+        }
+    }
+    fn eat(&mut self, ch:char) -> bool {
+        let result = self.peek() == Some(ch);
+        if result {
+            self.pos += 1;
+        }
+        result
+    }
+    fn parse_alt(&mut self) -> Result<Node, String> {
+        let mut alts = vec!(try!(self.parse_concat()));
+        while self.eat('|') {
+            alts.push(try!(self.parse_concat()));
+        }
+        Ok(if alts.len() == 1 {
+            alts.pop().unwrap()
+        } else {
+            NAlt(alts)
+        })
+    }
+    fn parse_concat(&mut self) -> Result<Node, String> {
+        let mut nodes = Vec::new();
+        loop {
+            match self.peek() {
+                None | Some('|') | Some(')') => break,
+                _ => nodes.push(try!(self.parse_term()))
+            }
+        }
+        Ok(match nodes.len() {
+            0 => NEmpty,
+            1 => nodes.pop().unwrap(),
+            _ => NConcat(nodes)
+        })
+    }
+    fn parse_term(&mut self) -> Result<Node, String> {
+        let atom = try!(self.parse_atom());
+        let (min, max) = match self.peek() {
+            Some('*') => (0, None),
+            Some('+') => (1, None),
+            Some('?') => (0, Some(1)),
+            Some('{') => match try!(self.parse_braces()) {
+                Some(bounds) => bounds,
+                None => return Ok(atom)
+            },
+            _ => return Ok(atom)
+        };
+        // `parse_braces` leaves the position on the closing brace
+        self.pos += 1;
+        match atom {
+            NStart | NEnd | NWordBoundary(_) | NLookAhead(_, _) => return Err("Nothing to repeat in regular expression".into_string()),
+            _ => ()
+        }
+        let greedy = !self.eat('?');
+        Ok(NRepeat(box atom, min, max, greedy))
+    }
+    /// Parse `{n}`, `{n,}` or `{n,m}`, leaving `{` as a literal if it isn't a valid quantifier
+    fn parse_braces(&mut self) -> Result<Option<(uint, Option<uint>)>, String> {
+        let start = self.pos;
+        self.pos += 1;
+        let min = self.parse_decimal();
+        let bounds = match min {
+            Some(min) if self.eat('}') => Some((min, Some(min))),
+            Some(min) if self.eat(',') => {
+                let max = self.parse_decimal();
+                if self.eat('}') {
+                    Some((min, max))
+                } else {
+                    None
+                }
+            },
+            _ => None
+        };
+        match bounds {
+            Some((min, Some(max))) if max < min => Err("Numbers out of order in regular expression quantifier".into_string()),
+            Some(bounds) => {
+                self.pos -= 1;
+                Ok(Some(bounds))
+            },
+            None => {
+                self.pos = start;
+                Ok(None)
+            }
+        }
+    }
+    fn parse_decimal(&mut self) -> Option<uint> {
+        let start = self.pos;
+        while self.peek().map_or(false, |ch| ch.is_digit_radix(10)) {
+            self.pos += 1;
+        }
+        if start == self.pos {
+            None
+        } else {
+            from_str(String::from_chars(self.chars.slice(start, self.pos)).as_slice())
+        }
+    }
+    fn parse_atom(&mut self) -> Result<Node, String> {
+        match try!(self.next()) {
+            '.' => Ok(NAny),
+            '^' => Ok(NStart),
+            '$' => Ok(NEnd),
+            '(' => {
+                let node = if self.eat('?') {
+                    match try!(self.next()) {
+                        ':' => NGroup(box try!(self.parse_alt()), None),
+                        '=' => NLookAhead(box try!(self.parse_alt()), false),
+                        '!' => NLookAhead(box try!(self.parse_alt()), true),
+                        '<' => {
+                            let name = try!(self.parse_group_name());
+                            if self.names.iter().any(|&(ref other, _)| *other == name) {
+                                return Err(format!("Duplicate group name `{}` in regular expression", name));
+                            }
+                            let index = self.group_count;
+                            self.group_count += 1;
+                            self.names.push((name, index));
+                            NGroup(box try!(self.parse_alt()), Some(index))
+                        },
+                        ch => return Err(format!("Invalid group `(?{}` in regular expression", ch))
+                    }
+                } else {
+                    let index = self.group_count;
+                    self.group_count += 1;
+                    NGroup(box try!(self.parse_alt()), Some(index))
+                };
+                if !self.eat(')') {
+                    return Err("Unterminated group in regular expression".into_string());
+                }
+                Ok(node)
+            },
+            '[' => self.parse_class(),
+            '\\' => self.parse_escape(),
+            ')' => Err("Unmatched `)` in regular expression".into_string()),
+            '*' | '+' | '?' => Err("Nothing to repeat in regular expression".into_string()),
+            ch => Ok(NChar(ch))
+        }
+    }
+    fn parse_group_name(&mut self) -> Result<String, String> {
+        let mut name = String::new();
+        loop {
+            match try!(self.next()) {
+                '>' if name.len() > 0 => return Ok(name),
+                ch if is_word_char(ch) || ch == '$' => name.push_char(ch),
+                _ => return Err("Invalid group name in regular expression".into_string())
+            }
+        }
+    }
+    fn parse_class(&mut self) -> Result<Node, String> {
+        let negated = self.eat('^');
+        let mut items = Vec::new();
+        loop {
+            let start = match try!(self.next()) {
+                ']' => break,
+                '\\' => match try!(self.parse_class_escape()) {
+                    ClassRange(ch, _) => ch,
+                    item => {
+                        items.push(item);
+                        continue;
+                    }
+                },
+                ch => ch
+            };
+            if self.peek() == Some('-') && self.pos + 1 < self.chars.len() && self.chars[self.pos + 1] != ']' {
+                self.pos += 1;
+                let end = match try!(self.next()) {
+                    '\\' => match try!(self.parse_class_escape()) {
+                        ClassRange(ch, _) => ch,
+                        _ => return Err("Invalid character class range in regular expression".into_string())
+                    },
+                    ch => ch
+                };
+                if end < start {
+                    return Err("Range out of order in character class".into_string());
+                }
+                items.push(ClassRange(start, end));
+            } else {
+                items.push(ClassRange(start, start));
+            }
+        }
+        Ok(NClass(items, negated))
+    }
+    fn parse_class_escape(&mut self) -> Result<ClassItem, String> {
+        Ok(match try!(self.next()) {
+            'd' => ClassDigit(false),
+            'D' => ClassDigit(true),
+            'w' => ClassWord(false),
+            'W' => ClassWord(true),
+            's' => ClassSpace(false),
+            'S' => ClassSpace(true),
+            'b' => ClassRange('\x08', '\x08'),
+            '-' => ClassRange('-', '-'),
+            ch => {
+                let ch = try!(self.parse_char_escape(ch));
+                ClassRange(ch, ch)
+            }
+        })
+    }
+    fn parse_escape(&mut self) -> Result<Node, String> {
+        Ok(match try!(self.next()) {
+            'd' => NClass(vec!(ClassDigit(false)), false),
+            'D' => NClass(vec!(ClassDigit(true)), false),
+            'w' => NClass(vec!(ClassWord(false)), false),
+            'W' => NClass(vec!(ClassWord(true)), false),
+            's' => NClass(vec!(ClassSpace(false)), false),
+            'S' => NClass(vec!(ClassSpace(true)), false),
+            'b' => NWordBoundary(false),
+            'B' => NWordBoundary(true),
+            'k' if self.eat('<') => NNamedBackRef(try!(self.parse_group_name())),
+            ch if ch >= '1' && ch <= '9' => {
+                self.pos -= 1;
+                NBackRef(self.parse_decimal().unwrap())
+            },
+            ch => NChar(try!(self.parse_char_escape(ch)))
+        })
+    }
+    /// Parse an escape that stands for a single character, after the backslash and `ch`
+    fn parse_char_escape(&mut self, ch:char) -> Result<char, String> {
+        Ok(match ch {
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            'f' => '\x0c',
+            'v' => '\x0b',
+            '0' => '\0',
+            'c' => match try!(self.next()) {
+                letter if letter.is_alphabetic() => from_u32(letter as u32 % 32).unwrap(),
+                _ => return Err("Invalid control escape in regular expression".into_string())
+            },
+            'x' => try!(self.parse_hex(2)),
+            'u' if self.eat('{') => {
+                let start = self.pos;
+                while self.peek().map_or(false, |ch| ch != '}') {
+                    self.pos += 1;
+                }
+                let digits = String::from_chars(self.chars.slice(start, self.pos));
+                try!(self.next());
+                match from_str_radix::<u32>(digits.as_slice(), 16).and_then(from_u32) {
+                    Some(ch) => ch,
+                    None => return Err("Invalid unicode escape in regular expression".into_string())
+                }
+            },
+            'u' => try!(self.parse_hex(4)),
+            ch => ch
+        })
+    }
+    fn parse_hex(&mut self, digits:uint) -> Result<char, String> {
+        let mut text = String::new();
+        for _ in range(0, digits) {
+            text.push_char(try!(self.next()));
+        }
+        match from_str_radix::<u32>(text.as_slice(), 16).and_then(from_u32) {
+            Some(ch) => Ok(ch),
+            None => Err(format!("Invalid escape `{}` in regular expression", text))
+        }
+    }
+    /// Replace named backreferences with numbered ones, and check numbered ones refer to groups
+    fn resolve_names(&self, node:Node) -> Result<Node, String> {
+        Ok(match node {
+            NNamedBackRef(name) => match self.names.iter().find(|&&(ref other, _)| *other == name) {
+                Some(&(_, index)) => NBackRef(index),
+                None => return Err(format!("Unknown group name `{}` in regular expression", name))
+            },
+            NBackRef(index) if index >= self.group_count => return Err(format!("Unknown group {} in regular expression", index)),
+            NConcat(nodes) => NConcat(try!(self.resolve_all(nodes))),
+            NAlt(nodes) => NAlt(try!(self.resolve_all(nodes))),
+            NGroup(box node, index) => NGroup(box try!(self.resolve_names(node)), index),
+            NRepeat(box node, min, max, greedy) => NRepeat(box try!(self.resolve_names(node)), min, max, greedy),
+            NLookAhead(box node, negated) => NLookAhead(box try!(self.resolve_names(node)), negated),
+            node => node
+        })
+    }
+    fn resolve_all(&self, nodes:Vec<Node>) -> Result<Vec<Node>, String> {
+        let mut resolved = Vec::with_capacity(nodes.len());
+        for node in nodes.move_iter() {
+            resolved.push(try!(self.resolve_names(node)));
+        }
+        Ok(resolved)
+    }
+}
+/// Compiles nodes into instructions for the backtracking matcher
+struct PatternCompiler {
+    insts: Vec<Inst>,
+    /// The first slot after the group slots, where marks are stored
+    mark_base: uint,
+    mark_count: uint
+}
+impl PatternCompiler {
+    fn emit(&mut self, node:&Node) {
+        match *node {
+            NEmpty => (),
+            NChar(ch) => self.insts.push(IChar(ch)),
+            NAny => self.insts.push(IAny),
+            NClass(ref items, negated) => self.insts.push(IClass(items.clone(), negated)),
+            NStart => self.insts.push(IStart),
+            NEnd => self.insts.push(IEnd),
+            NWordBoundary(negated) => self.insts.push(IWordBoundary(negated)),
+            NConcat(ref nodes) => for node in nodes.iter() {
+                self.emit(node);
+            },
+            NAlt(ref nodes) => {
+                let mut jumps = Vec::new();
+                for (i, node) in nodes.iter().enumerate() {
+                    if i + 1 < nodes.len() {
+                        let split = self.insts.len();
+                        self.insts.push(ISplit(split + 1, 0));
+                        self.emit(node);
+                        jumps.push(self.insts.len());
+                        self.insts.push(IJump(0));
+                        let next = self.insts.len();
+                        *self.insts.get_mut(split) = ISplit(split + 1, next);
+                    } else {
+                        self.emit(node);
+                    }
+                }
+                let end = self.insts.len();
+                for jump in jumps.iter() {
+                    *self.insts.get_mut(*jump) = IJump(end);
+                }
+            },
+            NGroup(box ref node, Some(index)) => {
+                self.insts.push(ISave(index * 2));
+                self.emit(node);
+                self.insts.push(ISave(index * 2 + 1));
+            },
+            NGroup(box ref node, None) => self.emit(node),
+            NRepeat(box ref node, min, max, greedy) => {
+                for _ in range(0, min) {
+                    self.emit(node);
+                }
+                match max {
+                    None => {
+                        let mark = self.new_mark();
+                        let split = self.insts.len();
+                        self.insts.push(ISplit(0, 0));
+                        self.insts.push(IMark(mark));
+                        self.emit(node);
+                        self.insts.push(ICheckProgress(mark));
+                        self.insts.push(IJump(split));
+                        let end = self.insts.len();
+                        *self.insts.get_mut(split) = self.split(split + 1, end, greedy);
+                    },
+                    Some(max) => {
+                        let mut splits = Vec::new();
+                        for _ in range(min, max) {
+                            splits.push(self.insts.len());
+                            self.insts.push(ISplit(0, 0));
+                            self.emit(node);
+                        }
+                        let end = self.insts.len();
+                        for split in splits.iter() {
+                            *self.insts.get_mut(*split) = self.split(*split + 1, end, greedy);
+                        }
+                    }
+                }
+            },
+            NBackRef(index) => self.insts.push(IBackRef(index)),
+            NNamedBackRef(_) => unreachable!(),
+            NLookAhead(box ref node, negated) => {
+                let look = self.insts.len();
+                self.insts.push(ILookAhead(negated, 0));
+                self.emit(node);
+                self.insts.push(IMatch);
+                let next = self.insts.len();
+                *self.insts.get_mut(look) = ILookAhead(negated, next);
+            }
+        }
+    }
+    fn split(&self, body:uint, end:uint, greedy:bool) -> Inst {
+        if greedy {
+            ISplit(body, end)
+        } else {
+            ISplit(end, body)
+        }
+    }
+    fn new_mark(&mut self) -> uint {
+        self.mark_count += 1;
+        self.mark_base + self.mark_count - 1
+    }
+}
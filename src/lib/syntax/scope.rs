@@ -0,0 +1,369 @@
+use syntax::ast::expr::*;
+use syntax::ast::pattern::Params;
+use syntax::ast::op::{UnaryIncrementPre, UnaryIncrementPost, UnaryDecrementPre, UnaryDecrementPost, UnaryDelete};
+use syntax::ast::keyword::KSuper;
+use syntax::parser::{ParseError, Redeclaration, ConstAssignment, UnexpectedKeyword, StrictModeViolation};
+use syntax::ast::pos::Position;
+use std::mem::replace;
+/// The names declared in a block or function
+struct Scope {
+    /// The names declared in this scope and how they were declared
+    names: Vec<(String, DeclKind)>,
+    /// Whether this is the scope of a function or the script, which holds `var` declarations
+    function: bool
+}
+impl Scope {
+    fn find(&self, name:&String) -> Option<DeclKind> {
+        self.names.iter().find(|&&(ref other, _)| other == name).map(|&(_, kind)| kind)
+    }
+}
+/// Checks that names aren't declared twice in the same scope, that constants aren't assigned to,
+/// that `super` is only used inside classes, and that strict mode code doesn't declare or assign
+/// to `eval` or `arguments` or repeat a parameter name
+struct ScopeChecker {
+    /// The scopes enclosing the current expression, from outermost to innermost
+    scopes: Vec<Scope>,
+    /// Whether the current function is a class method, so it can refer to `super`
+    super_field: bool,
+    /// Whether the current function is the constructor of a derived class, so it can call `super()`
+    super_call: bool,
+    /// Whether the current function is strict mode code
+    strict: bool
+}
+impl ScopeChecker {
+    /// Check a function with the given parameters and body, or the script if there are no parameters
+    fn check_function(&mut self, params:&Params, body:&Expr) -> Result<(), ParseError> {
+        // a function is strict if it's inside strict mode code or its own body says it is, which
+        // applies to its parameters too
+        let strict = self.strict;
+        self.strict = strict || body.has_strict_directive();
+        let param_names = params.names();
+        if self.strict {
+            for (i, name) in param_names.iter().enumerate() {
+                if param_names.slice_to(i).contains(name) {
+                    self.strict = strict;
+                    return Err(StrictModeViolation(format!("Duplicate parameter {}", name), body.start));
+                }
+            }
+        }
+        let mut names : Vec<(String, DeclKind)> = param_names.move_iter().map(|name| (name, VarDecl)).collect();
+        collect_vars(body, &mut names);
+        self.scopes.push(Scope {names: names, function: true});
+        let mut result = if self.strict {
+            check_restricted(params.names(), "Declaring", body.start)
+        } else {
+            Ok(())
+        };
+        for expr in params.exprs().move_iter() {
+            if result.is_ok() {
+                result = self.check(expr);
+            }
+        }
+        if result.is_ok() {
+            result = match body.def {
+                BlockExpr(ref exprs) => self.check_statements(exprs.iter().collect::<Vec<&Expr>>().as_slice()),
+                _ => self.check(body)
+            };
+        }
+        self.scopes.pop();
+        self.strict = strict;
+        result
+    }
+    /// Check the constructor or a member of a class, which can refer to `super`
+    fn check_method(&mut self, func:&Expr, derived_constructor:bool) -> Result<(), ParseError> {
+        let super_field = replace(&mut self.super_field, true);
+        let super_call = replace(&mut self.super_call, derived_constructor);
+        let result = match func.def {
+            FunctionDeclExpr(_, ref params, box ref body) | GeneratorDeclExpr(_, ref params, box ref body) |
+            AsyncFunctionDeclExpr(_, ref params, box ref body) => self.check_function(params, body),
+            _ => self.check(func)
+        };
+        self.super_field = super_field;
+        self.super_call = super_call;
+        result
+    }
+    /// Check the parent, constructor and members of a class
+    fn check_class(&mut self, parent:&Option<Box<Expr>>, constructor:&Expr, members:&Vec<ClassMember>) -> Result<(), ParseError> {
+        match *parent {
+            Some(box ref parent) => try!(self.check(parent)),
+            None => ()
+        }
+        try!(self.check_method(constructor, parent.is_some()));
+        for member in members.iter() {
+            try!(self.check_method(&member.func, false));
+        }
+        Ok(())
+    }
+    /// Check statements in a new block scope
+    fn check_block(&mut self, statements:&[&Expr]) -> Result<(), ParseError> {
+        self.scopes.push(Scope {names: Vec::new(), function: false});
+        let result = self.check_statements(statements);
+        self.scopes.pop();
+        result
+    }
+    /// Declare the `let` and `const` declarations of some statements in the innermost scope, then check them
+    fn check_statements(&mut self, statements:&[&Expr]) -> Result<(), ParseError> {
+        for expr in statements.iter() {
+            let expr = declaration(*expr);
+            match expr.def {
+                VarDeclExpr(kind, ref vars) if kind != VarDecl => for &(ref pattern, _) in vars.iter() {
+                    for name in pattern.names().move_iter() {
+                        try!(self.declare(name, kind, expr));
+                    }
+                },
+                // a class declaration is scoped to the block like `let`
+                ClassDeclExpr(Some(ref name), _, _, _) => try!(self.declare(name.clone(), LetDecl, expr)),
+                // an imported binding can't be assigned to, like `const`
+                ImportExpr(ref specifiers, _) => for specifier in specifiers.iter() {
+                    try!(self.declare(specifier.local().clone(), ConstDecl, expr));
+                },
+                _ => ()
+            }
+        }
+        for expr in statements.iter() {
+            try!(self.check(*expr));
+        }
+        Ok(())
+    }
+    /// Declare a name in the innermost scope, which is an error if it's already declared there
+    fn declare(&mut self, name:String, kind:DeclKind, expr:&Expr) -> Result<(), ParseError> {
+        let scope = self.scopes.mut_last().unwrap();
+        if scope.find(&name).is_some() {
+            return Err(Redeclaration(name, expr.start));
+        }
+        scope.names.push((name, kind));
+        Ok(())
+    }
+    /// Find how the name that an expression refers to was declared
+    fn resolve(&self, name:&String) -> Option<DeclKind> {
+        self.scopes.iter().rev().filter_map(|scope| scope.find(name)).next()
+    }
+    /// Check that an expression doesn't assign to any constants among the names it assigns to
+    fn check_targets(&self, names:Vec<String>, expr:&Expr) -> Result<(), ParseError> {
+        for name in names.move_iter() {
+            if self.resolve(&name) == Some(ConstDecl) {
+                return Err(ConstAssignment(name, expr.start));
+            }
+        }
+        Ok(())
+    }
+    /// Check that strict mode code doesn't declare or assign to `eval` or `arguments`, or delete a binding
+    fn check_strict(&self, expr:&Expr) -> Result<(), ParseError> {
+        let (names, action) = match expr.def {
+            UnaryOpExpr(UnaryDelete, box Expr {def: LocalExpr(ref name), ..}) =>
+                return Err(StrictModeViolation(format!("Deleting the binding {}", name), expr.start)),
+            VarDeclExpr(_, ref vars) => (vars.iter().flat_map(|&(ref pattern, _)| pattern.names().move_iter()).collect(), "Declaring"),
+            FunctionDeclExpr(Some(ref name), _, _) | GeneratorDeclExpr(Some(ref name), _, _) |
+            AsyncFunctionDeclExpr(Some(ref name), _, _) | ClassDeclExpr(Some(ref name), _, _, _) |
+            TryExpr(_, Some((ref name, _)), _) => (vec!(name.clone()), "Declaring"),
+            AssignExpr(box ref target, _) | AssignOpExpr(_, box ref target, _) => (target.names(), "Assigning to"),
+            UnaryOpExpr(UnaryIncrementPre, box Expr {def: LocalExpr(ref name), ..}) |
+            UnaryOpExpr(UnaryIncrementPost, box Expr {def: LocalExpr(ref name), ..}) |
+            UnaryOpExpr(UnaryDecrementPre, box Expr {def: LocalExpr(ref name), ..}) |
+            UnaryOpExpr(UnaryDecrementPost, box Expr {def: LocalExpr(ref name), ..}) => (vec!(name.clone()), "Assigning to"),
+            _ => return Ok(())
+        };
+        check_restricted(names, action, expr.start)
+    }
+    fn check(&mut self, expr:&Expr) -> Result<(), ParseError> {
+        if self.strict {
+            try!(self.check_strict(expr));
+        }
+        match expr.def {
+            BlockExpr(ref exprs) => return self.check_block(exprs.iter().collect::<Vec<&Expr>>().as_slice()),
+            FunctionDeclExpr(_, ref params, box ref body) | GeneratorDeclExpr(_, ref params, box ref body) |
+            AsyncFunctionDeclExpr(_, ref params, box ref body) => {
+                // a plain function can't refer to the `super` of a method it's in, unlike an arrow function
+                let super_field = replace(&mut self.super_field, false);
+                let super_call = replace(&mut self.super_call, false);
+                let result = self.check_function(params, body);
+                self.super_field = super_field;
+                self.super_call = super_call;
+                return result;
+            },
+            ArrowFunctionDeclExpr(ref params, box ref body) | AsyncArrowFunctionDeclExpr(ref params, box ref body) =>
+                return self.check_function(params, body),
+            ClassDeclExpr(_, ref parent, box ref constructor, ref members) => {
+                // the whole of a class is strict mode code
+                let strict = replace(&mut self.strict, true);
+                let result = self.check_class(parent, constructor, members);
+                self.strict = strict;
+                return result;
+            },
+            ObjectDeclExpr(ref props) => {
+                for prop in props.iter() {
+                    match prop.key() {
+                        Some(&ComputedKey(ref key)) => try!(self.check(key)),
+                        _ => ()
+                    }
+                    // the methods, getters and setters of an object literal can refer to `super`
+                    match *prop {
+                        MethodProperty(_, _, ref func) => try!(self.check_method(func, false)),
+                        _ => try!(self.check(prop.expr()))
+                    }
+                }
+                return Ok(());
+            },
+            SuperExpr if !self.super_field =>
+                return Err(UnexpectedKeyword(KSuper, expr.start)),
+            CallExpr(box Expr {def: SuperExpr, start, ..}, ref args) => {
+                if !self.super_call {
+                    return Err(UnexpectedKeyword(KSuper, start));
+                }
+                for arg in args.iter() {
+                    try!(self.check(arg.expr()));
+                }
+                return Ok(());
+            },
+            VarDeclExpr(VarDecl, ref vars) => {
+                // a `var` is hoisted through the blocks up to its function, so it can't share a name with their lexical declarations
+                for name in vars.iter().flat_map(|&(ref pattern, _)| pattern.names().move_iter()) {
+                    for scope in self.scopes.iter().rev() {
+                        match scope.find(&name) {
+                            Some(kind) if kind != VarDecl => return Err(Redeclaration(name.clone(), expr.start)),
+                            _ if scope.function => break,
+                            _ => ()
+                        }
+                    }
+                }
+            },
+            ForLoopExpr(_, _, _, _) | ForInLoopExpr(_, _, _) =>
+                return self.check_block(expr.children().as_slice()),
+            SwitchExpr(box ref value, ref cases, ref default) => {
+                try!(self.check(value));
+                let mut statements : Vec<&Expr> = Vec::new();
+                for &(ref cond, ref block) in cases.iter() {
+                    statements.push(cond);
+                    statements.extend(block.iter());
+                }
+                match *default {
+                    Some(box Expr {def: BlockExpr(ref block), ..}) => statements.extend(block.iter()),
+                    Some(box ref default) => statements.push(default),
+                    None => ()
+                }
+                return self.check_block(statements.as_slice());
+            },
+            TryExpr(box ref body, ref catch, ref finally) => {
+                try!(self.check(body));
+                match *catch {
+                    Some((ref name, box ref catch)) => {
+                        self.scopes.push(Scope {names: vec!((name.clone(), VarDecl)), function: false});
+                        let result = self.check(catch);
+                        self.scopes.pop();
+                        try!(result);
+                    },
+                    None => ()
+                }
+                return match *finally {
+                    Some(box ref finally) => self.check(finally),
+                    None => Ok(())
+                };
+            },
+            AssignExpr(box ref target, _) | AssignOpExpr(_, box ref target, _) =>
+                try!(self.check_targets(target.names(), expr)),
+            UnaryOpExpr(UnaryIncrementPre, box Expr {def: LocalExpr(ref name), ..}) |
+            UnaryOpExpr(UnaryIncrementPost, box Expr {def: LocalExpr(ref name), ..}) |
+            UnaryOpExpr(UnaryDecrementPre, box Expr {def: LocalExpr(ref name), ..}) |
+            UnaryOpExpr(UnaryDecrementPost, box Expr {def: LocalExpr(ref name), ..}) =>
+                try!(self.check_targets(vec!(name.clone()), expr)),
+            _ => ()
+        }
+        for child in expr.children().move_iter() {
+            try!(self.check(child));
+        }
+        Ok(())
+    }
+}
+/// Collect the names declared by `var` and function declarations in a function body, without
+/// looking inside the functions it declares
+fn collect_vars(expr:&Expr, names:&mut Vec<(String, DeclKind)>) {
+    match expr.def {
+        VarDeclExpr(VarDecl, ref vars) => for &(ref pattern, _) in vars.iter() {
+            names.extend(pattern.names().move_iter().map(|name| (name, VarDecl)));
+        },
+        BlockExpr(ref exprs) => for expr in exprs.iter() {
+            match declaration(expr).def {
+                FunctionDeclExpr(Some(ref name), _, _) | GeneratorDeclExpr(Some(ref name), _, _) |
+                AsyncFunctionDeclExpr(Some(ref name), _, _) => names.push((name.clone(), VarDecl)),
+                _ => ()
+            }
+        },
+        _ => ()
+    }
+    match expr.def {
+        FunctionDeclExpr(_, _, _) | GeneratorDeclExpr(_, _, _) | AsyncFunctionDeclExpr(_, _, _) |
+        ArrowFunctionDeclExpr(_, _) | AsyncArrowFunctionDeclExpr(_, _) => (),
+        _ => for child in expr.children().move_iter() {
+            collect_vars(child, names);
+        }
+    }
+}
+/// Returns an error if any of the names declared or assigned to by strict mode code is `eval` or
+/// `arguments`, which `action` describes
+fn check_restricted(names:Vec<String>, action:&str, pos:Position) -> Result<(), ParseError> {
+    match names.iter().find(|name| name.as_slice() == "eval" || name.as_slice() == "arguments") {
+        Some(name) => Err(StrictModeViolation(format!("{} {}", action, name), pos)),
+        None => Ok(())
+    }
+}
+/// Get the declaration an export declaration exports, or the statement itself if it isn't one
+pub fn declaration<'a>(statement:&'a Expr) -> &'a Expr {
+    match statement.def {
+        ExportExpr(ExportDeclaration(box ref decl)) | ExportExpr(ExportDefault(box ref decl)) => decl,
+        _ => statement
+    }
+}
+/// Check the declarations in each scope of a script, which is an error if a name is declared
+/// twice in the same scope by `let` or `const`, or if a constant is assigned to
+///
+/// The script is checked as strict mode code from the start if `strict` is set, as a module is
+pub fn check_scopes(script:&Expr, strict:bool) -> Result<(), ParseError> {
+    let mut checker = ScopeChecker {scopes: Vec::new(), super_field: false, super_call: false, strict: strict};
+    checker.check_function(&Params::from_names(Vec::new()), script)
+}
+/// Get the names declared by `var` and function declarations in the body of a function or script,
+/// which are bound from the start of it
+pub fn var_names(body:&Expr) -> Vec<String> {
+    let mut vars = Vec::new();
+    collect_vars(body, &mut vars);
+    let mut names : Vec<String> = Vec::new();
+    for (name, _) in vars.move_iter() {
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    names
+}
+/// Get the names that the functions inside the parameters and body of a function or script refer
+/// to, whose bindings have to be kept where those functions can find them after it returns
+pub fn captured_names(params:&Params, body:&Expr) -> Vec<String> {
+    let mut names = Vec::new();
+    for expr in params.exprs().move_iter().chain(Some(body).move_iter()) {
+        collect_captured(expr, false, &mut names);
+    }
+    names
+}
+/// Collect the names referred to inside functions in an expression, which is inside a function
+/// already if `nested` is set
+fn collect_captured(expr:&Expr, nested:bool, names:&mut Vec<String>) {
+    let nested = nested || match expr.def {
+        FunctionDeclExpr(_, _, _) | GeneratorDeclExpr(_, _, _) | AsyncFunctionDeclExpr(_, _, _) |
+        ArrowFunctionDeclExpr(_, _) | AsyncArrowFunctionDeclExpr(_, _) => true,
+        _ => false
+    };
+    if nested {
+        let referred = match expr.def {
+            LocalExpr(ref name) => vec!(name.clone()),
+            AssignExpr(box ref target, _) | AssignOpExpr(_, box ref target, _) => target.names(),
+            _ => Vec::new()
+        };
+        for name in referred.move_iter() {
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+    }
+    for child in expr.children().move_iter() {
+        collect_captured(child, nested, names);
+    }
+}
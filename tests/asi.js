@@ -0,0 +1,37 @@
+// @description Automatic semicolon insertion unit tests
+var a = 1
+var b = 2
+assert(a + b == 3, "Insert semicolons at the ends of lines")
+var c = a
++ b
+assert(c == 3, "Carry an expression on when the next line continues it")
+function nothing() {
+    return
+    1
+}
+assert(nothing() === undefined, "Don't carry a return value onto the next line")
+var i = 1
+var j = 5
+i
+++j
+assert(i == 1 && j == 6, "A postfix operator can't start a line")
+outer: for (var x = 0; x < 3; x++) {
+    for (var y = 0; y < 3; y++) {
+        break
+        outer
+    }
+}
+assert(x == 3, "Don't carry a break label onto the next line")
+var count = 0
+do count++
+while (count < 3) count += 10
+assert(count == 13, "Insert a semicolon after a do-while loop")
+if (a == 1) {
+    a = 5
+} else {
+    a = 6
+}
+{}
+-1
+assert(a == 5, "Blocks don't carry on into expressions")
+var obj = {value: 1}; obj.value++; assert(obj.value == 2, "Statements on one line")
@@ -0,0 +1,51 @@
+// @description Unsigned shift, exponentiation and compound assignment unit tests
+assert(-1 >>> 0 == 4294967295, "Unsigned shift gives an unsigned result");
+assert(-16 >>> 2 == 1073741820, "Unsigned shift fills in zeroes");
+assert(2 ** 10 == 1024, "Raise to a power");
+assert(2 ** 3 ** 2 == 512, "Exponentiation groups rightwards");
+assert(2 * 3 ** 2 == 18, "Exponentiation binds more tightly than multiplication");
+assert(1 && 2 == 2 && 3 << 1 == 6, "Two-character operators are read whole");
+var a = 10;
+a += 5;
+assert(a == 15, "Add and assign");
+a -= 3;
+assert(a == 12, "Subtract and assign");
+a *= 2;
+assert(a == 24, "Multiply and assign");
+a /= 4;
+assert(a == 6, "Divide and assign");
+a %= 4;
+assert(a == 2, "Take the remainder and assign");
+a **= 3;
+assert(a == 8, "Raise to a power and assign");
+a <<= 2;
+assert(a == 32, "Shift leftwards and assign");
+a >>= 1;
+assert(a == 16, "Shift rightwards and assign");
+a >>>= 2;
+assert(a == 4, "Shift rightwards unsigned and assign");
+a |= 3;
+assert(a == 7, "Or and assign");
+a &= 5;
+assert(a == 5, "And and assign");
+a ^= 1;
+assert(a == 4, "Xor and assign");
+var obj = {count: 1, items: [1, 2]};
+obj.count += 2;
+assert(obj.count == 3, "Assign to a field");
+obj.items[1] *= 10;
+assert(obj.items[1] == 20, "Assign to a computed member");
+var text = "a";
+text += "b";
+assert(text == "ab", "Concatenate and assign");
+var calls = 0;
+var key = function() {
+    calls++;
+    return 0;
+};
+var list = [5];
+list[key()] += 1;
+assert(list[0] == 6 && calls == 1, "The key of a compound assignment is evaluated once");
+var n = 1;
+n += (n = 10);
+assert(n == 11, "The target is read before the value");
@@ -0,0 +1,49 @@
+// @description Class unit tests
+class Point {
+    constructor(x, y) {
+        this.x = x;
+        this.y = y;
+    }
+    sum() {
+        return this.x + this.y;
+    }
+    get total() {
+        return this.sum();
+    }
+    set total(value) {
+        this.x = value;
+        this.y = 0;
+    }
+    static origin() {
+        return new Point(0, 0);
+    }
+}
+var p = new Point(1, 2);
+assert(p.x == 1 && p.y == 2, "Run the constructor");
+assert(p.sum() == 3, "Call a method from the prototype");
+assert(p.total == 3, "Call a getter");
+p.total = 5;
+assert(p.x == 5 && p.y == 0, "Call a setter");
+assert(Point.origin().x == 0, "Call a static method");
+assert(Object.getPrototypeOf(p) == Point.prototype, "Instances inherit from the prototype");
+class Point3D extends Point {
+    constructor(x, y, z) {
+        super(x, y);
+        this.z = z;
+    }
+    sum() {
+        return super.sum() + this.z;
+    }
+}
+var q = new Point3D(1, 2, 3);
+assert(q.sum() == 6, "Call the parent method through super");
+assert(q.total == 6, "Inherit a getter");
+class Named extends Point {}
+var n = new Named(4, 5);
+assert(n.x == 4 && n.y == 5, "The default constructor passes its arguments to the parent");
+var Anonymous = class {
+    value() {
+        return 7;
+    }
+};
+assert(new Anonymous().value() == 7, "Class expressions");
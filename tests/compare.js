@@ -0,0 +1,18 @@
+// @description Equality, comparison and truthiness unit tests
+assert(1 == "1" && "1" == 1, "Loose equality converts strings to numbers");
+assert(!(1 === "1") && 1 !== "1", "Strict equality doesn't convert");
+assert(!(1 === true) && 1 == true, "A boolean isn't strictly equal to a number");
+assert(null == undefined && null !== undefined, "null and undefined are only loosely equal");
+var a = {};
+var b = a;
+assert(a == b && a === b && a != {}, "Objects are equal only to themselves");
+assert("apple" < "banana" && "b" > "a" && !("a" > "b"), "Strings compare by their characters");
+assert("10" < "9" && 10 > 9, "Strings compare differently to numbers");
+assert(!(NaN < 1) && !(NaN >= 1), "Nothing is ordered against NaN");
+assert(!0 && !"" && !null && !undefined && !NaN, "Falsy values");
+assert(!!2 && !!"0" && !!{} && !!0.5, "Truthy values");
+var x = 1;
+x = "one";
+assert(x == "one", "A local can change from a number to a string");
+x = x + 1;
+assert(x === "one1", "Adding to a string joins them");
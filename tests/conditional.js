@@ -0,0 +1,18 @@
+// @description Conditional operator unit tests
+var yes = true;
+var no = false;
+assert((yes ? 1 : 2) == 1, "Take the first branch when the condition is true");
+assert((no ? 1 : 2) == 2, "Take the second branch when the condition is false");
+var grade = 75;
+var letter = grade > 90 ? "A" : grade > 70 ? "B" : "C";
+assert(letter == "B", "Conditionals group to the right");
+var sum = 1 + 2 == 3 ? "three" : "other";
+assert(sum == "three", "Binary operators bind more tightly than the conditional");
+assert((!yes ? "not" : "is") == "is", "Prefix operators bind more tightly than the conditional");
+var mixed = no ? 1 : "string";
+assert(mixed == "string", "Branches can have different types");
+var nested = yes ? no ? 1 : 2 : 3;
+assert(nested == 2, "Nest a conditional in the first branch");
+var assigned;
+assigned = no || yes ? "either" : "neither";
+assert(assigned == "either", "Logical operators bind more tightly than the conditional");
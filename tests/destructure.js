@@ -0,0 +1,28 @@
+// @description Destructuring unit tests
+var [a, b] = [1, 2];
+assert(a == 1 && b == 2, "Destructure an array");
+var [, c, , d] = [1, 2, 3, 4];
+assert(c == 2 && d == 4, "Skip holes in an array pattern");
+var [e, f = 5] = [1];
+assert(f == 5, "Use the default for a missing item");
+var [g, ...rest] = [1, 2, 3];
+assert(rest.length == 2 && rest[1] == 3, "Collect the rest of an array");
+var {x, y: z} = {x: 1, y: 2};
+assert(x == 1 && z == 2, "Destructure an object");
+var {w = 3} = {};
+assert(w == 3, "Use the default for a missing field");
+let {p, ...others} = {p: 1, q: 2, r: 3};
+assert(others.q == 2 && others.r == 3 && others.p == undefined, "Collect the rest of an object");
+const {outer: {inner: [first]}} = {outer: {inner: [7]}};
+assert(first == 7, "Destructure nested patterns");
+[a, b] = [b, a];
+assert(a == 2 && b == 1, "Swap with an assignment pattern");
+var obj = {};
+[obj.one, obj["two"]] = [1, 2];
+assert(obj.one == 1 && obj.two == 2, "Assign to fields in an assignment pattern");
+function sum([left, right], {scale}) {
+    return (left + right) * scale;
+}
+assert(sum([1, 2], {scale: 2}) == 6, "Destructure parameters");
+var pair = ([left, right]) => left - right;
+assert(pair([3, 1]) == 2, "Destructure arrow function parameters");
@@ -0,0 +1,53 @@
+// @description Exception unit tests
+var caught = 0;
+try {
+    throw 5;
+} catch (e) {
+    caught = e;
+}
+assert(caught == 5, "Catch thrown value");
+var cleaned = false;
+try {
+    caught = 1;
+} finally {
+    cleaned = true;
+}
+assert(cleaned, "Finally runs without throw");
+var order = "";
+try {
+    try {
+        throw "inner";
+    } finally {
+        order = order + "finally,";
+    }
+} catch (e) {
+    order = order + e;
+}
+assert(order == "finally,inner", "Finally runs before outer catch");
+var unbound = false;
+try {
+    missing + 1;
+} catch (e) {
+    unbound = e instanceof ReferenceError;
+}
+assert(unbound, "Using a name that isn't defined throws");
+implicit = 3;
+assert(implicit == 3, "Assigning to a name that isn't declared makes a global");
+var e = "outer";
+try {
+    throw "inner";
+} catch (e) {
+}
+assert(e == "outer", "Catch parameter is scoped to the catch block");
+var finished = 0;
+for (var i = 0; i < 3; i++) {
+    try {
+        if (i == 0) {
+            continue;
+        }
+        break;
+    } finally {
+        finished++;
+    }
+}
+assert(finished == 2, "Finally runs on break and continue");
@@ -0,0 +1,62 @@
+// @description Function unit tests
+function add(a, b) {
+    return a + b;
+}
+assert(add(1, 2) == 3, "Call a function with arguments");
+assert(hoisted() == "hoisted", "Call a function before its declaration");
+function hoisted() {
+    return "hoisted";
+}
+function factorial(n) {
+    return n <= 1 ? 1 : n * factorial(n - 1);
+}
+assert(factorial(5) == 120, "Call a function recursively");
+function counter() {
+    var count = 0;
+    return function() {
+        count++;
+        return count;
+    };
+}
+var next = counter();
+next();
+assert(next() == 2, "Keep the bindings a closure refers to between calls");
+assert(counter()() == 1, "Give each call its own bindings");
+var fib = function inner(n) {
+    return n < 2 ? n : inner(n - 1) + inner(n - 2);
+};
+assert(fib(10) == 55, "Refer to a named function expression by its name");
+var makers = [];
+for (var i = 0; i < 3; i++) {
+    let j = i;
+    makers[i] = () => j;
+}
+assert(makers[0]() == 0 && makers[2]() == 2, "Give each run of a block its own bindings");
+var holder = {
+    value: 4,
+    get: function() {
+        var read = () => this.value;
+        return read();
+    }
+};
+assert(holder.get() == 4, "Arrow functions use the this of the function they're in");
+function count() {
+    return arguments.length;
+}
+assert(count(1, 2, 3) == 3, "Functions get their arguments");
+function Box(value) {
+    this.value = value;
+}
+Box.prototype.get = function() {
+    return this.value;
+};
+assert(new Box(6).get() == 6, "Construct an object with a function");
+function sloppy() {
+    return this;
+}
+assert(sloppy() !== undefined, "Call a function that isn't strict with the global object as this");
+function strict() {
+    "use strict";
+    return this;
+}
+assert(strict() === undefined, "Call a strict function with this as it's given");
@@ -0,0 +1,56 @@
+// @description Generator unit tests
+function* count(to) {
+    for (var i = 0; i < to; i++) {
+        yield i;
+    }
+    return "done";
+}
+var counter = count(2);
+var first = counter.next();
+assert(first.value === 0 && first.done === false, "Yield the first value");
+assert(counter.next().value === 1, "Resume after a yield");
+var last = counter.next();
+assert(last.value === "done" && last.done === true, "Return when the body finishes");
+assert(counter.next().done === true, "Stay finished");
+function* echo() {
+    var received = yield "ready";
+    yield received * 2;
+}
+var echoer = echo();
+echoer.next();
+assert(echoer.next(21).value === 42, "Resume with the value passed to next");
+function* guarded() {
+    try {
+        yield 1;
+    } finally {
+        cleaned = true;
+    }
+}
+var cleaned = false;
+var guard = guarded();
+guard.next();
+var returned = guard.return(5);
+assert(returned.value === 5 && returned.done && cleaned, "Run finally blocks when returned from");
+var thrower = count(3);
+thrower.next();
+var caught = false;
+try {
+    thrower.throw("stop");
+} catch (e) {
+    caught = e === "stop";
+}
+assert(caught && thrower.next().done, "Throw into a generator");
+function* both() {
+    yield* count(2);
+    yield 2;
+}
+var all = both();
+assert(all.next().value === 0 && all.next().value === 1 && all.next().value === 2, "Delegate to another generator");
+function* keys(obj) {
+    for (var key in obj) {
+        let seen = key;
+        yield seen;
+    }
+}
+var keyer = keys({a: 1, b: 2});
+assert(keyer.next().value === "a" && keyer.next().value === "b" && keyer.next().done, "Keep the state of a loop and a block between yields");
@@ -0,0 +1,35 @@
+// @description Break, continue and label unit tests
+var total = 0;
+for (var i = 0; i < 10; i = i + 1) {
+    if (i == 5) break;
+    if (i == 1) continue;
+    total = total + i;
+}
+assert(total == 9, "Break and continue");
+var found = 0;
+outer: for (var x = 0; x < 3; x = x + 1) {
+    for (var y = 0; y < 3; y = y + 1) {
+        if (y == 1) continue outer;
+        if (x == 2) break outer;
+        found = found + 1;
+    }
+}
+assert(found == 2, "Labelled break and continue");
+var result = "";
+switch (2) {
+    case 1:
+        result = "one";
+        break;
+    case 2:
+        result = "two";
+        break;
+    default:
+        result = "other";
+}
+assert(result == "two", "Switch break");
+var inner = 0;
+a: b: for (var k = 0; k < 3; k = k + 1) {
+    inner = inner + 1;
+    continue a;
+}
+assert(inner == 3, "Continue a loop through a label on its label");
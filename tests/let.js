@@ -0,0 +1,33 @@
+// @description Let and const unit tests
+let a = 1;
+{
+    let a = 2;
+    assert(a == 2, "Inner let shadows outer let");
+}
+assert(a == 1, "Outer let is restored after the block");
+const b = 3;
+assert(b == 3, "Read a constant");
+var total = 0;
+for (let i = 0; i < 3; i = i + 1) {
+    total = total + i;
+}
+assert(total == 3, "Let in a for loop header");
+var threw = false;
+try {
+    c = 1;
+    let c = 2;
+} catch (e) {
+    threw = true;
+}
+assert(threw, "Assigning before the declaration throws");
+threw = false;
+try {
+    d;
+    const d = 4;
+} catch (e) {
+    threw = true;
+}
+assert(threw, "Reading before the declaration throws");
+var let = 5;
+let = let + 1;
+assert(let == 6, "Use let as a name when no declaration follows it");
@@ -0,0 +1,35 @@
+// @description Loop unit tests
+var total = 0;
+for (var i = 0; i < 5; i = i + 1) {
+    total = total + i;
+}
+assert(total == 10, "For loop");
+var count = 0;
+do {
+    count = count + 1;
+} while (count < 3);
+assert(count == 3, "Do-while loop");
+var once = 0;
+do {
+    once = once + 1;
+} while (false);
+assert(once == 1, "Do-while loop runs at least once");
+var keys = "";
+for (var key in {a: 1, b: 2}) {
+    keys = keys + key;
+}
+assert(keys == "ab", "For-in loop");
+var visited = "";
+var letters = {a: 1, b: 2, c: 3};
+for (var key in letters) {
+    visited = visited + key;
+    delete letters.c;
+    letters.d = 4;
+}
+assert(visited == "ab", "For-in loop skips deleted fields and doesn't visit added ones");
+var inherited = "";
+var child = Object.setPrototypeOf({own: 1}, {parent: 2});
+for (var key in child) {
+    inherited = inherited + key + ",";
+}
+assert(inherited == "own,parent,", "For-in loop visits inherited fields");
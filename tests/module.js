@@ -0,0 +1,16 @@
+// @description Module unit tests
+// @type module
+import Square, {sides, count, add as increment, total, grow} from "./modules/shapes.mjs";
+import * as all from "./modules/all.mjs";
+import answer from "./modules/all.mjs";
+assert(sides === 4, "Import a named export");
+assert(new Square(2).size === 2, "Import a default export");
+assert(count === 0, "Import a binding before it changes");
+increment();
+assert(count === 1, "See changes to imported bindings");
+assert(all.sides === 4 && all.corners === 4, "Re-export all exports and renamed exports");
+assert(all.Shape === Square, "Re-export a default export under a name");
+assert(all.shown === true, "Export a local binding under another name");
+assert(answer === 42 && all.default === 42, "Export an expression as the default export");
+grow();
+assert(total === 2, "See changes to imported let bindings");
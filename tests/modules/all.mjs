@@ -0,0 +1,5 @@
+export * from "./shapes.mjs";
+export {sides as corners, default as Shape} from "./shapes.mjs";
+var hidden = true;
+export {hidden as shown};
+export default 42;
@@ -0,0 +1,14 @@
+export const sides = 4;
+export var count = 0;
+export function add() {
+    count++;
+}
+export let total = 0;
+export function grow() {
+    total += 2;
+}
+export default class Square {
+    constructor(size) {
+        this.size = size;
+    }
+}
@@ -9,3 +9,6 @@ assert((5 - 2) == 3, "Number subtraction");
 assert((5 / 2) == 2.5, "Number division");
 assert((5 * 2) == 10, "Number multiplication");
 assert((3 + 4 * 5) == 23, "Operator precedence");
+assert(2 * 3 - 1 == 5, "Operands keep their order when regrouped");
+assert(2 * (3 + 1) == 8, "Brackets group operations");
+assert(-(2 + 3) == -5, "A prefix operator applies to a whole expression in brackets");
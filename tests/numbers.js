@@ -0,0 +1,23 @@
+// @description Numeric literal unit tests
+assert(1e3 == 1000 && 2E2 == 200, "Exponents");
+assert(1e-3 == 0.001 && 5e+1 == 50, "Signed exponents");
+assert(.5 == 0.5 && .25e1 == 2.5, "Numbers starting with a dot");
+assert(1.5 + 1. == 2.5, "A trailing dot");
+assert(0x1F == 31 && 0XfF == 255, "Hexadecimal");
+assert(0b101 == 5 && 0B11 == 3, "Binary");
+assert(0o17 == 15 && 0O7 == 7, "Octal");
+assert(017 == 15 && 019 == 19, "Legacy octal falls back to decimal");
+assert(1_000_000 == 1000000 && 0b1010_1010 == 170 && 1_0.2_5 == 10.25, "Numeric separators");
+assert(1 / 2 == 0.5, "Integers divide into fractions");
+assert(2147483647 + 1 == 2147483648, "Integers don't overflow");
+assert(4294967296 == 2 ** 32, "Integers that don't fit");
+var x = 1;
+x += 0.5;
+assert(x == 1.5, "An integer local can become a fraction");
+var bigInt = false;
+try {
+    0xffn;
+} catch (e) {
+    bigInt = e instanceof TypeError;
+}
+assert(bigInt, "BigInt literals parse but throw when they're used");
@@ -1,2 +1,41 @@
 // @description Object unit tests
-assert(Object.prototype == {}.__proto__, "Object prototype");
\ No newline at end of file
+assert(Object.prototype == {}.__proto__, "Object prototype");
+var a = 1, b = 2, key = "c";
+var o = {a, b, [key]: 3, "d e": 4, 5: "five", 1.5: "half"};
+assert(o.a == 1 && o.b == 2, "Shorthand properties");
+assert(o.c == 3, "Computed keys");
+assert(o["d e"] == 4, "String keys");
+assert(o[5] == "five" && o["1.5"] == "half", "Numeric keys");
+var counter = {
+    count: 0,
+    get double() {
+        return this.count * 2;
+    },
+    set double(value) {
+        this.count = value / 2;
+    },
+    increment() {
+        this.count++;
+        return this.count;
+    }
+};
+assert(counter.increment() == 1, "Shorthand methods");
+assert(counter.double == 2, "Getters");
+counter.double = 10;
+assert(counter.count == 5, "Setters");
+var copy = {...o, b: 20, ...null};
+assert(copy.a == 1 && copy.b == 20 && copy.c == 3, "Spread properties");
+var {a: first, ...others} = copy;
+assert(first == 1 && others.a == undefined && others.b == 20, "Rest of an object pattern");
+var base = {
+    greet() {
+        return "hello";
+    }
+};
+var derived = {
+    greet() {
+        return super.greet() + " there";
+    }
+};
+Object.setPrototypeOf(derived, base);
+assert(derived.greet() == "hello there", "Methods call the methods they override through super");
@@ -0,0 +1,36 @@
+// @description in, instanceof, delete and void unit tests
+var obj = {a: 1, b: 2};
+assert("a" in obj, "Find a field with in");
+assert(!("c" in obj), "Don't find a missing field with in");
+class Animal {
+    speak() {
+        return "...";
+    }
+}
+class Dog extends Animal {}
+var dog = new Dog();
+assert("speak" in dog, "Find a field through the prototype chain with in");
+assert(dog instanceof Dog, "An instance of its own class");
+assert(dog instanceof Animal, "An instance of the parent class");
+assert(!(dog instanceof Error), "Not an instance of an unrelated class");
+assert(!(1 instanceof Animal), "A primitive isn't an instance of anything");
+assert(delete obj.a, "Delete a field");
+assert(!("a" in obj) && obj.b == 2, "Deleting removes only that field");
+assert(delete obj["missing"], "Deleting a missing field succeeds");
+assert(!delete Math.PI, "Can't delete a field that isn't configurable");
+assert(void 0 === undefined, "void gives undefined");
+var count = 0;
+void count++;
+assert(count == 1, "void evaluates its operand");
+var keys = [];
+for (var key in {x: 1}) {
+    keys.push(key);
+}
+assert(keys.length == 1 && keys[0] == "x", "in still starts a for-in loop");
+var threw = false;
+try {
+    "a" in 1;
+} catch (e) {
+    threw = e instanceof TypeError;
+}
+assert(threw, "in throws a TypeError for a value that isn't an object");
@@ -0,0 +1,80 @@
+// @description Promise and async function unit tests
+var order = [];
+var resolved = new Promise(function(resolve) {
+    order.push("executor");
+    resolve(1);
+});
+resolved.then(function(value) {
+    order.push("then");
+    assert(value === 1, "Fulfill with the value resolved with");
+});
+order.push("script");
+assert(order[0] === "executor" && order[1] === "script" && order.length === 2, "Run reactions after the script");
+Promise.reject("no").then(function() {
+    assert(false, "Skip fulfillment handlers of rejected promises");
+}).catch(function(reason) {
+    assert(reason === "no", "Pass rejections on to the next handler");
+});
+Promise.resolve(2).then(function(value) {
+    return value * 3;
+}).then(function(value) {
+    assert(value === 6, "Chain the values handlers return");
+    throw "oops";
+}).catch(function(reason) {
+    assert(reason === "oops", "Reject with what a handler throws");
+});
+var thenable = {
+    then: function(resolve) {
+        resolve("followed");
+    }
+};
+Promise.resolve(thenable).then(function(value) {
+    assert(value === "followed", "Follow values with a then method");
+});
+new Promise(function() {
+    throw "thrown";
+}).catch(function(reason) {
+    assert(reason === "thrown", "Reject when the executor throws");
+});
+async function add(a, b) {
+    var first = await a;
+    return first + await b;
+}
+add(Promise.resolve(1), 2).then(function(sum) {
+    assert(sum === 3, "Await promises and plain values");
+});
+var double = async x => x * 2;
+double(4).then(function(value) {
+    assert(value === 8, "Resolve with what an async arrow function returns");
+});
+async function fails() {
+    try {
+        await Promise.reject("caught");
+    } catch (e) {
+        return e;
+    }
+}
+fails().then(function(value) {
+    assert(value === "caught", "Throw rejections from await");
+});
+var async = 1;
+var await = async + 1;
+assert(await === 2, "Use async and await as names outside async functions");
+async function pair() {
+    return [await 1, await Promise.resolve(2)];
+}
+pair().then(function(values) {
+    assert(values[0] === 1 && values[1] === 2, "Keep values worked out before an await");
+});
+async function rejects(value = missing) {
+}
+rejects().catch(function(reason) {
+    assert(reason instanceof ReferenceError, "Reject when binding the parameters throws");
+});
+var __jobs__ = null;
+var queued = false;
+Promise.resolve().then(function() {
+    queued = true;
+}).then(function() {
+    assert(queued, "Keep the job queue out of reach of scripts");
+});
@@ -0,0 +1,29 @@
+// @description Regular expression unit tests
+assert(/a(b+)c/.test("xabbcx"), "Test matches inside a string");
+assert(!/^abc$/.test("abcd"), "Anchors match the whole string");
+var found = /(\d+)-(\d+)/.exec("from 12-34");
+assert(found[0] == "12-34", "Exec returns the whole match");
+assert(found[2] == "34", "Exec returns capture groups");
+assert(found.index == 5, "Exec returns the match index");
+var date = /(?<year>\d{4})-(?<month>\d{2})/.exec("2014-07");
+assert(date.groups.month == "07", "Named groups are captured");
+assert(/a+?/.exec("aaa")[0] == "a", "Lazy quantifiers match as little as possible");
+assert(/ABC/i.test("abc"), "Ignore case flag");
+assert("a1b2c3".replace(/\d/g, "#") == "a#b#c#", "Global replace");
+assert("john smith".replace(/(\w+) (\w+)/, "$2 $1") == "smith john", "Replace with group references");
+assert("abc".search(/c/) == 2, "Search returns the match index");
+var parts = "a, b,c".split(/,\s*/);
+assert(parts.length == 3 && parts[1] == "b", "Split by regular expression");
+assert("x1y22z".match(/\d+/g).length == 2, "Global match returns every match");
+assert(/u/.test(), "Test without an argument tests \"undefined\"");
+assert(/1/.exec(1)[0] == "1", "Exec converts its argument to a string");
+var fixed = /a/;
+fixed.source = "b";
+assert(fixed.test("a") && !fixed.test("b"), "Changing source doesn't change what is matched");
+var invalid = false;
+try {
+    new RegExp("(");
+} catch (e) {
+    invalid = e instanceof SyntaxError;
+}
+assert(invalid, "An invalid pattern throws a SyntaxError");
@@ -0,0 +1,21 @@
+// @description Spread, rest parameter and default parameter unit tests
+var nums = [1, 5, 3];
+assert(Math.max(...nums) == 5, "Spread an array into a call");
+assert(Math.max(0, ...nums, 9) == 9, "Spread between other arguments");
+var joined = [0, ...nums, ...[7, 8]];
+assert(joined.length == 6 && joined[1] == 1 && joined[5] == 8, "Spread arrays into an array literal");
+var chars = [..."abc"];
+assert(chars.length == 3 && chars[2] == "c", "Spread a string into its characters");
+var err = new Error(...["spread message"]);
+assert(err.message == "spread message", "Spread into a constructor");
+function tail(first, ...others) {
+    return others.length;
+}
+assert(tail(1, 2, 3) == 2, "Collect the remaining arguments");
+function greet(name = "world", punctuation = name == "world" ? "!" : ".") {
+    return "hello " + name + punctuation;
+}
+assert(greet() == "hello world!", "Use default parameters for missing arguments");
+assert(greet("you") == "hello you.", "Defaults can refer to earlier parameters");
+var count = (...items) => items.length;
+assert(count(1, 2) == 2, "Rest parameters in an arrow function");
@@ -0,0 +1,18 @@
+// @description Strict mode tests
+"use strict";
+var point = {
+    get x() {
+        return 1;
+    }
+};
+var threw = false;
+try {
+    point.x = 2;
+} catch(e) {
+    threw = e instanceof TypeError;
+}
+assert(threw, "Assigning to a field without a setter throws");
+assert(point.x == 1, "Failed assignment leaves the field alone");
+var writable = {x: 1};
+writable.x = 2;
+assert(writable.x == 2, "Assigning to a writable field");
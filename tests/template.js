@@ -0,0 +1,15 @@
+// @description Template literal unit tests
+var name = "world";
+assert(`hello ${name}!` == "hello world!", "Substitute an expression");
+assert(`${1 + 2}${"a"}` == "3a", "Substitute adjacent expressions");
+assert(`line one
+line two` == "line one\nline two", "Keep multi-line content");
+assert(`a\tb` == "a\tb", "Cook escapes");
+function raw(strings) {
+    return strings.raw[0];
+}
+assert(raw`a\tb` == "a\\tb", "Pass raw strings to tags");
+function parts(strings, a, b) {
+    return strings.length + a + b;
+}
+assert(parts`x${1}y${2}z` == 6, "Pass strings and values to tags");
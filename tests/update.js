@@ -0,0 +1,20 @@
+// @description Increment and decrement unit tests
+var i = 1;
+assert(i++ == 1 && i == 2, "Postfix increment gives the old value");
+assert(++i == 3 && i == 3, "Prefix increment gives the new value");
+assert(i-- == 3 && i == 2, "Postfix decrement gives the old value");
+assert(--i == 1 && i == 1, "Prefix decrement gives the new value");
+assert(++i < 3, "Prefix increment binds more tightly than comparison");
+var total = 0;
+for (var j = 0; j < 4; j++) {
+    total += j;
+}
+assert(total == 6, "Count with a loop");
+var obj = {count: 5, items: [1, 2]};
+assert(obj.count++ == 5 && obj.count == 6, "Increment a field");
+assert(--obj.items[1] == 1 && obj.items[1] == 1, "Decrement a computed member");
+var text = "4";
+text++;
+assert(text === 5, "Increment converts to a number");
+var a = 3, b = 2;
+assert(a - -b == 5, "Tell two minuses apart from a decrement");